@@ -0,0 +1,93 @@
+/// 图片 BlurHash 缓存
+/// 图片经过代理时计算，按 ImageTag 保存（.image_blurhash.json），映射 BaseItemDto 时填入 ImageBlurHashes
+
+use dashmap::DashMap;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::{debug, info, warn};
+
+use super::persist::{write_json_atomic, DeferredSave};
+use crate::imaging::blurhash;
+
+/// ImageTag → BlurHash
+static BLURHASHES: LazyLock<DashMap<String, String>> = LazyLock::new(|| {
+    let map = DashMap::new();
+    if let Some(data) = load_blurhashes_from_file() {
+        for (k, v) in data {
+            map.insert(k, v);
+        }
+        info!("[IMAGE] 已恢复 {} 个 BlurHash", map.len());
+    }
+    map
+});
+
+/// 正在计算的标签
+static PENDING: LazyLock<DashMap<String, ()>> = LazyLock::new(DashMap::new);
+
+/// 无法计算的标签（无法解码的格式等），本次运行内不再重试
+static FAILED: LazyLock<DashMap<String, ()>> = LazyLock::new(DashMap::new);
+
+static SAVE: DeferredSave = DeferredSave::new("IMAGE", save_blurhashes);
+
+fn blurhash_file_path() -> PathBuf {
+    PathBuf::from(".image_blurhash.json")
+}
+
+fn load_blurhashes_from_file() -> Option<std::collections::HashMap<String, String>> {
+    let content = std::fs::read_to_string(blurhash_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_blurhashes() -> std::io::Result<()> {
+    let data: std::collections::BTreeMap<String, String> = BLURHASHES
+        .iter()
+        .map(|e| (e.key().clone(), e.value().clone()))
+        .collect();
+    write_json_atomic(&blurhash_file_path(), &data)
+}
+
+pub fn get_blur_hash(tag: &str) -> Option<String> {
+    BLURHASHES.get(tag).map(|v| v.clone())
+}
+
+/// 计算结束（包括 panic 或任务取消）时移出 PENDING
+struct PendingGuard(String);
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        PENDING.remove(&self.0);
+    }
+}
+
+/// 尚未计算时在后台线程计算图片的 BlurHash
+pub fn ensure_blur_hash(tag: &str, data: &[u8]) {
+    if BLURHASHES.contains_key(tag) || FAILED.contains_key(tag) {
+        return;
+    }
+    match PENDING.entry(tag.to_string()) {
+        dashmap::Entry::Occupied(_) => return,
+        dashmap::Entry::Vacant(slot) => {
+            slot.insert(());
+        }
+    }
+    let guard = PendingGuard(tag.to_string());
+    let data = data.to_vec();
+    tokio::spawn(async move {
+        let tag = guard.0.clone();
+        match tokio::task::spawn_blocking(move || blurhash::from_bytes(&data)).await {
+            Ok(Some(hash)) => {
+                debug!("[IMAGE] BlurHash: tag={}, hash={}", tag, hash);
+                BLURHASHES.insert(tag, hash);
+                SAVE.schedule();
+            }
+            Ok(None) => {
+                FAILED.insert(tag, ());
+            }
+            Err(e) => {
+                warn!("[IMAGE] BlurHash 计算失败: tag={}, {}", tag, e);
+                FAILED.insert(tag, ());
+            }
+        }
+        drop(guard);
+    });
+}
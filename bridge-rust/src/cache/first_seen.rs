@@ -0,0 +1,61 @@
+/// 项目首次发现时间
+/// 飞牛接口不返回入库时间，以桥接首次在列表中看到该 GUID 的时间作为添加时间（.item_first_seen.json），
+/// 使 DateCreated 排序与"最近添加"反映实际入库顺序
+
+use dashmap::DashMap;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::info;
+
+use super::persist::{write_json_atomic, DeferredSave};
+
+/// fnOS item guid → 首次发现时间（Unix 秒）
+static FIRST_SEEN: LazyLock<DashMap<String, i64>> = LazyLock::new(|| {
+    let map = DashMap::new();
+    if let Some(data) = load_first_seen_from_file() {
+        for (k, v) in data {
+            map.insert(k, v);
+        }
+        info!("[CACHE] 已恢复 {} 条首次发现时间", map.len());
+    }
+    map
+});
+
+static SAVE: DeferredSave = DeferredSave::new("CACHE", save_first_seen);
+
+fn first_seen_file_path() -> PathBuf {
+    PathBuf::from(".item_first_seen.json")
+}
+
+fn load_first_seen_from_file() -> Option<std::collections::HashMap<String, i64>> {
+    let content = std::fs::read_to_string(first_seen_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_first_seen() -> std::io::Result<()> {
+    let data: std::collections::BTreeMap<String, i64> = FIRST_SEEN
+        .iter()
+        .map(|e| (e.key().clone(), *e.value()))
+        .collect();
+    write_json_atomic(&first_seen_file_path(), &data)
+}
+
+/// 记录一批项目的首次发现时间（已记录的保持不变，有新增时延迟合并保存）
+pub fn record_first_seen<'a>(item_guids: impl IntoIterator<Item = &'a str>) {
+    let now = chrono::Utc::now().timestamp();
+    let mut added = 0;
+    for guid in item_guids.into_iter().filter(|g| !g.is_empty()) {
+        if !FIRST_SEEN.contains_key(guid) {
+            FIRST_SEEN.insert(guid.to_string(), now);
+            added += 1;
+        }
+    }
+    if added > 0 {
+        SAVE.schedule();
+    }
+}
+
+/// 添加时间（首次发现时间）
+pub fn date_added(item_guid: &str) -> Option<i64> {
+    FIRST_SEEN.get(item_guid).map(|t| *t)
+}
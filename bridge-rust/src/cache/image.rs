@@ -1,22 +1,75 @@
 /// 图片 URL 缓存
-/// 在列表查询时缓存 poster/backdrop URL，供图片代理路由使用
+/// 在列表查询时缓存各类图片路径，供图片代理路由使用
 /// 这样图片请求不需要再调用飞牛 API
+/// 只保存路径，不保存任何用户凭据（见 services::image_auth）
 
 use dashmap::DashMap;
 use std::sync::LazyLock;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct CachedImage {
     pub poster: Option<String>,
-    pub backdrop: Option<String>,
-    pub server: String,
-    pub token: String,
+    pub backdrops: Vec<String>,
+    /// 是否来自详情接口（包含剧照）
+    pub detailed: bool,
+}
+
+fn non_empty(path: &str) -> Option<String> {
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+impl CachedImage {
+    /// 由海报与剧照构造（飞牛只返回这两种图片，剧照作为背景图）
+    pub fn from_fnos(poster: &str, still_path: &str) -> Self {
+        Self {
+            poster: non_empty(poster),
+            backdrops: non_empty(still_path).into_iter().collect(),
+            detailed: false,
+        }
+    }
+
+    /// 是否包含任何图片
+    pub fn is_empty(&self) -> bool {
+        self.poster.is_none() && self.backdrops.is_empty()
+    }
+
+    /// 按 Jellyfin ImageType 选择图片路径
+    /// Thumb 使用第一张背景图（同为横图）；飞牛没有 Logo / Banner / Art
+    pub fn path(&self, image_type: &str, index: usize) -> Option<&str> {
+        let path = match image_type.to_lowercase().as_str() {
+            "primary" | "poster" => self.poster.as_deref(),
+            "backdrop" => self.backdrops.get(index).map(String::as_str),
+            "thumb" => self.backdrops.first().map(String::as_str),
+            _ => None,
+        };
+        path.filter(|p| !p.is_empty())
+    }
+
+    /// 合并：新数据缺失的字段保留旧值
+    fn merge(&mut self, other: CachedImage) {
+        fn keep(old: &mut Option<String>, new: Option<String>) {
+            if new.is_some() {
+                *old = new;
+            }
+        }
+        keep(&mut self.poster, other.poster);
+        if !other.backdrops.is_empty() {
+            self.backdrops = other.backdrops;
+        }
+        self.detailed |= other.detailed;
+    }
 }
 
 static CACHE: LazyLock<DashMap<String, CachedImage>> = LazyLock::new(DashMap::new);
 
+/// 写入缓存（与已有条目合并，列表接口返回的字段较少，不覆盖详情接口的数据）
 pub fn set_image_cache(item_id: &str, data: CachedImage) {
-    CACHE.insert(item_id.to_string(), data);
+    match CACHE.get_mut(item_id) {
+        Some(mut existing) => existing.merge(data),
+        None => {
+            CACHE.insert(item_id.to_string(), data);
+        }
+    }
 }
 
 pub fn get_image_cache(item_id: &str) -> Option<CachedImage> {
@@ -25,6 +78,6 @@ pub fn get_image_cache(item_id: &str) -> Option<CachedImage> {
 
 pub fn set_image_cache_batch(entries: &[(String, CachedImage)]) {
     for (item_id, data) in entries {
-        CACHE.insert(item_id.clone(), data.clone());
+        set_image_cache(item_id, data.clone());
     }
 }
@@ -0,0 +1,203 @@
+/// 图片磁盘缓存
+/// 缓存处理后的图片（按飞牛图片路径 + 尺寸/格式参数），目录 .image_cache/{sha1}.{ext}
+/// 总大小超出上限时按最近访问时间淘汰到上限的 90%
+/// 同一缓存键的并发请求通过 `lock_image_key` 合并，只向飞牛请求、处理一次
+
+use dashmap::DashMap;
+use sha1::{Digest, Sha1};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tracing::{debug, info, warn};
+
+struct CacheEntry {
+    file_name: String,
+    size: u64,
+    last_access: u64,
+}
+
+/// 缓存键 → 文件
+static INDEX: LazyLock<DashMap<String, CacheEntry>> = LazyLock::new(|| {
+    let map = DashMap::new();
+    let mut total = 0;
+    if let Ok(dir) = std::fs::read_dir(cache_root()) {
+        for entry in dir.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some((key, _)) = file_name.split_once('.') else {
+                continue;
+            };
+            // 写入中断留下的临时文件
+            if file_name.ends_with(".tmp") {
+                let _ = std::fs::remove_file(entry.path());
+                continue;
+            }
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let last_access = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0);
+            total += meta.len();
+            map.insert(key.to_string(), CacheEntry { file_name, size: meta.len(), last_access });
+        }
+        info!("[IMAGE] 已恢复 {} 个缓存图片（{} KB）", map.len(), total / 1024);
+    }
+    TOTAL_SIZE.store(total, Ordering::Relaxed);
+    map
+});
+
+static TOTAL_SIZE: AtomicU64 = AtomicU64::new(0);
+
+/// 临时文件序号，与进程号一起保证临时文件名唯一
+static TMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// 处理中的缓存键 → 锁
+static IN_FLIGHT: LazyLock<DashMap<String, Arc<Mutex<()>>>> = LazyLock::new(DashMap::new);
+
+/// 缓存键处理锁，释放时清理无人等待的条目
+pub struct ImageKeyGuard {
+    key: String,
+    lock: Arc<Mutex<()>>,
+    _guard: OwnedMutexGuard<()>,
+}
+
+impl Drop for ImageKeyGuard {
+    fn drop(&mut self) {
+        // 引用：IN_FLIGHT 中一份、self.lock 一份、持有中的 guard 一份，多出的是等待者
+        IN_FLIGHT.remove_if(&self.key, |_, l| Arc::ptr_eq(l, &self.lock) && Arc::strong_count(l) <= 3);
+    }
+}
+
+/// 获取缓存键的处理锁；拿到锁后应再查一次缓存（等待期间其他请求可能已写入）
+pub async fn lock_image_key(key: &str) -> ImageKeyGuard {
+    let lock = IN_FLIGHT
+        .entry(key.to_string())
+        .or_insert_with(|| Arc::new(Mutex::new(())))
+        .clone();
+    let guard = lock.clone().lock_owned().await;
+    ImageKeyGuard { key: key.to_string(), lock, _guard: guard }
+}
+
+fn cache_root() -> PathBuf {
+    PathBuf::from(".image_cache")
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 缓存键：图片路径 + 处理参数
+pub fn image_cache_key(image_path: &str, options_key: &str) -> String {
+    Sha1::digest(format!("{}|{}", image_path, options_key).as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 根据文件头识别图片格式，返回 (扩展名, Content-Type)
+pub fn sniff_image(data: &[u8]) -> (&'static str, &'static str) {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        ("jpg", "image/jpeg")
+    } else if data.starts_with(&[0x89, b'P', b'N', b'G']) {
+        ("png", "image/png")
+    } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        ("webp", "image/webp")
+    } else if data.starts_with(b"GIF8") {
+        ("gif", "image/gif")
+    } else if data.starts_with(b"BM") {
+        ("bmp", "image/bmp")
+    } else {
+        ("bin", "application/octet-stream")
+    }
+}
+
+/// 读取缓存，返回 (内容, Content-Type)
+pub async fn get_cached_image(key: &str) -> Option<(Vec<u8>, &'static str)> {
+    let file_name = INDEX.get(key)?.file_name.clone();
+    match tokio::fs::read(cache_root().join(&file_name)).await {
+        Ok(data) => {
+            if let Some(mut entry) = INDEX.get_mut(key) {
+                entry.last_access = now_millis();
+            }
+            let (_, content_type) = sniff_image(&data);
+            Some((data, content_type))
+        }
+        Err(_) => {
+            // 文件被外部删除
+            if let Some((_, entry)) = INDEX.remove(key) {
+                TOTAL_SIZE.fetch_sub(entry.size, Ordering::Relaxed);
+            }
+            None
+        }
+    }
+}
+
+/// 写入缓存，必要时淘汰最久未访问的条目
+pub async fn put_cached_image(key: &str, data: &[u8], budget_bytes: u64) {
+    if budget_bytes == 0 || data.len() as u64 > budget_bytes / 4 {
+        return;
+    }
+    let (ext, _) = sniff_image(data);
+    let file_name = format!("{}.{}", key, ext);
+    let root = cache_root();
+    if tokio::fs::create_dir_all(&root).await.is_err() {
+        return;
+    }
+    // 先写临时文件再改名，避免读到写了一半的图片
+    let seq = TMP_SEQ.fetch_add(1, Ordering::Relaxed);
+    let tmp = root.join(format!("{}.{}-{}.tmp", key, std::process::id(), seq));
+    if let Err(e) = tokio::fs::write(&tmp, data).await {
+        warn!("[IMAGE] 写入图片缓存失败: {}", e);
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return;
+    }
+    if let Err(e) = tokio::fs::rename(&tmp, root.join(&file_name)).await {
+        warn!("[IMAGE] 写入图片缓存失败: {}", e);
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return;
+    }
+
+    let size = data.len() as u64;
+    let entry = CacheEntry { file_name, size, last_access: now_millis() };
+    if let Some(old) = INDEX.insert(key.to_string(), entry) {
+        TOTAL_SIZE.fetch_sub(old.size, Ordering::Relaxed);
+        if old.file_name != format!("{}.{}", key, ext) {
+            let _ = tokio::fs::remove_file(root.join(&old.file_name)).await;
+        }
+    }
+    let total = TOTAL_SIZE.fetch_add(size, Ordering::Relaxed) + size;
+    if total > budget_bytes {
+        evict(budget_bytes / 10 * 9).await;
+    }
+}
+
+/// 按最近访问时间淘汰到目标大小以下
+async fn evict(target: u64) {
+    let mut entries: Vec<(String, u64)> = INDEX.iter().map(|e| (e.key().clone(), e.last_access)).collect();
+    entries.sort_by_key(|(_, t)| *t);
+
+    let mut removed = 0;
+    for (key, _) in entries {
+        if TOTAL_SIZE.load(Ordering::Relaxed) <= target {
+            break;
+        }
+        if let Some((_, entry)) = INDEX.remove(&key) {
+            TOTAL_SIZE.fetch_sub(entry.size, Ordering::Relaxed);
+            let _ = tokio::fs::remove_file(cache_root().join(&entry.file_name)).await;
+            removed += 1;
+        }
+    }
+    debug!(
+        "[IMAGE] 淘汰 {} 个缓存图片，当前 {} KB",
+        removed,
+        TOTAL_SIZE.load(Ordering::Relaxed) / 1024
+    );
+}
@@ -7,6 +7,7 @@ use std::time::Instant;
 use tokio::sync::Mutex;
 use tracing::debug;
 
+use crate::cache::first_seen::record_first_seen;
 use crate::config::BridgeConfig;
 use crate::fnos_client::client::RequestResult;
 use crate::services::fnos::fnos_get_item_list;
@@ -157,6 +158,9 @@ pub async fn cached_get_item_list(
     }
 
     let result = fnos_get_item_list(server, token, parent_guid, sort_column, sort_type, config).await;
+    if let Some(data) = &result.data {
+        record_first_seen(data.list.iter().map(|item| item.guid.as_str()));
+    }
 
     CACHE.insert(key.clone(), CachedEntry {
         data: result.clone(),
@@ -0,0 +1,129 @@
+/// 项目视频元数据缓存
+/// 每次取得 stream/list 时记录画面尺寸、动态范围、VideoType、3D 格式（.item_metadata.json），
+/// 列表映射时直接读取，无需为每一行请求 stream/list；未缓存的项目在后台逐个补齐
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::{debug, info};
+
+use super::persist::{write_json_atomic, DeferredSave};
+use crate::cache::stream_list::cached_get_stream_list;
+use crate::config::BridgeConfig;
+use crate::mappers::media::{video_3d_format, video_range, video_type};
+
+/// 每次列表请求最多在后台补齐的项目数
+const MAX_PREFETCH: usize = 20;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemVideoMeta {
+    pub width: i32,
+    pub height: i32,
+    pub video_range: String,
+    pub video_range_type: String,
+    pub video_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub video_3d_format: Option<String>,
+}
+
+impl ItemVideoMeta {
+    /// 从 stream/list 响应提取（多版本时取分辨率最高的视频流）
+    pub fn from_stream_list(data: &serde_json::Value) -> Option<Self> {
+        let vs = data["video_streams"]
+            .as_array()?
+            .iter()
+            .max_by_key(|vs| vs["height"].as_i64().unwrap_or(0))?;
+        let path = vs["media_guid"].as_str()
+            .and_then(|mg| {
+                data["files"].as_array()?.iter().find(|f| f["guid"].as_str() == Some(mg))
+            })
+            .or_else(|| data["files"].as_array()?.first())
+            .and_then(|f| f["path"].as_str())
+            .unwrap_or("");
+        let (range, range_type) = video_range(vs);
+        Some(Self {
+            width: vs["width"].as_i64().unwrap_or(0) as i32,
+            height: vs["height"].as_i64().unwrap_or(0) as i32,
+            video_range: range.to_string(),
+            video_range_type: range_type.to_string(),
+            video_type: video_type(path).to_string(),
+            video_3d_format: video_3d_format(path).map(String::from),
+        })
+    }
+}
+
+/// fnOS item guid → 视频元数据
+static ITEM_META: LazyLock<DashMap<String, ItemVideoMeta>> = LazyLock::new(|| {
+    let map = DashMap::new();
+    if let Some(data) = load_item_meta_from_file() {
+        for (k, v) in data {
+            map.insert(k, v);
+        }
+        info!("[CACHE] 已恢复 {} 条视频元数据", map.len());
+    }
+    map
+});
+
+/// 正在后台获取的项目
+static PENDING: LazyLock<DashMap<String, ()>> = LazyLock::new(DashMap::new);
+
+static SAVE: DeferredSave = DeferredSave::new("CACHE", save_item_meta);
+
+fn item_meta_file_path() -> PathBuf {
+    PathBuf::from(".item_metadata.json")
+}
+
+fn load_item_meta_from_file() -> Option<std::collections::HashMap<String, ItemVideoMeta>> {
+    let content = std::fs::read_to_string(item_meta_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_item_meta() -> std::io::Result<()> {
+    let data: std::collections::BTreeMap<String, ItemVideoMeta> = ITEM_META
+        .iter()
+        .map(|e| (e.key().clone(), e.value().clone()))
+        .collect();
+    write_json_atomic(&item_meta_file_path(), &data)
+}
+
+pub fn get_item_meta(item_guid: &str) -> Option<ItemVideoMeta> {
+    ITEM_META.get(item_guid).map(|v| v.clone())
+}
+
+/// 记录 stream/list 响应中的视频元数据（内容未变化时不写文件，变化时延迟合并保存）
+pub fn record_item_meta(item_guid: &str, data: &serde_json::Value) {
+    let Some(meta) = ItemVideoMeta::from_stream_list(data) else { return };
+    if ITEM_META.get(item_guid).is_none_or(|old| *old != meta) {
+        debug!("[CACHE] 视频元数据: {} {}x{} {}", item_guid, meta.width, meta.height, meta.video_range_type);
+        ITEM_META.insert(item_guid.to_string(), meta);
+        SAVE.schedule();
+    }
+}
+
+/// 在后台为尚无元数据的项目获取 stream/list（每次最多 MAX_PREFETCH 个，逐个请求）
+pub fn prefetch_item_meta(item_guids: Vec<String>, server: &str, token: &str, config: &BridgeConfig) {
+    let guids: Vec<String> = item_guids
+        .into_iter()
+        .filter(|g| !g.is_empty() && !ITEM_META.contains_key(g))
+        .filter(|g| match PENDING.entry(g.clone()) {
+            dashmap::Entry::Occupied(_) => false,
+            dashmap::Entry::Vacant(slot) => {
+                slot.insert(());
+                true
+            }
+        })
+        .take(MAX_PREFETCH)
+        .collect();
+    if guids.is_empty() {
+        return;
+    }
+    let (server, token, config) = (server.to_string(), token.to_string(), config.clone());
+    tokio::spawn(async move {
+        for guid in guids {
+            // 成功时 cached_get_stream_list 会记录元数据
+            cached_get_stream_list(&server, &token, &guid, &config).await;
+            PENDING.remove(&guid);
+        }
+    });
+}
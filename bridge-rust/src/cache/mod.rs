@@ -1,4 +1,10 @@
+pub mod blurhash;
+pub mod first_seen;
 pub mod image;
+pub mod image_file;
 pub mod item_list;
+pub mod item_meta;
+pub mod persist;
+pub mod play_history;
 pub mod stream_list;
 pub mod user_info;
@@ -0,0 +1,61 @@
+/// 缓存文件的延迟批量保存
+/// 高频写入的缓存（BlurHash、条目元数据等）合并为一次落盘：首次变更后等待片刻，在阻塞线程中序列化，
+/// 先写临时文件再重命名，避免并发写入或中途崩溃留下损坏的 JSON
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::warn;
+
+/// 变更后等待多久再落盘
+const SAVE_DELAY: Duration = Duration::from_secs(5);
+
+/// 写入临时文件后原子替换目标文件
+pub fn write_json_atomic<T: Serialize>(path: &Path, data: &T) -> std::io::Result<()> {
+    let json = serde_json::to_string(data)?;
+    let mut tmp = PathBuf::from(path);
+    tmp.as_mut_os_string().push(".tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// 延迟保存：同一时间只有一个待执行的保存任务，多次 schedule 合并为一次写入
+pub struct DeferredSave {
+    scheduled: AtomicBool,
+    writing: Mutex<()>,
+    tag: &'static str,
+    save: fn() -> std::io::Result<()>,
+}
+
+impl DeferredSave {
+    pub const fn new(tag: &'static str, save: fn() -> std::io::Result<()>) -> Self {
+        Self { scheduled: AtomicBool::new(false), writing: Mutex::new(()), tag, save }
+    }
+
+    /// 安排一次保存（已有待执行的保存时直接返回）
+    /// 不在 tokio 运行时中调用时立即同步保存
+    pub fn schedule(&'static self) {
+        if self.scheduled.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            self.run();
+            return;
+        };
+        handle.spawn(async move {
+            tokio::time::sleep(SAVE_DELAY).await;
+            let _ = tokio::task::spawn_blocking(move || self.run()).await;
+        });
+    }
+
+    fn run(&self) {
+        // 先清标记再序列化：保存期间的新变更会安排下一次保存
+        self.scheduled.store(false, Ordering::Release);
+        let _guard = self.writing.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = (self.save)() {
+            warn!("[{}] 保存缓存文件失败: {}", self.tag, e);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+/// 最近播放时间
+/// 飞牛的 item/list 不返回播放时间，桥接在收到播放上报时记录每个用户每个项目的最近播放时间（.play_history.json），
+/// 供继续观看按最近播放排序
+
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::info;
+
+use super::persist::{write_json_atomic, DeferredSave};
+
+/// 每个用户最多保留的记录数（超出时丢弃最早播放的）
+const MAX_PER_USER: usize = 500;
+
+/// 同一项目两次记录的最小间隔（秒），避免进度上报频繁触发保存
+const RECORD_INTERVAL_SECS: i64 = 60;
+
+/// user_id → (fnOS item guid → 最近播放时间，Unix 秒)
+static PLAY_HISTORY: LazyLock<DashMap<String, HashMap<String, i64>>> = LazyLock::new(|| {
+    let map = DashMap::new();
+    if let Some(data) = load_play_history_from_file() {
+        for (k, v) in data {
+            map.insert(k, v);
+        }
+        info!("[CACHE] 已恢复 {} 个用户的播放时间", map.len());
+    }
+    map
+});
+
+static SAVE: DeferredSave = DeferredSave::new("CACHE", save_play_history);
+
+fn play_history_file_path() -> PathBuf {
+    PathBuf::from(".play_history.json")
+}
+
+fn load_play_history_from_file() -> Option<HashMap<String, HashMap<String, i64>>> {
+    let content = std::fs::read_to_string(play_history_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_play_history() -> std::io::Result<()> {
+    let data: std::collections::BTreeMap<String, HashMap<String, i64>> = PLAY_HISTORY
+        .iter()
+        .map(|e| (e.key().clone(), e.value().clone()))
+        .collect();
+    write_json_atomic(&play_history_file_path(), &data)
+}
+
+/// 记录用户播放了某个项目
+pub fn record_played(user_id: &str, item_guid: &str) {
+    if user_id.is_empty() || item_guid.is_empty() {
+        return;
+    }
+    let now = chrono::Utc::now().timestamp();
+    let mut history = PLAY_HISTORY.entry(user_id.to_string()).or_default();
+    if history.get(item_guid).is_some_and(|t| now - t < RECORD_INTERVAL_SECS) {
+        return;
+    }
+    history.insert(item_guid.to_string(), now);
+    if history.len() > MAX_PER_USER {
+        let mut times: Vec<i64> = history.values().copied().collect();
+        times.sort_unstable_by(|a, b| b.cmp(a));
+        let cutoff = times[MAX_PER_USER - 1];
+        history.retain(|_, t| *t >= cutoff);
+    }
+    drop(history);
+    SAVE.schedule();
+}
+
+/// 用户最近播放某个项目的时间（未经桥接播放过时为 None）
+pub fn last_played(user_id: &str, item_guid: &str) -> Option<i64> {
+    PLAY_HISTORY.get(user_id)?.get(item_guid).copied()
+}
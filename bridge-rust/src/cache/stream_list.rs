@@ -8,6 +8,7 @@ use std::time::Instant;
 use tokio::sync::Mutex;
 use tracing::debug;
 
+use crate::cache::item_meta::record_item_meta;
 use crate::config::BridgeConfig;
 use crate::fnos_client::client::RequestResult;
 use crate::services::fnos::fnos_get_stream_list;
@@ -60,6 +61,9 @@ pub async fn cached_get_stream_list(
     }
 
     let result = fnos_get_stream_list(server, token, item_guid, config).await;
+    if let Some(data) = result.data.as_ref().filter(|_| result.success) {
+        record_item_meta(item_guid, data);
+    }
 
     CACHE.insert(key.clone(), CachedEntry {
         data: result.clone(),
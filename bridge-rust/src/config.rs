@@ -15,6 +15,23 @@ pub struct BridgeConfig {
     pub jellyfin_version: String,
     /// 伪装的服务器名称
     pub server_name: String,
+    /// 是否在 /Users/Public 公开近期登录过的用户（电视端用户选择）
+    pub public_user_list: bool,
+    /// 桥接层管理员（飞牛用户名，逗号分隔）：字幕管理、片段编辑、删除等需要管理员的操作
+    pub admin_users: Vec<String>,
+    /// 是否生成拖动进度条缩略图（Trickplay）
+    pub enable_trickplay: bool,
+    /// ffmpeg 可执行文件路径（Trickplay 抽帧）
+    pub ffmpeg_path: String,
+    /// ffprobe 可执行文件路径（读取容器章节）
+    pub ffprobe_path: String,
+    /// 图片磁盘缓存上限（MB），0 表示不缓存
+    pub image_cache_mb: u64,
+    /// 服务账号（匿名图片请求使用），为空表示不启用
+    pub service_username: String,
+    pub service_password: String,
+    /// SortName 排序规则（pinyin：汉字按拼音；unicode：保留原文）
+    pub sort_collation: crate::services::hanzi::SortCollation,
 }
 
 impl BridgeConfig {
@@ -32,6 +49,31 @@ impl BridgeConfig {
                 .unwrap_or(false),
             jellyfin_version: "10.12.0".into(),
             server_name: std::env::var("SERVER_NAME").unwrap_or_else(|_| "fnos-bridge".into()),
+            public_user_list: std::env::var("PUBLIC_USER_LIST")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            admin_users: std::env::var("ADMIN_USERS")
+                .map(|v| v.split(',').map(|u| u.trim().to_string()).filter(|u| !u.is_empty()).collect())
+                .unwrap_or_default(),
+            enable_trickplay: std::env::var("ENABLE_TRICKPLAY")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            ffmpeg_path: std::env::var("FFMPEG_PATH").unwrap_or_else(|_| "ffmpeg".into()),
+            ffprobe_path: std::env::var("FFPROBE_PATH").unwrap_or_else(|_| "ffprobe".into()),
+            image_cache_mb: std::env::var("IMAGE_CACHE_MB")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(512),
+            service_username: std::env::var("FNOS_SERVICE_USERNAME").unwrap_or_default(),
+            service_password: std::env::var("FNOS_SERVICE_PASSWORD").unwrap_or_default(),
+            sort_collation: std::env::var("SORT_COLLATION")
+                .map(|v| crate::services::hanzi::SortCollation::from_name(&v))
+                .unwrap_or_default(),
         }
     }
+
+    /// 飞牛用户名是否为桥接层管理员（飞牛 user/info 不提供角色信息，由 ADMIN_USERS 配置）
+    pub fn is_admin_user(&self, username: &str) -> bool {
+        !username.is_empty() && self.admin_users.iter().any(|u| u.eq_ignore_ascii_case(username))
+    }
 }
@@ -0,0 +1,23 @@
+/// BlurHash 编码（blurhash crate）
+/// 分量数按宽高比选择，与 Jellyfin 一致：尽量让每块接近正方形，总数约 16
+
+use image::imageops;
+
+/// 计算前先缩小到此尺寸以内（BlurHash 只保留低频信息）
+const SAMPLE_SIZE: u32 = 64;
+
+/// 解码图片并计算 BlurHash，不支持的格式返回 None
+pub fn from_bytes(data: &[u8]) -> Option<String> {
+    let image = super::decode(data)?;
+    let image = if image.width() > SAMPLE_SIZE || image.height() > SAMPLE_SIZE {
+        imageops::thumbnail(&image, SAMPLE_SIZE, SAMPLE_SIZE)
+    } else {
+        image
+    };
+    let (width, height) = image.dimensions();
+    let x_comp_f = (16.0 * width as f32 / height as f32).sqrt();
+    let y_comp_f = x_comp_f * height as f32 / width as f32;
+    let x_comp = (x_comp_f as u32 + 1).min(9);
+    let y_comp = (y_comp_f as u32 + 1).min(9);
+    blurhash::encode(x_comp, y_comp, width, height, image.as_raw()).ok()
+}
@@ -0,0 +1,263 @@
+/// 图片处理
+/// 解码（JPEG / PNG / WebP）→ 裁白边 / 缩放 / 模糊 → 编码（JPEG / PNG / WebP 无损），编解码由 image crate 完成
+/// 无法解码的格式（GIF 等）由调用方原样返回；另提供 BlurHash 编码
+
+pub mod blurhash;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::{self, FilterType};
+use image::{ExtendedColorType, ImageEncoder, ImageReader, Limits, RgbaImage};
+use std::io::Cursor;
+
+/// 单张图片解码上限（像素），防止异常尺寸耗尽内存
+const MAX_PIXELS: u64 = 40_000_000;
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl OutputFormat {
+    /// Jellyfin ImageFormat 参数（jpg / png / webp）
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "png" => Some(Self::Png),
+            "webp" => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::Webp => "webp",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::Webp => "image/webp",
+        }
+    }
+}
+
+/// Jellyfin 图片请求参数
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImageOptions {
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub max_width: Option<usize>,
+    pub max_height: Option<usize>,
+    pub fill_width: Option<usize>,
+    pub fill_height: Option<usize>,
+    pub quality: Option<u8>,
+    pub format: Option<OutputFormat>,
+    pub blur: Option<usize>,
+    pub crop_whitespace: bool,
+}
+
+impl ImageOptions {
+    /// 从 query 参数解析（参数名大小写不敏感）
+    pub fn from_query(query: &std::collections::HashMap<String, String>) -> Self {
+        let get = |name: &str| {
+            query.iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        };
+        // 数值参数可能是小数（如 fillWidth=396.5），取整
+        let size = |name: &str| {
+            get(name)
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| *v >= 1.0)
+                .map(|v| v.round() as usize)
+        };
+        Self {
+            width: size("width"),
+            height: size("height"),
+            max_width: size("maxWidth"),
+            max_height: size("maxHeight"),
+            fill_width: size("fillWidth"),
+            fill_height: size("fillHeight"),
+            quality: get("quality").and_then(|v| v.parse::<u8>().ok()).map(|q| q.clamp(1, 100)),
+            format: get("format").and_then(OutputFormat::from_name),
+            blur: get("blur").and_then(|v| v.parse::<usize>().ok()).filter(|b| *b > 0).map(|b| b.min(100)),
+            crop_whitespace: get("cropWhitespace").is_some_and(|v| v.eq_ignore_ascii_case("true")),
+        }
+    }
+
+    /// 是否需要处理（否则直接返回原图）
+    pub fn is_noop(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 缓存键的一部分
+    pub fn cache_key(&self) -> String {
+        format!(
+            "w{:?}h{:?}mw{:?}mh{:?}fw{:?}fh{:?}q{:?}f{:?}b{:?}c{}",
+            self.width, self.height, self.max_width, self.max_height,
+            self.fill_width, self.fill_height, self.quality, self.format, self.blur, self.crop_whitespace
+        )
+    }
+
+    /// 按参数计算目标尺寸（保持宽高比，不放大）
+    pub fn target_size(&self, width: usize, height: usize) -> (usize, usize) {
+        let (w, h) = (width as f64, height as f64);
+        let mut scale: f64 = 1.0;
+
+        match (self.width, self.height) {
+            (Some(tw), Some(th)) => scale = (tw as f64 / w).min(th as f64 / h),
+            (Some(tw), None) => scale = tw as f64 / w,
+            (None, Some(th)) => scale = th as f64 / h,
+            (None, None) => {
+                // fill：覆盖整个区域
+                match (self.fill_width, self.fill_height) {
+                    (Some(fw), Some(fh)) => scale = (fw as f64 / w).max(fh as f64 / h),
+                    (Some(fw), None) => scale = fw as f64 / w,
+                    (None, Some(fh)) => scale = fh as f64 / h,
+                    (None, None) => {}
+                }
+            }
+        }
+        if let Some(mw) = self.max_width {
+            scale = scale.min(mw as f64 / w);
+        }
+        if let Some(mh) = self.max_height {
+            scale = scale.min(mh as f64 / h);
+        }
+        let scale = scale.min(1.0);
+        (((w * scale).round() as usize).max(1), ((h * scale).round() as usize).max(1))
+    }
+}
+
+/// 识别并解码图片为 RGBA，不支持或损坏的图片返回 None
+pub fn decode(data: &[u8]) -> Option<RgbaImage> {
+    let mut reader = ImageReader::new(Cursor::new(data)).with_guessed_format().ok()?;
+    let mut limits = Limits::default();
+    limits.max_alloc = Some(MAX_PIXELS * 4);
+    reader.limits(limits);
+    Some(reader.decode().ok()?.to_rgba8())
+}
+
+/// 白色或透明像素
+fn is_blank(p: &image::Rgba<u8>) -> bool {
+    p[3] < 16 || (p[0] >= 240 && p[1] >= 240 && p[2] >= 240)
+}
+
+/// 裁掉四周的白色 / 透明边（全部为空白时返回原图）
+fn crop_whitespace(image: RgbaImage) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let row_blank = |y: u32| (0..width).all(|x| is_blank(image.get_pixel(x, y)));
+    let col_blank = |x: u32, top: u32, bottom: u32| (top..bottom).all(|y| is_blank(image.get_pixel(x, y)));
+
+    let Some(top) = (0..height).find(|&y| !row_blank(y)) else {
+        return image;
+    };
+    let bottom = (0..height).rev().find(|&y| !row_blank(y)).unwrap_or(top) + 1;
+    let left = (0..width).find(|&x| !col_blank(x, top, bottom)).unwrap_or(0);
+    let right = (0..width).rev().find(|&x| !col_blank(x, top, bottom)).unwrap_or(width - 1) + 1;
+
+    if (left, top, right, bottom) == (0, 0, width, height) {
+        return image;
+    }
+    imageops::crop_imm(&image, left, top, right - left, bottom - top).to_image()
+}
+
+/// 按格式编码，JPEG 丢弃透明通道
+fn encode(image: &RgbaImage, format: OutputFormat, quality: u8) -> Option<Vec<u8>> {
+    let (width, height) = image.dimensions();
+    let mut out = Vec::new();
+    match format {
+        OutputFormat::Jpeg => {
+            let rgb = image::DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+            JpegEncoder::new_with_quality(&mut out, quality)
+                .write_image(rgb.as_raw(), width, height, ExtendedColorType::Rgb8)
+                .ok()?;
+        }
+        OutputFormat::Png => PngEncoder::new(&mut out)
+            .write_image(image.as_raw(), width, height, ExtendedColorType::Rgba8)
+            .ok()?,
+        OutputFormat::Webp => WebPEncoder::new_lossless(&mut out)
+            .write_image(image.as_raw(), width, height, ExtendedColorType::Rgba8)
+            .ok()?,
+    }
+    Some(out)
+}
+
+/// 按参数处理图片，返回 (编码结果, 输出格式)
+/// 未指定格式时：带透明通道输出 PNG，否则输出 JPEG
+pub fn process(data: &[u8], options: &ImageOptions) -> Option<(Vec<u8>, OutputFormat)> {
+    let mut image = decode(data)?;
+    if options.crop_whitespace {
+        image = crop_whitespace(image);
+    }
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let (tw, th) = options.target_size(width, height);
+    if (tw, th) != (width, height) {
+        image = imageops::resize(&image, tw as u32, th as u32, FilterType::Triangle);
+    }
+    if let Some(radius) = options.blur {
+        image = imageops::blur(&image, radius as f32 / 2.0);
+    }
+
+    let has_alpha = image.pixels().any(|p| p[3] != 255);
+    let format = options.format.unwrap_or(if has_alpha { OutputFormat::Png } else { OutputFormat::Jpeg });
+    let quality = options.quality.unwrap_or(90);
+    match encode(&image, format, quality) {
+        Some(data) => Some((data, format)),
+        // 超出 WebP 尺寸上限时改用 PNG
+        None if format == OutputFormat::Webp => encode(&image, OutputFormat::Png, quality).map(|d| (d, OutputFormat::Png)),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(format: OutputFormat) -> Vec<u8> {
+        let image = RgbaImage::from_fn(64, 32, |x, y| image::Rgba([x as u8 * 4, y as u8 * 8, 128, 255]));
+        encode(&image, format, 90).unwrap()
+    }
+
+    #[test]
+    fn resizes_and_picks_format() {
+        let options = ImageOptions { max_width: Some(16), ..Default::default() };
+        let (data, format) = process(&sample(OutputFormat::Png), &options).unwrap();
+        assert_eq!(format, OutputFormat::Jpeg);
+        let image = decode(&data).unwrap();
+        assert_eq!(image.dimensions(), (16, 8));
+    }
+
+    #[test]
+    fn truncated_and_corrupt_input_is_rejected() {
+        for format in [OutputFormat::Jpeg, OutputFormat::Png, OutputFormat::Webp] {
+            let data = sample(format);
+            for len in [0, 2, 16, data.len() / 2] {
+                assert!(process(&data[..len], &ImageOptions { blur: Some(4), ..Default::default() }).is_none());
+            }
+            let mut corrupt = data.clone();
+            for b in corrupt.iter_mut().skip(20).step_by(7) {
+                *b ^= 0x5A;
+            }
+            // 损坏数据可能解码失败，也可能得到花屏，但不能 panic
+            let _ = process(&corrupt, &ImageOptions { width: Some(8), ..Default::default() });
+        }
+    }
+
+    #[test]
+    fn crops_white_border() {
+        let image = RgbaImage::from_fn(10, 10, |x, y| {
+            if (3..7).contains(&x) && (2..5).contains(&y) { image::Rgba([0, 0, 0, 255]) } else { image::Rgba([255, 255, 255, 255]) }
+        });
+        assert_eq!(crop_whitespace(image).dimensions(), (4, 3));
+    }
+}
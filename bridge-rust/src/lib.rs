@@ -1,6 +1,7 @@
 pub mod cache;
 pub mod config;
 pub mod fnos_client;
+pub mod imaging;
 pub mod mappers;
 pub mod middleware;
 pub mod proxy;
@@ -34,6 +34,7 @@ async fn main() {
     extract_web_zip_if_needed();
 
     let config = BridgeConfig::from_env();
+    fnos_bridge::services::hanzi::set_sort_collation(config.sort_collation);
 
     println!(
         r#"
@@ -74,6 +75,12 @@ fn build_router(config: BridgeConfig) -> Router {
         .merge(fnos_bridge::routes::mediainfo::router())
         .merge(fnos_bridge::routes::playback::router())
         .merge(fnos_bridge::routes::extras::router())
+        .merge(fnos_bridge::routes::displayprefs::router())
+        .merge(fnos_bridge::routes::playlists::router())
+        .merge(fnos_bridge::routes::subtitles::router())
+        .merge(fnos_bridge::routes::trickplay::router())
+        .merge(fnos_bridge::routes::mediasegments::router())
+        .merge(fnos_bridge::routes::search::router())
         .merge(fnos_bridge::proxy::stream::router())
         // WebSocket
         .route("/socket", get(ws_handler))
@@ -88,8 +95,6 @@ fn build_router(config: BridgeConfig) -> Router {
         .route("/Localization/Countries", get(empty_array))
         .route("/Localization/Cultures", get(empty_array))
         .route("/Localization/ParentalRatings", get(empty_array))
-        // DisplayPreferences
-        .route("/DisplayPreferences/{id}", get(display_prefs).post(no_content))
         // Intros
         .route("/Items/{itemId}/Intros", get(empty_items))
         .route("/Users/{userId}/Items/{itemId}/Intros", get(empty_items))
@@ -183,19 +188,6 @@ async fn sessions_list(req: axum::extract::Request) -> Json<serde_json::Value> {
     Json(json!([s]))
 }
 
-async fn display_prefs(
-    axum::extract::Path(id): axum::extract::Path<String>,
-) -> Json<serde_json::Value> {
-    Json(json!({
-        "Id": id,
-        "SortBy": "SortName",
-        "SortOrder": "Ascending",
-        "RememberIndexing": false,
-        "RememberSorting": false,
-        "CustomPrefs": {}
-    }))
-}
-
 async fn theme_media() -> Json<serde_json::Value> {
     Json(json!({
         "ThemeVideosResult": {"Items": [], "TotalRecordCount": 0},
@@ -432,7 +424,7 @@ fn normalize_path(req: axum::extract::Request) -> axum::extract::Request {
         ("enabled", "Enabled"), ("displaypreferences", "DisplayPreferences"),
         ("localization", "Localization"), ("countries", "Countries"),
         ("cultures", "Cultures"), ("parentalratings", "ParentalRatings"),
-        ("filters", "Filters"), ("nextup", "NextUp"), ("latest", "Latest"),
+        ("filters", "Filters"), ("filters2", "Filters2"), ("nextup", "NextUp"), ("latest", "Latest"),
         ("primary", "Primary"), ("backdrop", "Backdrop"), ("thumb", "Thumb"),
         ("logo", "Logo"), ("banner", "Banner"), ("views", "Views"),
         ("stream", "stream"), ("subtitles", "Subtitles"), ("intros", "Intros"),
@@ -441,6 +433,10 @@ fn normalize_path(req: axum::extract::Request) -> axum::extract::Request {
         ("list", "List"), ("studios", "Studios"), ("endpoint", "Endpoint"),
         ("playback", "Playback"), ("bitratetest", "BitrateTest"),
         ("hls", "hls"), ("main.m3u8", "main.m3u8"), ("preset.m3u8", "preset.m3u8"),
+        ("playlists", "Playlists"), ("move", "Move"), ("download", "Download"),
+        ("remotesearch", "RemoteSearch"), ("trickplay", "Trickplay"),
+        ("mediasegments", "MediaSegments"), ("chapter", "Chapter"),
+        ("search", "Search"), ("hints", "Hints"),
     ];
 
     let path = req.uri().path().to_string();
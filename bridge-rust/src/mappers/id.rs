@@ -61,6 +61,11 @@ pub fn register_reverse_mapping(jellyfin_id: &str, fnos_guid: &str) {
     REVERSE_MAP.insert(jellyfin_id.to_lowercase(), fnos_guid.to_string());
 }
 
+/// 生成确定性的派生 ID（不注册反向映射，用于章节、片段等子对象）
+pub fn derived_id(name: &str) -> String {
+    uuidv5(name, NAMESPACE)
+}
+
 /// 生成确定性的服务器 ID
 pub fn generate_server_id(server_url: &str) -> String {
     uuidv5(server_url, NAMESPACE)
@@ -1,11 +1,18 @@
 /// Item 映射器
 /// 飞牛 PlayListItem / ItemDetail → Jellyfin BaseItemDto
 
-use base64::Engine;
+use sha1::{Digest, Sha1};
 use crate::types::fnos::{FnosPlayInfo, FnosPlayListItem};
-use crate::types::jellyfin::{BaseItemDto, UserItemDataDto};
-use crate::cache::image::{set_image_cache, CachedImage};
+use crate::types::jellyfin::{BaseItemDto, SearchHint, UserItemDataDto};
+use crate::cache::blurhash::get_blur_hash;
+use crate::cache::first_seen::date_added;
+use crate::cache::item_meta::get_item_meta;
+use crate::cache::image::{get_image_cache, set_image_cache, CachedImage};
+use crate::services::hanzi::sort_name;
+use crate::services::image_auth::{image_tag_hash, sign_image_tag};
+use crate::services::playlist::Playlist;
 use super::id::{to_jellyfin_id, register_item_type};
+use super::user::can_download;
 
 /// 秒 → Jellyfin ticks (1 tick = 100ns)
 pub fn seconds_to_ticks(seconds: f64) -> i64 {
@@ -30,26 +37,93 @@ pub fn map_type(fnos_type: &str) -> &'static str {
 }
 
 /// 飞牛类型 → Jellyfin MediaType
-fn map_media_type(jf_type: &str) -> Option<&'static str> {
+pub fn map_media_type(jf_type: &str) -> Option<&'static str> {
     match jf_type {
         "Movie" | "Episode" | "Video" => Some("Video"),
         _ => None,
     }
 }
 
-/// 构造图片标签
-fn make_image_tags(poster: &str) -> Option<serde_json::Value> {
-    if poster.is_empty() {
-        return Some(serde_json::json!({}));
+/// 图片标签（由飞牛图片路径派生，路径变化即标签变化）
+pub fn image_tag(path: &str) -> String {
+    Sha1::digest(path.as_bytes())
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 返回给客户端的图片标签：路径哈希 + 签名
+fn signed_tag(path: &str) -> String {
+    sign_image_tag(&image_tag(path))
+}
+
+/// 构造图片标签（ImageTags 不含背景图）
+fn make_image_tags(images: &CachedImage) -> Option<serde_json::Value> {
+    let mut tags = serde_json::Map::new();
+    if let Some(path) = &images.poster {
+        tags.insert("Primary".to_string(), signed_tag(path).into());
     }
-    let tag = base64::engine::general_purpose::URL_SAFE_NO_PAD
-        .encode(poster.as_bytes());
-    let tag = &tag[..tag.len().min(16)];
-    Some(serde_json::json!({ "Primary": tag }))
+    Some(serde_json::Value::Object(tags))
+}
+
+fn make_backdrop_tags(images: &CachedImage) -> Option<Vec<String>> {
+    Some(images.backdrops.iter().map(|p| signed_tag(p)).collect())
+}
+
+/// 季 / 单集引用所属剧集的图片（SeriesPrimaryImageTag、ParentBackdrop / Thumb），并填入 ImageBlurHashes
+/// 只使用已缓存的剧集图片，调用方修改 series_id 后需重新调用
+pub fn apply_parent_images(dto: &mut BaseItemDto) {
+    let series_id = dto.series_id.clone();
+    if let Some((series_id, series)) = series_id.and_then(|id| get_image_cache(&id).map(|s| (id, s))) {
+        dto.series_primary_image_tag = series.poster.as_deref().map(signed_tag);
+        let has_backdrop = dto.backdrop_image_tags.as_ref().is_some_and(|t| !t.is_empty());
+        if !has_backdrop && !series.backdrops.is_empty() {
+            dto.parent_backdrop_item_id = Some(series_id.clone());
+            dto.parent_backdrop_image_tags = make_backdrop_tags(&series);
+        }
+        if let Some(thumb) = series.path("Thumb", 0) {
+            dto.parent_thumb_item_id = Some(series_id);
+            dto.parent_thumb_image_tag = Some(signed_tag(thumb));
+        }
+    }
+    apply_blur_hashes(dto);
+}
+
+/// ImageBlurHashes：已计算过 BlurHash 的图片（含父级图片）
+fn apply_blur_hashes(dto: &mut BaseItemDto) {
+    let mut tags: Vec<(&str, String)> = Vec::new();
+    if let Some(serde_json::Value::Object(map)) = &dto.image_tags {
+        tags.extend(map.iter().filter_map(|(k, v)| v.as_str().map(|t| (k.as_str(), t.to_string()))));
+    }
+    for list in [&dto.backdrop_image_tags, &dto.parent_backdrop_image_tags].into_iter().flatten() {
+        tags.extend(list.iter().map(|t| ("Backdrop", t.clone())));
+    }
+    for (kind, tag) in [
+        ("Primary", &dto.series_primary_image_tag),
+        ("Thumb", &dto.parent_thumb_image_tag),
+    ] {
+        if let Some(tag) = tag {
+            tags.push((kind, tag.clone()));
+        }
+    }
+
+    let mut hashes = serde_json::Map::new();
+    for (kind, tag) in tags {
+        if let Some(hash) = get_blur_hash(image_tag_hash(&tag)) {
+            let entry = hashes
+                .entry(kind.to_string())
+                .or_insert_with(|| serde_json::Value::Object(Default::default()));
+            if let Some(map) = entry.as_object_mut() {
+                map.insert(tag, hash.into());
+            }
+        }
+    }
+    dto.image_blur_hashes = (!hashes.is_empty()).then_some(serde_json::Value::Object(hashes));
 }
 
 /// 将飞牛 PlayListItem 映射为 Jellyfin BaseItemDto
-pub fn map_playlist_item_to_dto(item: &FnosPlayListItem, server_id: &str, server: &str, token: &str) -> BaseItemDto {
+pub fn map_playlist_item_to_dto(item: &FnosPlayListItem, server_id: &str) -> BaseItemDto {
     let jf_type = map_type(&item.item_type);
     let is_folder = matches!(jf_type, "Series" | "Season" | "Folder");
     let duration = if item.duration > 0.0 {
@@ -62,16 +136,13 @@ pub fn map_playlist_item_to_dto(item: &FnosPlayListItem, server_id: &str, server
 
     register_item_type(&item.guid, &item.item_type);
 
-    // 缓存图片路径
+    // 缓存图片路径（与详情接口缓存的图片合并）
     let jf_id = to_jellyfin_id(&item.guid);
-    if !item.poster.is_empty() {
-        set_image_cache(&jf_id, CachedImage {
-            poster: Some(item.poster.clone()),
-            backdrop: None,
-            server: server.to_string(),
-            token: token.to_string(),
-        });
+    let images = CachedImage::from_fnos(&item.poster, "");
+    if !images.is_empty() {
+        set_image_cache(&jf_id, images.clone());
     }
+    let images = get_image_cache(&jf_id).unwrap_or(images);
 
     let mut dto = BaseItemDto {
         name: if !item.title.is_empty() {
@@ -79,18 +150,19 @@ pub fn map_playlist_item_to_dto(item: &FnosPlayListItem, server_id: &str, server
         } else {
             item.tv_title.clone()
         },
+        original_title: (!item.original_title.is_empty()).then(|| item.original_title.clone()),
         server_id: server_id.to_string(),
         id: to_jellyfin_id(&item.guid),
         can_delete: false,
-        can_download: false,
+        can_download: can_download(jf_type),
         overview: if item.overview.is_empty() { None } else { Some(item.overview.clone()) },
         community_rating: item.vote_average.parse::<f64>().ok().filter(|v| *v > 0.0),
         run_time_ticks: if duration > 0.0 { Some(seconds_to_ticks(duration)) } else { None },
         is_folder,
         item_type: jf_type.to_string(),
         media_type: map_media_type(jf_type).map(String::from),
-        image_tags: make_image_tags(&item.poster),
-        backdrop_image_tags: Some(vec![]),
+        image_tags: make_image_tags(&images),
+        backdrop_image_tags: make_backdrop_tags(&images),
         location_type: Some("FileSystem".into()),
         user_data: Some(UserItemDataDto {
             playback_position_ticks: if item.ts > 0.0 { seconds_to_ticks(item.ts) } else { 0 },
@@ -103,6 +175,7 @@ pub fn map_playlist_item_to_dto(item: &FnosPlayListItem, server_id: &str, server
                 None
             },
             unplayed_item_count: None,
+            last_played_date: None,
         }),
         ..Default::default()
     };
@@ -148,11 +221,127 @@ pub fn map_playlist_item_to_dto(item: &FnosPlayListItem, server_id: &str, server
         }
     }
 
+    apply_parent_images(&mut dto);
+    apply_sort_name(&mut dto, &item.sort_title);
+    apply_dates(&mut dto, &item.guid);
+    apply_video_meta(&mut dto, &item.guid);
+
     dto
 }
 
+/// 填充 SortName / ForcedSortName
+/// 飞牛中设置了与标题不同的排序标题时作为 ForcedSortName，SortName 由其（或标题）按拼音生成
+fn apply_sort_name(dto: &mut BaseItemDto, sort_title: &str) {
+    let sort_title = sort_title.trim();
+    if !sort_title.is_empty() && sort_title != dto.name {
+        dto.forced_sort_name = Some(sort_title.to_string());
+    }
+    dto.sort_name = Some(sort_name(dto.forced_sort_name.as_deref().unwrap_or(&dto.name)));
+}
+
+/// Unix 时间戳 → Jellyfin 日期字符串
+pub fn format_timestamp(ts: i64) -> Option<String> {
+    chrono::DateTime::from_timestamp(ts, 0).map(|t| t.format("%Y-%m-%dT%H:%M:%S.0000000Z").to_string())
+}
+
+/// 填充 DateCreated（桥接首次发现时间），剧集 / 季另填同值的 DateLastMediaAdded
+fn apply_dates(dto: &mut BaseItemDto, fnos_guid: &str) {
+    dto.date_created = date_added(fnos_guid).and_then(format_timestamp);
+    if matches!(dto.item_type.as_str(), "Series" | "Season") {
+        dto.date_last_media_added = dto.date_created.clone();
+    }
+}
+
+/// 填充 IsHD / Width / Height / VideoType / Video3DFormat / VideoRange（仅视频项目）
+/// 元数据来自 stream/list 缓存，尚未缓存时不填
+pub fn apply_video_meta(dto: &mut BaseItemDto, fnos_guid: &str) {
+    if dto.media_type.as_deref() != Some("Video") {
+        return;
+    }
+    if let Some(meta) = get_item_meta(fnos_guid) {
+        if meta.height > 0 {
+            dto.is_hd = Some(is_hd_size(meta.width, meta.height));
+            dto.width = Some(meta.width);
+            dto.height = Some(meta.height);
+        }
+        dto.video_type = Some(meta.video_type);
+        dto.video_3d_format = meta.video_3d_format;
+        dto.video_range = Some(meta.video_range);
+        dto.video_range_type = Some(meta.video_range_type);
+    }
+}
+
+/// 高清：宽 ≥ 1260 或高 ≥ 700（宽银幕 1920x800 也算）
+fn is_hd_size(width: i32, height: i32) -> bool {
+    width >= 1260 || height >= 700
+}
+
+/// 列表项目的画面尺寸 (宽, 高)，来自 stream/list 缓存，未缓存时为 None
+fn video_size(item: &FnosPlayListItem) -> Option<(i32, i32)> {
+    get_item_meta(&item.guid)
+        .filter(|meta| meta.height > 0)
+        .map(|meta| (meta.width, meta.height))
+}
+
+pub fn is_hd_item(item: &FnosPlayListItem) -> bool {
+    video_size(item).is_some_and(|(w, h)| is_hd_size(w, h))
+}
+
+pub fn is_4k_item(item: &FnosPlayListItem) -> bool {
+    video_size(item).is_some_and(|(w, h)| w >= 3800 || h >= 2100)
+}
+
+pub fn is_3d_item(item: &FnosPlayListItem) -> bool {
+    get_item_meta(&item.guid).is_some_and(|meta| meta.video_3d_format.is_some())
+}
+
+/// 由列表项与已映射的 BaseItemDto 构造搜索提示
+/// Thumb / Backdrop 缺失时使用父级（剧集）图片
+pub fn map_search_hint(item: &FnosPlayListItem, dto: &BaseItemDto) -> SearchHint {
+    let own_tag = |kind: &str| {
+        dto.image_tags
+            .as_ref()
+            .and_then(|t| t.get(kind))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    };
+    let (thumb_image_tag, thumb_image_item_id) = match own_tag("Thumb") {
+        Some(tag) => (Some(tag), Some(dto.id.clone())),
+        None => (dto.parent_thumb_image_tag.clone(), dto.parent_thumb_item_id.clone()),
+    };
+    let (backdrop_image_tag, backdrop_image_item_id) =
+        match dto.backdrop_image_tags.as_ref().and_then(|t| t.first()) {
+            Some(tag) => (Some(tag.clone()), Some(dto.id.clone())),
+            None => (
+                dto.parent_backdrop_image_tags.as_ref().and_then(|t| t.first().cloned()),
+                dto.parent_backdrop_item_id.clone(),
+            ),
+        };
+    SearchHint {
+        item_id: dto.id.clone(),
+        id: dto.id.clone(),
+        name: dto.name.clone(),
+        matched_term: None,
+        index_number: dto.index_number,
+        parent_index_number: dto.parent_index_number,
+        production_year: dto.production_year,
+        primary_image_tag: own_tag("Primary"),
+        thumb_image_tag,
+        thumb_image_item_id,
+        backdrop_image_tag,
+        backdrop_image_item_id,
+        item_type: dto.item_type.clone(),
+        is_folder: dto.is_folder,
+        run_time_ticks: dto.run_time_ticks,
+        media_type: dto.media_type.clone(),
+        series: dto.series_name.clone(),
+        primary_image_aspect_ratio: (item.poster_width > 0 && item.poster_height > 0)
+            .then(|| item.poster_width as f64 / item.poster_height as f64),
+    }
+}
+
 /// 将飞牛 PlayInfo 映射为 Jellyfin BaseItemDto
-pub fn map_play_info_to_dto(info: &FnosPlayInfo, server_id: &str, server: &str, token: &str) -> BaseItemDto {
+pub fn map_play_info_to_dto(info: &FnosPlayInfo, server_id: &str) -> BaseItemDto {
     let item = &info.item;
     let jf_type = map_type(&item.item_type);
     let is_folder = matches!(jf_type, "Series" | "Season");
@@ -166,14 +355,10 @@ pub fn map_play_info_to_dto(info: &FnosPlayInfo, server_id: &str, server: &str,
 
     // 缓存图片路径
     let jf_id = to_jellyfin_id(&item.guid);
-    if !item.posters.is_empty() || !item.still_path.is_empty() {
-        set_image_cache(&jf_id, CachedImage {
-            poster: if item.posters.is_empty() { None } else { Some(item.posters.clone()) },
-            backdrop: if item.still_path.is_empty() { None } else { Some(item.still_path.clone()) },
-            server: server.to_string(),
-            token: token.to_string(),
-        });
-    }
+    let mut images = CachedImage::from_fnos(&item.posters, &item.still_path);
+    images.detailed = true;
+    set_image_cache(&jf_id, images);
+    let images = get_image_cache(&jf_id).unwrap_or_default();
 
     let mut dto = BaseItemDto {
         name: if !item.title.is_empty() {
@@ -184,20 +369,15 @@ pub fn map_play_info_to_dto(info: &FnosPlayInfo, server_id: &str, server: &str,
         server_id: server_id.to_string(),
         id: to_jellyfin_id(&item.guid),
         can_delete: false,
-        can_download: false,
+        can_download: can_download(jf_type),
         overview: if item.overview.is_empty() { None } else { Some(item.overview.clone()) },
         community_rating: item.vote_average.parse::<f64>().ok().filter(|v| *v > 0.0),
         run_time_ticks: if duration > 0.0 { Some(seconds_to_ticks(duration)) } else { None },
         is_folder,
         item_type: jf_type.to_string(),
         media_type: map_media_type(jf_type).map(String::from),
-        image_tags: make_image_tags(&item.posters),
-        backdrop_image_tags: if !item.still_path.is_empty() {
-            make_image_tags(&item.still_path)
-                .and_then(|v| v.get("Primary").map(|p| vec![p.as_str().unwrap_or("").to_string()]))
-        } else {
-            Some(vec![])
-        },
+        image_tags: make_image_tags(&images),
+        backdrop_image_tags: make_backdrop_tags(&images),
         location_type: Some("FileSystem".into()),
         user_data: Some(UserItemDataDto {
             playback_position_ticks: if item.watched_ts > 0.0 {
@@ -214,6 +394,7 @@ pub fn map_play_info_to_dto(info: &FnosPlayInfo, server_id: &str, server: &str,
                 None
             },
             unplayed_item_count: None,
+            last_played_date: None,
         }),
         ..Default::default()
     };
@@ -256,6 +437,11 @@ pub fn map_play_info_to_dto(info: &FnosPlayInfo, server_id: &str, server: &str,
         }
     }
 
+    apply_parent_images(&mut dto);
+    apply_sort_name(&mut dto, &item.sort_title);
+    apply_dates(&mut dto, &item.guid);
+    apply_video_meta(&mut dto, &item.guid);
+
     dto
 }
 
@@ -281,3 +467,27 @@ pub fn make_collection_folder(
         ..Default::default()
     }
 }
+
+/// 将桥接层播放列表映射为 Jellyfin Playlist
+pub fn map_playlist_to_dto(playlist: &Playlist, server_id: &str) -> BaseItemDto {
+    BaseItemDto {
+        name: playlist.name.clone(),
+        server_id: server_id.to_string(),
+        id: playlist.jellyfin_id(),
+        can_delete: true,
+        can_download: false,
+        is_folder: true,
+        item_type: "Playlist".to_string(),
+        media_type: Some(if playlist.media_type.is_empty() {
+            "Video".to_string()
+        } else {
+            playlist.media_type.clone()
+        }),
+        parent_id: Some(to_jellyfin_id("view_playlists")),
+        child_count: Some(playlist.items.len() as i32),
+        image_tags: Some(serde_json::json!({})),
+        backdrop_image_tags: Some(vec![]),
+        location_type: Some("FileSystem".into()),
+        ..Default::default()
+    }
+}
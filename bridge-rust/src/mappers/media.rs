@@ -5,7 +5,10 @@ use dashmap::DashMap;
 use serde_json::json;
 use std::sync::LazyLock;
 
+use super::id::to_fnos_guid;
 use super::item::seconds_to_ticks;
+use crate::services::local_subtitle::subtitle_stream_values;
+use crate::types::jellyfin::UserConfiguration;
 
 /// 字幕信息
 #[derive(Debug, Clone)]
@@ -34,6 +37,100 @@ pub fn get_subtitle_info(media_source_id: &str, index: i32) -> Option<SubtitleIn
 /// 浏览器兼容的音频编解码器
 const BROWSER_COMPATIBLE_CODECS: &[&str] = &["aac", "mp3", "flac", "opus", "vorbis", "pcm_s16le", "pcm_f32le"];
 
+/// 语言代码归一化为 ISO 639-2/B（jellyfin-web 偏好设置使用的格式）
+fn normalize_language(lang: &str) -> String {
+    let lower = lang.trim().to_lowercase();
+    let code = match lower.as_str() {
+        "zh" | "zho" | "chi" | "chs" | "cht" | "cmn" | "yue" | "zh-cn" | "zh-tw" | "zh-hk" => "chi",
+        "en" | "eng" => "eng",
+        "ja" | "jpn" => "jpn",
+        "ko" | "kor" => "kor",
+        "fr" | "fre" | "fra" => "fre",
+        "de" | "ger" | "deu" => "ger",
+        "es" | "spa" => "spa",
+        "ru" | "rus" => "rus",
+        "it" | "ita" => "ita",
+        "pt" | "por" => "por",
+        "th" | "tha" => "tha",
+        _ => return lower,
+    };
+    code.to_string()
+}
+
+/// 解析偏好语言列表（支持逗号分隔多个）
+fn preferred_languages(pref: &str) -> Vec<String> {
+    pref.split(',')
+        .map(normalize_language)
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+/// 语言在偏好列表中的位置，不匹配返回 None
+fn language_rank(stream: &serde_json::Value, prefs: &[String]) -> Option<usize> {
+    let lang = normalize_language(stream["Language"].as_str().unwrap_or(""));
+    if lang.is_empty() {
+        return None;
+    }
+    prefs.iter().position(|p| *p == lang)
+}
+
+/// 按用户配置选择默认音轨（参照 Jellyfin MediaStreamSelector）
+/// 依次比较：偏好语言（位置越前越优先，未匹配排最后）→ 浏览器兼容编码 →
+/// PlayDefaultAudioTrack 时的默认音轨 → 原有顺序
+fn select_default_audio(audio: &[serde_json::Value], config: &UserConfiguration) -> Option<i32> {
+    let prefs = preferred_languages(&config.audio_language_preference);
+    let is_compatible = |s: &serde_json::Value| {
+        let codec = s["Codec"].as_str().unwrap_or("").to_lowercase();
+        BROWSER_COMPATIBLE_CODECS.contains(&codec.as_str())
+    };
+    let is_default = |s: &serde_json::Value| {
+        config.play_default_audio_track && s["IsDefault"].as_bool().unwrap_or(false)
+    };
+
+    audio.iter()
+        .min_by_key(|s| (language_rank(s, &prefs).unwrap_or(usize::MAX), !is_compatible(s), !is_default(s)))
+        .and_then(|s| s["Index"].as_i64().map(|i| i as i32))
+}
+
+/// 按用户配置选择默认字幕，-1 表示不显示
+/// SubtitleMode: Default / Always / OnlyForced / None / Smart
+fn select_default_subtitle(
+    subtitles: &[serde_json::Value],
+    audio_language: Option<&str>,
+    config: &UserConfiguration,
+) -> i32 {
+    let prefs = preferred_languages(&config.subtitle_language_preference);
+    let flag = |s: &serde_json::Value, key: &str| s[key].as_bool().unwrap_or(false);
+    let index_of = |s: &serde_json::Value| s["Index"].as_i64().unwrap_or(-1) as i32;
+
+    // 按偏好语言排序，未匹配的排在后面，保持原有顺序
+    let mut sorted: Vec<&serde_json::Value> = subtitles.iter().collect();
+    sorted.sort_by_key(|s| language_rank(s, &prefs).unwrap_or(usize::MAX));
+    let preferred = || sorted.iter().find(|s| language_rank(s, &prefs).is_some()).copied();
+    let forced = || sorted.iter().find(|s| flag(s, "IsForced")).copied();
+
+    let selected = match config.subtitle_mode.as_str() {
+        "None" => None,
+        "OnlyForced" => forced(),
+        "Always" => preferred().or_else(|| sorted.first().copied()),
+        "Smart" => {
+            // 音频语言不在字幕偏好中（外语）时显示偏好语言字幕
+            let audio_lang = audio_language.map(normalize_language).unwrap_or_default();
+            let is_foreign = !prefs.is_empty() && !prefs.contains(&audio_lang);
+            if is_foreign {
+                preferred().or_else(forced)
+            } else {
+                forced()
+            }
+        }
+        _ => sorted.iter()
+            .find(|s| flag(s, "IsDefault") || flag(s, "IsForced"))
+            .copied(),
+    };
+
+    selected.map(index_of).unwrap_or(-1)
+}
+
 /// 飞牛视频流 → Jellyfin MediaStream
 fn map_video_stream(vs: &serde_json::Value, index: i32) -> serde_json::Value {
     let codec = vs["codec_name"].as_str().unwrap_or("h264");
@@ -63,10 +160,69 @@ fn map_video_stream(vs: &serde_json::Value, index: i32) -> serde_json::Value {
         "ColorSpace": vs["color_space"].as_str(),
         "ColorTransfer": vs["color_transfer"].as_str(),
         "ColorPrimaries": vs["color_primaries"].as_str(),
+        "VideoRange": video_range(vs).0,
+        "VideoRangeType": video_range(vs).1,
         "DisplayTitle": format_video_title(vs),
     })
 }
 
+/// 视频动态范围 (VideoRange, VideoRangeType)
+/// 优先使用飞牛的 color_range_type，缺失时按 color_transfer / 位深推断
+pub fn video_range(vs: &serde_json::Value) -> (&'static str, &'static str) {
+    let range_type = vs["color_range_type"].as_str().unwrap_or("").to_lowercase();
+    if range_type.contains("dovi") || range_type.contains("dolby") || range_type == "dv" {
+        return ("HDR", "DOVI");
+    }
+    if range_type.contains("hdr10+") || range_type.contains("hdr10plus") {
+        return ("HDR", "HDR10Plus");
+    }
+    if range_type.contains("hlg") {
+        return ("HDR", "HLG");
+    }
+    if range_type.contains("hdr") {
+        return ("HDR", "HDR10");
+    }
+    if range_type == "sdr" {
+        return ("SDR", "SDR");
+    }
+    match vs["color_transfer"].as_str().unwrap_or("") {
+        "smpte2084" if vs["bit_depth"].as_i64().unwrap_or(10) >= 10 => ("HDR", "HDR10"),
+        "arib-std-b67" => ("HDR", "HLG"),
+        _ => ("SDR", "SDR"),
+    }
+}
+
+/// 按文件路径判断 VideoType（ISO 镜像 / 蓝光目录 / DVD 目录 / 普通文件）
+pub fn video_type(path: &str) -> &'static str {
+    let lower = path.to_lowercase();
+    if lower.ends_with(".iso") {
+        "Iso"
+    } else if lower.contains("/bdmv/") {
+        "BluRay"
+    } else if lower.contains("/video_ts/") {
+        "Dvd"
+    } else {
+        "VideoFile"
+    }
+}
+
+/// 按文件名识别 3D 格式（需同时带有 "3d" 标记，如 Movie.3D.HSBS.mkv）
+pub fn video_3d_format(path: &str) -> Option<&'static str> {
+    let name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    let tokens: Vec<&str> = name.split(|c: char| !c.is_ascii_alphanumeric()).collect();
+    if !tokens.contains(&"3d") {
+        return None;
+    }
+    tokens.iter().find_map(|t| match *t {
+        "hsbs" | "sbs" => Some("HalfSideBySide"),
+        "fsbs" => Some("FullSideBySide"),
+        "htab" | "tab" | "hou" => Some("HalfTopAndBottom"),
+        "ftab" | "ou" => Some("FullTopAndBottom"),
+        "mvc" => Some("MVC"),
+        _ => None,
+    })
+}
+
 /// 飞牛音频流 → Jellyfin MediaStream
 fn map_audio_stream(audio: &serde_json::Value, index: i32) -> serde_json::Value {
     let codec = audio["codec_name"].as_str().unwrap_or("aac");
@@ -129,17 +285,24 @@ fn map_subtitle_stream(ss: &serde_json::Value, index: i32, is_external: bool) ->
     })
 }
 
+/// 一个版本（media_guid）的文件与流
+struct MediaVersion<'a> {
+    media_guid: &'a str,
+    file_name: &'a str,
+    video_streams: &'a [&'a serde_json::Value],
+    audio_streams: &'a [&'a serde_json::Value],
+    subtitle_streams: &'a [&'a serde_json::Value],
+    file_info: Option<&'a serde_json::Value>,
+}
+
 /// 构造单个 MediaSourceInfo
 fn build_single_media_source(
-    media_guid: &str,
-    file_name: &str,
-    video_streams: &[&serde_json::Value],
-    audio_streams: &[&serde_json::Value],
-    subtitle_streams: &[&serde_json::Value],
-    file_info: Option<&serde_json::Value>,
+    version: &MediaVersion,
     duration: f64,
     video_stream_url: &str,
+    user_config: &UserConfiguration,
 ) -> serde_json::Value {
+    let MediaVersion { media_guid, file_name, video_streams, audio_streams, subtitle_streams, file_info } = *version;
     let mut stream_index: i32 = 0;
     let mut media_streams = Vec::new();
 
@@ -150,23 +313,22 @@ fn build_single_media_source(
     }
 
     // 音频流
-    let audio_start_index = stream_index;
+    let mut mapped_audio = Vec::new();
     for audio in audio_streams {
-        media_streams.push(map_audio_stream(audio, stream_index));
+        mapped_audio.push(map_audio_stream(audio, stream_index));
         stream_index += 1;
     }
 
-    // 默认音频：优先浏览器兼容
-    let mut default_audio_index = audio_start_index;
-    for (i, audio) in audio_streams.iter().enumerate() {
-        let codec = audio["codec_name"].as_str().unwrap_or("").to_lowercase();
-        if BROWSER_COMPATIBLE_CODECS.contains(&codec.as_str()) {
-            default_audio_index = audio_start_index + i as i32;
-            break;
-        }
-    }
+    // 默认音频：按用户语言偏好选择
+    let default_audio_index = select_default_audio(&mapped_audio, user_config);
+    let default_audio_language = mapped_audio.iter()
+        .find(|s| s["Index"].as_i64().map(|i| i as i32) == default_audio_index)
+        .and_then(|s| s["Language"].as_str())
+        .map(|s| s.to_string());
+    media_streams.extend(mapped_audio);
 
     // 字幕流
+    let subtitle_start = media_streams.len();
     for ss in subtitle_streams {
         let is_external = ss["is_external"].as_i64().unwrap_or(0) == 1;
         
@@ -206,6 +368,13 @@ fn build_single_media_source(
         }
     }
 
+    // 默认字幕：按用户字幕模式选择
+    let default_subtitle_index = select_default_subtitle(
+        &media_streams[subtitle_start..],
+        default_audio_language.as_deref(),
+        user_config,
+    );
+
     // 容器格式
     let container = if !file_name.is_empty() {
         file_name.rsplit('.').next().unwrap_or("mkv")
@@ -282,12 +451,20 @@ fn build_single_media_source(
         if let Some(size) = fi["size"].as_i64() {
             source["Size"] = json!(size);
         }
+        let path = fi["path"].as_str().unwrap_or(file_name);
+        source["VideoType"] = json!(video_type(path));
+        if let Some(format) = video_3d_format(path) {
+            source["Video3DFormat"] = json!(format);
+        }
     }
     if duration > 0.0 {
         source["RunTimeTicks"] = json!(seconds_to_ticks(duration));
     }
-    if !audio_streams.is_empty() {
-        source["DefaultAudioStreamIndex"] = json!(default_audio_index);
+    if let Some(idx) = default_audio_index {
+        source["DefaultAudioStreamIndex"] = json!(idx);
+    }
+    if !subtitle_streams.is_empty() {
+        source["DefaultSubtitleStreamIndex"] = json!(default_subtitle_index);
     }
     if let Some(vs) = vs0 {
         if let Some(bps) = vs["bps"].as_i64() {
@@ -301,6 +478,46 @@ fn build_single_media_source(
     source
 }
 
+/// 各版本的 media_guid，按分辨率降序（与 build_media_sources 返回的 MediaSources 顺序一致）
+fn sorted_media_guids(video_streams: &[serde_json::Value]) -> Vec<String> {
+    let mut media_guids: Vec<String> = Vec::new();
+    for mg in video_streams.iter().filter_map(|v| v["media_guid"].as_str()) {
+        if !mg.is_empty() && !media_guids.iter().any(|g| g == mg) {
+            media_guids.push(mg.to_string());
+        }
+    }
+    let height = |mg: &str| {
+        video_streams.iter()
+            .find(|v| v["media_guid"].as_str() == Some(mg))
+            .and_then(|v| v["height"].as_i64())
+            .unwrap_or(0)
+    };
+    media_guids.sort_by_key(|mg| std::cmp::Reverse(height(mg)));
+    media_guids
+}
+
+/// 桥接层字幕在某个版本中的起始流序号（视频 + 音频 + 飞牛字幕的数量），与 build_media_sources 的编号一致
+/// 未指定 media_source_id 时取第一个版本；指定的版本不存在时为 None
+pub fn local_subtitle_offset(
+    video_streams: &[serde_json::Value],
+    audio_streams: &[serde_json::Value],
+    subtitle_streams: &[serde_json::Value],
+    media_source_id: Option<&str>,
+) -> Option<i32> {
+    let media_guids = sorted_media_guids(video_streams);
+    if media_guids.is_empty() {
+        return Some((video_streams.len() + audio_streams.len() + subtitle_streams.len()) as i32);
+    }
+    let mg = match media_source_id {
+        Some(id) => media_guids.iter().find(|mg| *mg == id)?,
+        None => &media_guids[0],
+    };
+    let count = |streams: &[serde_json::Value]| {
+        streams.iter().filter(|s| s["media_guid"].as_str() == Some(mg.as_str())).count()
+    };
+    Some((count(video_streams) + count(audio_streams) + count(subtitle_streams)) as i32)
+}
+
 /// 按 media_guid 分组构造多个 MediaSource
 pub fn build_media_sources(
     item_id: &str,
@@ -309,17 +526,14 @@ pub fn build_media_sources(
     audio_streams: &[serde_json::Value],
     subtitle_streams: &[serde_json::Value],
     duration: f64,
+    user_config: &UserConfiguration,
 ) -> Vec<serde_json::Value> {
-    // 收集所有 media_guid
-    let mut media_guids: Vec<String> = Vec::new();
-    let mut seen = std::collections::HashSet::new();
-    for vs in video_streams {
-        if let Some(mg) = vs["media_guid"].as_str() {
-            if !mg.is_empty() && seen.insert(mg.to_string()) {
-                media_guids.push(mg.to_string());
-            }
-        }
-    }
+    // 桥接层上传的外挂字幕，追加到每个版本
+    let local_subtitles = to_fnos_guid(item_id)
+        .map(|guid| subtitle_stream_values(&guid))
+        .unwrap_or_default();
+
+    let media_guids = sorted_media_guids(video_streams);
 
     if media_guids.is_empty() {
         let file_info = files.first();
@@ -333,25 +547,18 @@ pub fn build_media_sources(
         let url = format!("/Videos/{}/stream?static=true&mediaSourceId={}", item_id, media_guid);
         let vs_refs: Vec<&serde_json::Value> = video_streams.iter().collect();
         let as_refs: Vec<&serde_json::Value> = audio_streams.iter().collect();
-        let ss_refs: Vec<&serde_json::Value> = subtitle_streams.iter().collect();
-        return vec![build_single_media_source(
-            media_guid, file_name, &vs_refs, &as_refs, &ss_refs, file_info, duration, &url,
-        )];
+        let ss_refs: Vec<&serde_json::Value> = subtitle_streams.iter().chain(&local_subtitles).collect();
+        let version = MediaVersion {
+            media_guid,
+            file_name,
+            video_streams: &vs_refs,
+            audio_streams: &as_refs,
+            subtitle_streams: &ss_refs,
+            file_info,
+        };
+        return vec![build_single_media_source(&version, duration, &url, user_config)];
     }
 
-    // 按分辨率降序排列
-    media_guids.sort_by(|a, b| {
-        let ha = video_streams.iter()
-            .find(|v| v["media_guid"].as_str() == Some(a.as_str()))
-            .and_then(|v| v["height"].as_i64())
-            .unwrap_or(0);
-        let hb = video_streams.iter()
-            .find(|v| v["media_guid"].as_str() == Some(b.as_str()))
-            .and_then(|v| v["height"].as_i64())
-            .unwrap_or(0);
-        hb.cmp(&ha)
-    });
-
     let mut sources = Vec::new();
     for mg in &media_guids {
         let my_vs: Vec<&serde_json::Value> = video_streams.iter()
@@ -362,6 +569,7 @@ pub fn build_media_sources(
             .collect();
         let my_ss: Vec<&serde_json::Value> = subtitle_streams.iter()
             .filter(|s| s["media_guid"].as_str() == Some(mg.as_str()))
+            .chain(&local_subtitles)
             .collect();
         let my_file = files.iter().find(|f| f["guid"].as_str() == Some(mg.as_str()));
         let file_name = my_file
@@ -370,9 +578,15 @@ pub fn build_media_sources(
             .unwrap_or("video");
         let url = format!("/Videos/{}/stream?static=true&mediaSourceId={}", item_id, mg);
 
-        sources.push(build_single_media_source(
-            mg, file_name, &my_vs, &my_as, &my_ss, my_file, duration, &url,
-        ));
+        let version = MediaVersion {
+            media_guid: mg,
+            file_name,
+            video_streams: &my_vs,
+            audio_streams: &my_as,
+            subtitle_streams: &my_ss,
+            file_info: my_file,
+        };
+        sources.push(build_single_media_source(&version, duration, &url, user_config));
     }
 
     sources
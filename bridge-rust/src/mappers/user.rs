@@ -1,11 +1,15 @@
 /// 用户信息映射器
 /// 飞牛 UserInfo → Jellyfin UserDto
 
+use crate::fnos_client::signature::md5_hex;
+use crate::services::session::get_known_user;
+use crate::services::user_config::get_user_config;
 use crate::types::fnos::FnosUserInfo;
 use crate::types::jellyfin::{UserConfiguration, UserDto, UserPolicy};
 
 fn default_user_config() -> UserConfiguration {
     UserConfiguration {
+        audio_language_preference: String::new(),
         play_default_audio_track: true,
         subtitle_language_preference: String::new(),
         display_missing_episodes: false,
@@ -18,12 +22,32 @@ fn default_user_config() -> UserConfiguration {
         remember_audio_selections: true,
         remember_subtitle_selections: true,
         enable_next_episode_auto_play: true,
+        force_sdr_transcoding: false,
     }
 }
 
+/// 用户配置：已保存的优先，否则为默认值
+pub fn user_config(user_id: &str) -> UserConfiguration {
+    get_user_config(user_id).unwrap_or_else(default_user_config)
+}
+
+/// 是否允许下载：仅视频类项目
+pub fn can_download(jf_type: &str) -> bool {
+    matches!(jf_type, "Movie" | "Episode" | "Video")
+}
+
+/// 管理员（ADMIN_USERS）可管理字幕与删除内容；飞牛不提供媒体库权限，所有用户可访问全部媒体库
+fn map_user_policy(is_admin: bool) -> UserPolicy {
+    let mut policy = default_user_policy();
+    policy.is_administrator = is_admin;
+    policy.enable_content_deletion = is_admin;
+    policy.enable_subtitle_management = is_admin;
+    policy
+}
+
 fn default_user_policy() -> UserPolicy {
     UserPolicy {
-        is_administrator: true,
+        is_administrator: false,
         is_hidden: false,
         is_disabled: false,
         enable_user_preference_access: true,
@@ -38,11 +62,13 @@ fn default_user_policy() -> UserPolicy {
         enable_playback_remuxing: true,
         enable_content_deletion: false,
         enable_content_downloading: true,
+        enable_subtitle_management: false,
         enable_sync_transcoding: false,
         enable_media_conversion: false,
         enable_all_devices: true,
         enable_all_channels: true,
         enable_all_folders: true,
+        enabled_folders: vec![],
         enable_public_sharing: false,
         invalid_login_attempt_count: 0,
         login_attempts_before_lockout: -1,
@@ -56,10 +82,23 @@ fn default_user_policy() -> UserPolicy {
     }
 }
 
+/// 头像路径 → PrimaryImageTag
+pub fn make_avatar_tag(avatar: &str) -> Option<String> {
+    if avatar.is_empty() {
+        return None;
+    }
+    Some(md5_hex(avatar))
+}
+
+fn millis_to_rfc3339(ms: i64) -> Option<String> {
+    chrono::DateTime::from_timestamp_millis(ms).map(|d| d.to_rfc3339())
+}
+
 pub fn map_user_to_jellyfin(
     fnos_user: &FnosUserInfo,
     user_id: &str,
     server_id: &str,
+    is_admin: bool,
 ) -> UserDto {
     let name = if !fnos_user.nickname.is_empty() {
         &fnos_user.nickname
@@ -69,19 +108,31 @@ pub fn map_user_to_jellyfin(
         "User"
     };
 
+    // 登录/活动时间来自会话存储，未知时退回当前时间
     let now = chrono::Utc::now().to_rfc3339();
+    let known = get_known_user(user_id);
+    let last_login_date = known.as_ref()
+        .and_then(|u| millis_to_rfc3339(u.last_login))
+        .unwrap_or_else(|| now.clone());
+    let last_activity_date = known.as_ref()
+        .and_then(|u| millis_to_rfc3339(u.last_activity))
+        .unwrap_or(now);
+
+    // 飞牛账号均需密码登录
+    let has_password = true;
 
     UserDto {
         name: name.to_string(),
         server_id: server_id.to_string(),
         id: user_id.to_string(),
-        has_password: true,
-        has_configured_password: true,
+        primary_image_tag: make_avatar_tag(&fnos_user.avatar),
+        has_password,
+        has_configured_password: has_password,
         has_configured_easy_password: false,
         enable_auto_login: false,
-        last_login_date: Some(now.clone()),
-        last_activity_date: Some(now),
-        configuration: default_user_config(),
-        policy: default_user_policy(),
+        last_login_date: Some(last_login_date),
+        last_activity_date: Some(last_activity_date),
+        configuration: user_config(user_id),
+        policy: map_user_policy(is_admin),
     }
 }
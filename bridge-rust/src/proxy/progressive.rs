@@ -0,0 +1,420 @@
+/// HLS → 渐进式流
+/// 启动飞牛转码会话，按顺序拉取 HLS 分片拼接为单个 MPEG-TS 流
+/// 供不支持 HLS 的客户端播放或下载转码副本（飞牛只产出 TS 分片；请求 mp4 时由 ffmpeg 重封装为 fragmented MP4）
+
+use axum::{
+    body::{Body, Bytes},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use futures_util::{Stream, StreamExt};
+use reqwest::Client;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{debug, error, warn};
+
+use crate::cache::stream_list::cached_get_stream_list;
+use crate::config::BridgeConfig;
+use crate::fnos_client::signature::generate_authx_string;
+use crate::mappers::item::ticks_to_seconds;
+use crate::services::fnos::fnos_get_play_info;
+use crate::services::hls_session::{
+    get_or_create_hls_session, get_stream_meta, hls_session_key, is_current_one_off_session, register_stream_meta,
+    release_one_off_session, start_hls_session_at, StreamMeta,
+};
+use crate::services::session::SessionData;
+
+/// 分片尚未转码完成时的重试间隔与次数
+const SEGMENT_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+const SEGMENT_MAX_RETRIES: u32 = 120;
+
+/// 渐进式流的输出容器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressiveContainer {
+    /// 直接拼接飞牛的 TS 分片
+    Ts,
+    /// 拼接后的 TS 经 ffmpeg 重封装（不重新编码）
+    Mp4,
+}
+
+impl ProgressiveContainer {
+    /// 按 Container 参数选择，未指定时为 ts；其他容器不支持
+    pub fn from_query(container: Option<&str>) -> Option<Self> {
+        match container.map(|c| c.to_ascii_lowercase()).as_deref() {
+            None | Some("ts") | Some("mpegts") => Some(Self::Ts),
+            Some("mp4") => Some(Self::Mp4),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Ts => "ts",
+            Self::Mp4 => "mp4",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Ts => "video/mp2t",
+            Self::Mp4 => "video/mp4",
+        }
+    }
+}
+
+/// 拼接状态
+struct ConcatState {
+    client: Client,
+    server: String,
+    token: String,
+    session_guid: String,
+    segments: Vec<String>,
+    next: usize,
+    ended: bool,
+    /// 跳转播放时的一次性会话登记，响应 body 释放时随之注销
+    one_off: Option<OneOffGuard>,
+}
+
+impl ConcatState {
+    /// 一次性会话已被同一客户端的新跳转替换
+    fn superseded(&self) -> bool {
+        self.one_off.as_ref().is_some_and(|g| !is_current_one_off_session(&g.client, g.id))
+    }
+}
+
+struct OneOffGuard {
+    client: String,
+    id: u64,
+}
+
+impl Drop for OneOffGuard {
+    fn drop(&mut self) {
+        release_one_off_session(&self.client, self.id);
+    }
+}
+
+/// 确保 media_guid 有流元数据（未经 PlaybackInfo 直接请求流时补充）
+pub async fn ensure_stream_meta(
+    session: &SessionData,
+    fnos_guid: &str,
+    media_guid: &str,
+    config: &BridgeConfig,
+) -> bool {
+    if get_stream_meta(media_guid).is_some() {
+        return true;
+    }
+
+    let stream_result = cached_get_stream_list(&session.fnos_server, &session.fnos_token, fnos_guid, config).await;
+    let Some(sd) = stream_result.data.filter(|_| stream_result.success) else {
+        return false;
+    };
+    let empty = vec![];
+    let video_streams = sd["video_streams"].as_array().unwrap_or(&empty);
+    let audio_streams = sd["audio_streams"].as_array().unwrap_or(&empty);
+    let Some(vs) = video_streams.iter()
+        .find(|v| v["media_guid"].as_str() == Some(media_guid))
+        .or_else(|| video_streams.first())
+    else {
+        return false;
+    };
+    let audio = audio_streams.iter()
+        .find(|a| a["media_guid"].as_str() == Some(media_guid))
+        .or_else(|| audio_streams.first());
+
+    let duration = match fnos_get_play_info(&session.fnos_server, &session.fnos_token, fnos_guid, config).await {
+        r if r.success => r.data.map(|d| d.item.duration).unwrap_or(0.0),
+        _ => 0.0,
+    };
+
+    register_stream_meta(
+        media_guid,
+        StreamMeta {
+            media_guid: media_guid.to_string(),
+            item_guid: fnos_guid.to_string(),
+            video_guid: vs["guid"].as_str().unwrap_or("").to_string(),
+            video_encoder: vs["codec_name"].as_str().unwrap_or("h264").to_string(),
+            resolution: vs["resolution_type"].as_str().unwrap_or("1080p").to_string(),
+            bitrate: vs["bps"].as_i64().unwrap_or(15_000_000),
+            audio_encoder: "aac".to_string(),
+            audio_guid: audio.and_then(|a| a["guid"].as_str()).unwrap_or("").to_string(),
+            subtitle_guid: String::new(),
+            channels: audio.and_then(|a| a["channels"].as_i64()).unwrap_or(2) as i32,
+            duration,
+            forced_sdr: false,
+        },
+    );
+    true
+}
+
+/// 分片在飞牛上的路径（m3u8 中可能是相对或绝对路径）
+fn segment_path(session_guid: &str, segment: &str) -> String {
+    if segment.starts_with('/') {
+        segment.to_string()
+    } else {
+        format!("/v/media/{}/{}", session_guid, segment)
+    }
+}
+
+async fn fnos_get(client: &Client, server: &str, token: &str, path: &str) -> Option<reqwest::Response> {
+    client
+        .get(format!("{}{}", server, path))
+        .header("Authorization", token)
+        .header("Cookie", "mode=relay")
+        .header("Authx", generate_authx_string(path, None))
+        .send()
+        .await
+        .ok()
+}
+
+/// 拉取媒体播放列表，返回 (分片列表, 是否已结束)
+/// 主播放列表时跟随第一个子播放列表
+async fn fetch_media_playlist(state: &mut ConcatState) -> Option<(Vec<String>, bool)> {
+    let mut path = format!("/v/media/{}/preset.m3u8", state.session_guid);
+    for _ in 0..2 {
+        let resp = fnos_get(&state.client, &state.server, &state.token, &path).await?;
+        if !resp.status().is_success() {
+            return None;
+        }
+        let text = resp.text().await.ok()?;
+        let entries: Vec<String> = text.lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(String::from)
+            .collect();
+        if let Some(variant) = entries.iter().find(|e| e.ends_with(".m3u8")) {
+            path = segment_path(&state.session_guid, variant);
+            continue;
+        }
+        return Some((entries, text.contains("#EXT-X-ENDLIST")));
+    }
+    None
+}
+
+/// 拉取单个分片，转码未完成时重试
+async fn fetch_segment(state: &ConcatState, segment: &str) -> Option<Bytes> {
+    let path = segment_path(&state.session_guid, segment);
+    for attempt in 0..SEGMENT_MAX_RETRIES {
+        if state.superseded() {
+            return None;
+        }
+        if let Some(resp) = fnos_get(&state.client, &state.server, &state.token, &path).await {
+            let status = resp.status();
+            if status.is_success() {
+                return resp.bytes().await.ok();
+            }
+            if status == reqwest::StatusCode::GONE {
+                warn!("[PROGRESSIVE] 转码会话已失效: {}", state.session_guid);
+                return None;
+            }
+        }
+        if attempt % 20 == 19 {
+            debug!("[PROGRESSIVE] 等待分片转码: {} (第 {} 次)", segment, attempt + 1);
+        }
+        tokio::time::sleep(SEGMENT_RETRY_INTERVAL).await;
+    }
+    error!("[PROGRESSIVE] 分片等待超时: {}", segment);
+    None
+}
+
+/// 取下一个分片；已知分片用完且播放列表未结束时刷新播放列表
+async fn next_chunk(mut state: ConcatState) -> Option<(Result<Bytes, std::io::Error>, ConcatState)> {
+    if state.superseded() {
+        debug!("[PROGRESSIVE] 客户端已跳转到新位置，停止拉取: {}", state.session_guid);
+        return None;
+    }
+    let mut idle_refreshes = 0;
+    while state.next >= state.segments.len() {
+        if state.ended || idle_refreshes >= SEGMENT_MAX_RETRIES {
+            return None;
+        }
+        tokio::time::sleep(SEGMENT_RETRY_INTERVAL).await;
+        let (segments, ended) = fetch_media_playlist(&mut state).await?;
+        if segments.len() <= state.segments.len() {
+            idle_refreshes += 1;
+        }
+        state.segments = segments;
+        state.ended = ended;
+    }
+
+    let segment = state.segments[state.next].clone();
+    let bytes = fetch_segment(&state, &segment).await?;
+    state.next += 1;
+    Some((Ok(bytes), state))
+}
+
+/// 解析 DLNA TimeSeekRange 头（"npt=123.4-" 或 "npt=00:02:03.4-"），返回起始秒数
+pub fn parse_time_seek_range(value: &str) -> Option<f64> {
+    let start = value.trim().strip_prefix("npt=")?.split('-').next()?.trim();
+    if start.contains(':') {
+        let parts: Vec<f64> = start.split(':').map(|p| p.parse::<f64>().ok()).collect::<Option<_>>()?;
+        Some(parts.iter().fold(0.0, |acc, v| acc * 60.0 + v))
+    } else {
+        start.parse::<f64>().ok()
+    }
+}
+
+/// 将 MPEG-TS 流经 ffmpeg 重封装为 fragmented MP4（只复制音视频流，不重新编码）
+/// ffmpeg 随返回的流释放而结束，写入端随之停止拉取分片
+fn remux_to_fmp4<S>(ffmpeg: &str, input: S) -> std::io::Result<impl Stream<Item = Result<Bytes, std::io::Error>>>
+where
+    S: Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static,
+{
+    let mut child = tokio::process::Command::new(ffmpeg)
+        .args([
+            "-hide_banner", "-loglevel", "error",
+            "-f", "mpegts", "-i", "pipe:0",
+            "-map", "0:v:0", "-map", "0:a:0?",
+            "-c", "copy",
+            "-f", "mp4", "-movflags", "frag_keyframe+empty_moov+default_base_moof",
+            "pipe:1",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+    let (Some(mut stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+        return Err(std::io::Error::other("ffmpeg 管道不可用"));
+    };
+
+    tokio::spawn(async move {
+        let mut input = Box::pin(input);
+        while let Some(Ok(chunk)) = input.next().await {
+            if stdin.write_all(&chunk).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(futures_util::stream::unfold((stdout, child), |(mut stdout, child)| async move {
+        let mut buf = vec![0u8; 64 * 1024];
+        match stdout.read(&mut buf).await {
+            Ok(0) => None,
+            Ok(n) => {
+                buf.truncate(n);
+                Some((Ok(Bytes::from(buf)), (stdout, child)))
+            }
+            Err(e) => Some((Err(e), (stdout, child))),
+        }
+    }))
+}
+
+/// 转码渐进式流响应
+/// `start_ticks` > 0 时从该位置启动独立的转码会话（跳转播放）
+/// `download_name` 有值时以附件形式返回
+pub async fn progressive_stream(
+    config: &BridgeConfig,
+    session: &SessionData,
+    fnos_guid: &str,
+    media_guid: &str,
+    start_ticks: i64,
+    container: ProgressiveContainer,
+    download_name: Option<&str>,
+) -> Response {
+    if !ensure_stream_meta(session, fnos_guid, media_guid, config).await {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let start_seconds = ticks_to_seconds(start_ticks.max(0));
+    // 跳转时的一次性会话按客户端登记，下次跳转或连接断开即停止
+    let hls_session = if start_seconds >= 1.0 {
+        start_hls_session_at(
+            &session.fnos_server, &session.fnos_token, &session.access_token, media_guid, start_seconds, config,
+        )
+        .await
+        .map(|(guid, _, id)| (guid, Some(OneOffGuard { client: session.access_token.clone(), id })))
+    } else {
+        let forced_sdr = get_stream_meta(media_guid).is_some_and(|m| m.forced_sdr);
+        let key = hls_session_key(media_guid, forced_sdr);
+        get_or_create_hls_session(&session.fnos_server, &session.fnos_token, &key, config)
+            .await
+            .map(|(guid, _)| (guid, None))
+    };
+    let Some((session_guid, one_off)) = hls_session else {
+        return StatusCode::BAD_GATEWAY.into_response();
+    };
+
+    let client = Client::builder()
+        .danger_accept_invalid_certs(config.ignore_cert)
+        .timeout(Duration::from_secs(60))
+        .build()
+        .unwrap_or_default();
+
+    let mut state = ConcatState {
+        client,
+        server: session.fnos_server.clone(),
+        token: session.fnos_token.clone(),
+        session_guid,
+        segments: vec![],
+        next: 0,
+        ended: false,
+        one_off,
+    };
+
+    // 首次拉取播放列表，确认转码已启动
+    match fetch_media_playlist(&mut state).await {
+        Some((segments, ended)) => {
+            debug!(
+                "[PROGRESSIVE] 开始拼接: mediaGuid={}, start={}s, 分片={}, ended={}",
+                media_guid, start_seconds, segments.len(), ended
+            );
+            state.segments = segments;
+            state.ended = ended;
+        }
+        None => {
+            error!("[PROGRESSIVE] 获取播放列表失败: mediaGuid={}", media_guid);
+            return StatusCode::BAD_GATEWAY.into_response();
+        }
+    }
+
+    let stream = futures_util::stream::unfold(state, next_chunk);
+    let body = match container {
+        ProgressiveContainer::Ts => Body::from_stream(stream),
+        ProgressiveContainer::Mp4 => match remux_to_fmp4(&config.ffmpeg_path, stream) {
+            Ok(stream) => Body::from_stream(stream),
+            Err(e) => {
+                error!("[PROGRESSIVE] 启动 ffmpeg 重封装失败: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        },
+    };
+
+    // 长度未知，不支持字节 Range；DLNA 客户端通过 TimeSeekRange / StartTimeTicks 跳转
+    let mut builder = Response::builder()
+        .status(200)
+        .header("content-type", container.content_type())
+        .header("accept-ranges", "none")
+        .header("transferMode.dlna.org", "Streaming")
+        .header("contentFeatures.dlna.org", "DLNA.ORG_OP=10;DLNA.ORG_CI=1;DLNA.ORG_FLAGS=01100000000000000000000000000000")
+        .header("access-control-allow-origin", "*")
+        .header("cache-control", "no-store, no-cache, must-revalidate");
+    if let Some(name) = download_name {
+        builder = builder.header("content-disposition", content_disposition(name));
+    }
+    if let Some(meta) = get_stream_meta(media_guid) {
+        if meta.duration > 0.0 {
+            let remaining = (meta.duration - start_seconds).max(0.0);
+            builder = builder.header("x-content-duration", format!("{:.3}", remaining));
+        }
+    }
+    builder
+        .body(body)
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+/// 构造 attachment 的 Content-Disposition（RFC 6266，非 ASCII 文件名用 filename*）
+pub fn content_disposition(file_name: &str) -> String {
+    let ascii: String = file_name.chars()
+        .map(|c| if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' { c } else { '_' })
+        .collect();
+    let mut encoded = String::new();
+    for b in file_name.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", b));
+        }
+    }
+    format!("attachment; filename=\"{}\"; filename*=UTF-8''{}", ascii, encoded)
+}
@@ -4,10 +4,10 @@
 use axum::{
     body::Body,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     routing::get,
-    Router,
+    Extension, Router,
 };
 use reqwest::Client;
 use serde::Deserialize;
@@ -16,11 +16,16 @@ use tracing::{debug, error, info};
 
 use crate::config::BridgeConfig;
 use crate::fnos_client::signature::generate_authx_string;
+use crate::cache::stream_list::cached_get_stream_list;
 use crate::mappers::id::to_fnos_guid;
-use crate::middleware::auth::{extract_token, require_auth};
+use crate::mappers::item::{map_type, seconds_to_ticks};
+use crate::mappers::user::can_download;
+use crate::middleware::auth::{extract_token, optional_auth, require_auth};
 use crate::services::fnos::{fnos_get_play_info, fnos_get_stream};
-use crate::services::hls_session::{clear_hls_session, get_cached_hls_session, get_or_create_hls_session};
+use crate::services::hls_session::{expire_hls_session, get_cached_hls_session, get_or_create_hls_session};
 use crate::services::session::{get_session, SessionData};
+use crate::services::trickplay::resolve_ffmpeg_ticket;
+use super::progressive::{content_disposition, parse_time_seek_range, progressive_stream, ProgressiveContainer};
 
 /// 从客户端透传到上游的请求头
 const PASSTHROUGH_HEADERS: &[&str] = &[
@@ -37,14 +42,20 @@ const FORWARD_HEADERS: &[&str] = &[
 
 pub fn router() -> Router<BridgeConfig> {
     Router::new()
+        // 直链：ffmpeg 子进程以任务票据访问，在处理函数中校验
         .route(
             "/Videos/{itemId}/stream",
-            get(video_stream).layer(axum::middleware::from_fn(require_auth)),
+            get(video_stream).layer(axum::middleware::from_fn(optional_auth)),
         )
         .route(
             "/Videos/{itemId}/stream/{ext}",
             get(video_stream_with_ext).layer(axum::middleware::from_fn(require_auth)),
         )
+        // 下载原始文件
+        .route(
+            "/Items/{itemId}/Download",
+            get(item_download).layer(axum::middleware::from_fn(require_auth)),
+        )
         // HLS 播放列表 - 支持带和不带 /Videos 前缀
         .route("/Videos/{mediaGuid}/hls/{file}", get(hls_stream))
         .route("/{mediaGuid}/hls/{file}", get(hls_stream))
@@ -53,12 +64,22 @@ pub fn router() -> Router<BridgeConfig> {
             "/Videos/{itemId}/{mediaSourceId}/Subtitles/{index}/Stream/{format}",
             get(subtitle_stream).layer(axum::middleware::from_fn(require_auth)),
         )
+        .route(
+            "/Videos/{itemId}/{mediaSourceId}/Subtitles/{index}/{startPositionTicks}/Stream/{format}",
+            get(subtitle_stream_with_offset).layer(axum::middleware::from_fn(require_auth)),
+        )
 }
 
 #[derive(Deserialize, Default)]
 struct StreamQuery {
     #[serde(rename = "mediaSourceId")]
     media_source_id: Option<String>,
+    #[serde(rename = "static")]
+    is_static: Option<String>,
+    #[serde(alias = "Container", alias = "container")]
+    container: Option<String>,
+    #[serde(alias = "StartTimeTicks", alias = "startTimeTicks")]
+    start_time_ticks: Option<i64>,
     #[allow(dead_code)]
     api_key: Option<String>,
     #[allow(dead_code)]
@@ -68,14 +89,27 @@ struct StreamQuery {
 
 async fn video_stream_with_ext(
     State(config): State<BridgeConfig>,
-    Path((item_id, _ext)): Path<(String, String)>,
-    Query(query): Query<StreamQuery>,
+    Path((item_id, ext)): Path<(String, String)>,
+    Query(mut query): Query<StreamQuery>,
     req: axum::extract::Request,
 ) -> Response {
-    debug!("[STREAM_EXT] 收到带扩展名的流请求: item_id={}, ext={}, uri={}", item_id, _ext, req.uri());
+    debug!("[STREAM_EXT] 收到带扩展名的流请求: item_id={}, ext={}, uri={}", item_id, ext, req.uri());
+    if query.container.is_none() {
+        query.container = Some(ext);
+    }
     video_stream(State(config), Path(item_id), Query(query), req).await
 }
 
+/// ffmpeg 任务票据对应的会话：只用于 static=true 的直链，且 mediaSourceId 须与签发时一致
+fn ticket_session(req: &axum::extract::Request, query: &StreamQuery) -> Option<SessionData> {
+    if query.is_static.as_deref() != Some("true") {
+        return None;
+    }
+    let ticket = extract_token(req).0?;
+    let media_source_id = query.media_source_id.as_deref()?;
+    resolve_ffmpeg_ticket(&ticket, media_source_id).and_then(|token| get_session(&token))
+}
+
 async fn video_stream(
     State(config): State<BridgeConfig>,
     Path(item_id): Path<String>,
@@ -88,8 +122,8 @@ async fn video_stream(
     let full_uri = req.uri().to_string();
     debug!("[STREAM] 完整请求 URI: {}", full_uri);
     
-    let session = match req.extensions().get::<SessionData>() {
-        Some(s) => s.clone(),
+    let session = match req.extensions().get::<SessionData>().cloned().or_else(|| ticket_session(&req, &query)) {
+        Some(s) => s,
         None => {
             debug!("[STREAM] ❌ 未找到 session，返回 401");
             return StatusCode::UNAUTHORIZED.into_response();
@@ -138,20 +172,56 @@ async fn video_stream(
         }
     };
 
+    // 非静态流：走 HLS 转码拼接为渐进式 MPEG-TS（Container=mp4 时重封装为 fragmented MP4）
+    // 跳转位置取 StartTimeTicks，或 DLNA 客户端的 TimeSeekRange.dlna.org 头
+    if query.is_static.as_deref() == Some("false") {
+        let Some(container) = ProgressiveContainer::from_query(query.container.as_deref()) else {
+            debug!("[STREAM] static=false 仅支持 ts / mp4, container={:?}", query.container);
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+        let start_ticks = query.start_time_ticks
+            .or_else(|| {
+                req.headers().get("timeseekrange.dlna.org")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(parse_time_seek_range)
+                    .map(seconds_to_ticks)
+            })
+            .unwrap_or(0);
+        debug!(
+            "[STREAM] static=false → 转码渐进式流, container={:?}, start_ticks={}",
+            query.container, start_ticks
+        );
+        return progressive_stream(&config, &session, &fnos_guid, &media_guid, start_ticks, container, None).await;
+    }
+
+    proxy_original_file(&config, &session, &media_guid, req.headers(), None).await
+}
+
+/// 代理原始文件（本地 NAS 或网盘直链），支持 Range
+/// `content_disposition` 有值时作为附件下载
+async fn proxy_original_file(
+    config: &BridgeConfig,
+    session: &SessionData,
+    media_guid: &str,
+    headers: &HeaderMap,
+    content_disposition: Option<String>,
+) -> Response {
+    let range_header = headers.get("range").and_then(|v| v.to_str().ok()).map(String::from);
+
     // 获取流信息
     debug!("[STREAM] 调用 fnos_get_stream...");
     let stream_result = fnos_get_stream(
         &session.fnos_server,
         &session.fnos_token,
-        &media_guid,
+        media_guid,
         "127.0.0.1",
-        &config,
+        config,
     )
     .await;
     debug!("[STREAM] ✓ fnos_get_stream 完成, success={}", stream_result.success);
 
     let (target_url, extra_headers, skip_verify) = build_upstream_target(
-        &session, &media_guid, &stream_result, &config,
+        session, media_guid, &stream_result, config,
     );
     debug!("[STREAM] target_url={}, skip_verify={}", target_url, skip_verify);
 
@@ -168,7 +238,7 @@ async fn video_stream(
 
     // 透传客户端头
     for h in PASSTHROUGH_HEADERS {
-        if let Some(v) = req.headers().get(*h) {
+        if let Some(v) = headers.get(*h) {
             upstream_req = upstream_req.header(*h, v);
         }
     }
@@ -239,6 +309,9 @@ async fn video_stream(
             if ct == "application/octet-stream" {
                 builder = builder.header("content-type", "video/mp4");
             }
+            if let Some(ref cd) = content_disposition {
+                builder = builder.header("content-disposition", cd.as_str());
+            }
 
             // 流式传输 body
             debug!("[STREAM] 构建响应 body...");
@@ -254,12 +327,67 @@ async fn video_stream(
     }
 }
 
+/// GET /Items/{itemId}/Download — 以附件形式下载原始文件
+async fn item_download(
+    State(config): State<BridgeConfig>,
+    Path(item_id): Path<String>,
+    Query(query): Query<StreamQuery>,
+    req: axum::extract::Request,
+) -> Response {
+    let session = match req.extensions().get::<SessionData>() {
+        Some(s) => s.clone(),
+        None => return StatusCode::UNAUTHORIZED.into_response(),
+    };
+    let Some(fnos_guid) = to_fnos_guid(&item_id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let play_info = match fnos_get_play_info(&session.fnos_server, &session.fnos_token, &fnos_guid, &config).await {
+        r if r.success && r.data.is_some() => r.data.unwrap(),
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    // 与 CanDownload 使用同一校验
+    let jf_type = map_type(&play_info.item.item_type);
+    if !can_download(jf_type) {
+        debug!("[DOWNLOAD] 拒绝下载: item_id={}, type={}", item_id, jf_type);
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let media_guid = query.media_source_id.clone().unwrap_or(play_info.media_guid.clone());
+
+    // 文件名取自飞牛文件路径，缺失时用标题
+    let stream_list = cached_get_stream_list(&session.fnos_server, &session.fnos_token, &fnos_guid, &config).await;
+    let file_name = stream_list.data.as_ref()
+        .and_then(|sd| sd["files"].as_array())
+        .and_then(|files| files.iter().find(|f| f["guid"].as_str() == Some(media_guid.as_str())))
+        .and_then(|f| f["path"].as_str())
+        .and_then(|p| p.rsplit('/').next())
+        .filter(|n| !n.is_empty())
+        .map(String::from)
+        .unwrap_or_else(|| format!("{}.mkv", play_info.item.title));
+
+    // static=false：下载转码副本（MPEG-TS，Container=mp4 时为 fragmented MP4）
+    if query.is_static.as_deref() == Some("false") {
+        let Some(container) = ProgressiveContainer::from_query(query.container.as_deref()) else {
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+        let stem = file_name.rsplit_once('.').map_or(file_name.as_str(), |(s, _)| s);
+        let name = format!("{}.{}", stem, container.extension());
+        info!("[DOWNLOAD] 转码下载: item_id={}, media_guid={}, file={}", item_id, media_guid, name);
+        return progressive_stream(&config, &session, &fnos_guid, &media_guid, 0, container, Some(&name)).await;
+    }
+
+    info!("[DOWNLOAD] 下载: item_id={}, media_guid={}, file={}", item_id, media_guid, file_name);
+    proxy_original_file(&config, &session, &media_guid, req.headers(), Some(content_disposition(&file_name))).await
+}
+
 async fn hls_stream(
     State(config): State<BridgeConfig>,
-    Path((media_guid, file)): Path<(String, String)>,
+    Path((session_key, file)): Path<(String, String)>,
     req: axum::extract::Request,
 ) -> Response {
-    info!("[HLS] hls_stream 收到请求: mediaGuid={}, file={}", media_guid, file);
+    info!("[HLS] hls_stream 收到请求: key={}, file={}", session_key, file);
 
     // 认证：先从请求获取 session
     let (token, _) = extract_token(&req);
@@ -269,7 +397,7 @@ async fn hls_stream(
         (s.fnos_server.clone(), s.fnos_token.clone())
     } else {
         // 从 HLS 会话缓存获取凭据
-        match get_cached_hls_session(&media_guid) {
+        match get_cached_hls_session(&session_key) {
             Some((_, server, token)) => (server, token),
             None => return StatusCode::UNAUTHORIZED.into_response(),
         }
@@ -277,9 +405,9 @@ async fn hls_stream(
 
     // 获取或创建 HLS 转码会话
     let hls_session = if session.is_some() {
-        get_or_create_hls_session(&fnos_server, &fnos_token, &media_guid, &config).await
+        get_or_create_hls_session(&fnos_server, &fnos_token, &session_key, &config).await
     } else {
-        get_cached_hls_session(&media_guid).map(|(sg, _, _)| {
+        get_cached_hls_session(&session_key).map(|(sg, _, _)| {
             let pl = format!("/v/media/{}/preset.m3u8", sg);
             (sg, pl)
         })
@@ -288,7 +416,7 @@ async fn hls_stream(
     let (session_guid, _play_link) = match hls_session {
         Some(s) => s,
         None => {
-            debug!("[HLS] 无转码会话: mediaGuid={}", media_guid);
+            debug!("[HLS] 无转码会话: key={}", session_key);
             return StatusCode::NOT_FOUND.into_response();
         }
     };
@@ -407,8 +535,8 @@ async fn hls_stream(
 
             // 410 Gone → 清除会话
             if status == 410 && session.is_some() {
-                debug!("[HLS] 410 Gone → 清除会话 mediaGuid={}", media_guid);
-                clear_hls_session(&media_guid);
+                debug!("[HLS] 410 Gone → 清除会话 key={}", session_key);
+                expire_hls_session(&session_key);
                 return StatusCode::GONE.into_response();
             }
 
@@ -492,72 +620,125 @@ fn build_upstream_target(
     (target_url, extra, skip_verify)
 }
 
+#[derive(Deserialize, Default)]
+struct SubtitleQuery {
+    #[serde(alias = "CopyTimestamps", alias = "copyTimestamps")]
+    copy_timestamps: Option<bool>,
+    #[serde(alias = "AddVttTimeMap", alias = "addVttTimeMap")]
+    add_vtt_time_map: Option<bool>,
+    #[serde(alias = "EndPositionTicks", alias = "endPositionTicks")]
+    end_position_ticks: Option<i64>,
+}
+
 /// 字幕流处理
 async fn subtitle_stream(
     Path((item_id, media_source_id, index, format)): Path<(String, String, i32, String)>,
-    req: axum::extract::Request,
+    Query(query): Query<SubtitleQuery>,
+    Extension(session): Extension<SessionData>,
+) -> Response {
+    serve_subtitle(&session, &item_id, &media_source_id, index, 0, &format, &query).await
+}
+
+/// 带起始位置的字幕流（从中途开始播放/转码时请求）
+async fn subtitle_stream_with_offset(
+    Path((item_id, media_source_id, index, start_ticks, format)): Path<(String, String, i32, i64, String)>,
+    Query(query): Query<SubtitleQuery>,
+    Extension(session): Extension<SessionData>,
+) -> Response {
+    serve_subtitle(&session, &item_id, &media_source_id, index, start_ticks, &format, &query).await
+}
+
+/// 读取字幕原始内容：桥接层上传的字幕读本地文件，其余从飞牛拉取
+async fn fetch_subtitle_bytes(
+    session: &SessionData,
+    sub_info: &crate::mappers::media::SubtitleInfo,
+) -> Option<Vec<u8>> {
+    use crate::services::local_subtitle::{find_subtitle, read_subtitle, LOCAL_SUBTITLE_PREFIX};
+
+    if sub_info.guid.starts_with(LOCAL_SUBTITLE_PREFIX) {
+        return find_subtitle(&sub_info.guid).and_then(|s| read_subtitle(&s));
+    }
+    if sub_info.guid.is_empty() {
+        return None;
+    }
+
+    // 构建飞牛字幕 URL
+    let subtitle_path = format!("/v/api/v1/media/subtitle?guid={}", sub_info.guid);
+    let target_url = format!("{}{}", session.fnos_server, subtitle_path);
+    let authx = generate_authx_string(&subtitle_path, None);
+
+    let client = Client::builder()
+        .danger_accept_invalid_certs(true)
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .unwrap_or_default();
+
+    let upstream_req = client
+        .get(&target_url)
+        .header("Authorization", &session.fnos_token)
+        .header("Cookie", "mode=relay")
+        .header("Authx", &authx);
+
+    match upstream_req.send().await {
+        Ok(resp) if resp.status().as_u16() == 200 => match resp.bytes().await {
+            Ok(body) => Some(body.to_vec()),
+            Err(e) => {
+                error!("[SUBTITLE] 读取字幕内容失败: {}", e);
+                None
+            }
+        },
+        Ok(_) => None,
+        Err(e) => {
+            error!("[SUBTITLE] 代理字幕请求失败: {}", e);
+            None
+        }
+    }
+}
+
+/// 拉取飞牛字幕并转换为请求的格式
+/// CopyTimestamps=false（默认）时时间轴减去 start_ticks，与从该位置开始的转码流对齐
+async fn serve_subtitle(
+    session: &SessionData,
+    item_id: &str,
+    media_source_id: &str,
+    index: i32,
+    start_ticks: i64,
+    format: &str,
+    query: &SubtitleQuery,
 ) -> Response {
     use crate::mappers::media::get_subtitle_info;
-    
-    let session = match req.extensions().get::<SessionData>() {
-        Some(s) => s.clone(),
-        None => return StatusCode::UNAUTHORIZED.into_response(),
-    };
+    use crate::services::subtitle::{self, is_image_subtitle, ConvertOptions, SubtitleFormat};
 
     debug!(
-        "[SUBTITLE] 字幕请求: itemId={}, mediaSourceId={}, index={}, format={}",
-        item_id, media_source_id, index, format
+        "[SUBTITLE] 字幕请求: itemId={}, mediaSourceId={}, index={}, start={}, format={}",
+        item_id, media_source_id, index, start_ticks, format
     );
 
+    let options = ConvertOptions {
+        offset_ms: if query.copy_timestamps.unwrap_or(false) { 0 } else { start_ticks.max(0) / 10_000 },
+        end_ms: query.end_position_ticks.filter(|t| *t > 0).map(|t| t / 10_000),
+        vtt_time_map: query.add_vtt_time_map.unwrap_or(false),
+    };
+
     // 从缓存获取字幕信息
-    if let Some(sub_info) = get_subtitle_info(&media_source_id, index) {
-        if !sub_info.guid.is_empty() {
-            // 构建飞牛字幕 URL
-            let subtitle_path = format!("/v/api/v1/media/subtitle?guid={}", sub_info.guid);
-            let target_url = format!("{}{}", session.fnos_server, subtitle_path);
-            let authx = generate_authx_string(&subtitle_path, None);
-
-            let client = Client::builder()
-                .danger_accept_invalid_certs(true)
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_default();
-
-            let upstream_req = client
-                .get(&target_url)
-                .header("Authorization", &session.fnos_token)
-                .header("Cookie", "mode=relay")
-                .header("Authx", &authx);
-
-            match upstream_req.send().await {
-                Ok(resp) => {
-                    let status = resp.status().as_u16();
-                    if status == 200 {
-                        let content_type = if format == "vtt" || format == "webvtt" {
-                            "text/vtt"
-                        } else {
-                            "application/octet-stream"
-                        };
-                        
-                        match resp.bytes().await {
-                            Ok(body) => {
-                                return Response::builder()
-                                    .status(200)
-                                    .header("content-type", content_type)
-                                    .header("access-control-allow-origin", "*")
-                                    .body(Body::from(body))
-                                    .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response());
-                            }
-                            Err(e) => {
-                                error!("[SUBTITLE] 读取字幕内容失败: {}", e);
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    error!("[SUBTITLE] 代理字幕请求失败: {}", e);
-                }
-            }
+    if let Some(sub_info) = get_subtitle_info(media_source_id, index) {
+        if let Some(body) = fetch_subtitle_bytes(session, &sub_info).await {
+            // 文本字幕转换为请求的格式；图形字幕（PGS/VobSub）或未知格式原样返回
+            let target = SubtitleFormat::from_name(format)
+                .filter(|_| !is_image_subtitle(&sub_info.codec));
+            let (content_type, body) = match target {
+                Some(target) => (
+                    target.content_type(),
+                    Body::from(subtitle::convert(&body, target, &options)),
+                ),
+                None => ("application/octet-stream", Body::from(body)),
+            };
+            return Response::builder()
+                .status(200)
+                .header("content-type", content_type)
+                .header("access-control-allow-origin", "*")
+                .body(body)
+                .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response());
         }
     }
 
@@ -0,0 +1,66 @@
+/// DisplayPreferences 路由 — 按用户、客户端保存排序与首页布局
+
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::config::BridgeConfig;
+use crate::middleware::auth::{optional_auth, require_auth};
+use crate::services::display_prefs::{get_display_prefs, set_display_prefs};
+use crate::services::session::SessionData;
+use crate::types::jellyfin::DisplayPreferencesDto;
+
+pub fn router() -> Router<BridgeConfig> {
+    Router::new()
+        // 未登录时读取返回默认值，保存需要登录
+        .route(
+            "/DisplayPreferences/{id}",
+            get(display_prefs_get).layer(axum::middleware::from_fn(optional_auth)),
+        )
+        .route(
+            "/DisplayPreferences/{id}",
+            post(display_prefs_set).layer(axum::middleware::from_fn(require_auth)),
+        )
+}
+
+#[derive(Deserialize, Default)]
+struct DisplayPrefsQuery {
+    #[serde(alias = "Client", alias = "client")]
+    client: Option<String>,
+}
+
+fn client_name(query: &DisplayPrefsQuery) -> String {
+    query
+        .client
+        .as_deref()
+        .filter(|c| !c.is_empty())
+        .unwrap_or("emby")
+        .to_string()
+}
+
+async fn display_prefs_get(
+    Path(id): Path<String>,
+    Query(query): Query<DisplayPrefsQuery>,
+    session: Option<Extension<SessionData>>,
+) -> Json<DisplayPreferencesDto> {
+    // 未登录时返回默认值
+    let user_id = session.map(|Extension(s)| s.user_id).unwrap_or_default();
+    Json(get_display_prefs(&user_id, &client_name(&query), &id))
+}
+
+async fn display_prefs_set(
+    Path(id): Path<String>,
+    Query(query): Query<DisplayPrefsQuery>,
+    Extension(session): Extension<SessionData>,
+    Json(prefs): Json<DisplayPreferencesDto>,
+) -> StatusCode {
+    let client = client_name(&query);
+    debug!("[PREFS] 保存显示偏好: user={}, client={}, id={}", session.user_id, client, id);
+    set_display_prefs(&session.user_id, &client, &id, prefs);
+    StatusCode::NO_CONTENT
+}
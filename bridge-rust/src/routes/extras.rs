@@ -53,6 +53,7 @@ fn default_user_data(is_fav: bool, played: bool) -> UserItemDataDto {
         played,
         played_percentage: None,
         unplayed_item_count: None,
+        last_played_date: None,
     }
 }
 
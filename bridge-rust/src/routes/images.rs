@@ -1,23 +1,33 @@
 /// 图片代理路由
 /// 优先从 imageCache 获取图片路径，fallback 到 fnosGetPlayInfo
+/// 凭据选择见 services::image_auth
 
 use axum::{
     body::Body,
     extract::{Path, Query, State},
-    http::{header, StatusCode},
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     routing::get,
     Extension, Router,
 };
 use std::collections::HashMap;
+use tracing::debug;
 
+use crate::cache::blurhash::ensure_blur_hash;
+use crate::cache::image_file::{get_cached_image, image_cache_key, lock_image_key, put_cached_image, sniff_image};
 use crate::config::BridgeConfig;
 use crate::fnos_client::signature::generate_authx_string;
+use crate::imaging::{self, ImageOptions};
 use crate::mappers::id::to_fnos_guid;
+use crate::mappers::item::image_tag;
 use crate::middleware::auth::optional_auth;
+use crate::services::chapters::chapter_image_path;
 use crate::services::fnos::fnos_get_play_info;
+use crate::services::image_auth::{
+    invalidate_service_credential, service_credential, tag_authorizes, ImageCredential,
+};
 use crate::cache::image::{get_image_cache, set_image_cache, CachedImage};
-use crate::services::session::SessionData;
+use crate::services::session::{get_known_user, SessionData};
 
 pub fn router() -> Router<BridgeConfig> {
     Router::new()
@@ -29,59 +39,99 @@ pub fn router() -> Router<BridgeConfig> {
             "/Items/{itemId}/Images/{imageType}/{index}",
             get(proxy_image_indexed).layer(axum::middleware::from_fn(optional_auth)),
         )
+        .route(
+            "/Users/{userId}/Images/{imageType}",
+            get(proxy_user_image).layer(axum::middleware::from_fn(optional_auth)),
+        )
 }
 
 async fn proxy_image(
     State(config): State<BridgeConfig>,
     Path((item_id, image_type)): Path<(String, String)>,
     Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
     session: Option<Extension<SessionData>>,
 ) -> Response {
-    do_proxy_image(&config, &item_id, &image_type, &query, session.map(|e| e.0)).await
+    do_proxy_image(&config, &item_id, &image_type, 0, &query, &headers, session.map(|e| e.0)).await
 }
 
 async fn proxy_image_indexed(
     State(config): State<BridgeConfig>,
-    Path((item_id, image_type, _index)): Path<(String, String, String)>,
+    Path((item_id, image_type, index)): Path<(String, String, String)>,
     Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
     session: Option<Extension<SessionData>>,
 ) -> Response {
-    do_proxy_image(&config, &item_id, &image_type, &query, session.map(|e| e.0)).await
+    if image_type.eq_ignore_ascii_case("Chapter") {
+        return chapter_image(&item_id, &index).await;
+    }
+    let Ok(index) = index.parse::<usize>() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    do_proxy_image(&config, &item_id, &image_type, index, &query, &headers, session.map(|e| e.0)).await
+}
+
+/// 章节图片（由后台任务截取到本地）
+async fn chapter_image(item_id: &str, index: &str) -> Response {
+    let path = to_fnos_guid(item_id)
+        .zip(index.parse::<usize>().ok())
+        .and_then(|(guid, i)| chapter_image_path(&guid, i));
+    let Some(path) = path else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => Response::builder()
+            .status(200)
+            .header(header::CONTENT_TYPE, "image/jpeg")
+            .header(header::CACHE_CONTROL, "public, max-age=31536000")
+            .body(Body::from(bytes))
+            .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
 }
 
 async fn do_proxy_image(
     config: &BridgeConfig,
     item_id: &str,
     image_type: &str,
+    index: usize,
     query: &HashMap<String, String>,
+    headers: &HeaderMap,
     session: Option<SessionData>,
 ) -> Response {
+    let session_credential = session.map(|s| ImageCredential {
+        server: s.fnos_server,
+        token: s.fnos_token,
+        service: false,
+    });
+
+    // 匿名请求只接受签名标签（校验见第 3 步）
+    let tag = query.iter().find(|(k, _)| k.eq_ignore_ascii_case("tag")).map(|(_, v)| v.as_str());
+    if session_credential.is_none() && tag.is_none() {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
     // 1. 先查缓存
     let mut cached = get_image_cache(item_id);
 
-    // 2. 缓存未命中，尝试用 session 调 API
-    if cached.is_none() {
-        if let Some(ref session) = session {
-            if let Some(fnos_guid) = to_fnos_guid(item_id) {
-                let result = fnos_get_play_info(
-                    &session.fnos_server,
-                    &session.fnos_token,
-                    &fnos_guid,
-                    config,
-                )
-                .await;
-
-                if result.success {
-                    if let Some(ref data) = result.data {
-                        let img = CachedImage {
-                            poster: if data.item.posters.is_empty() { None } else { Some(data.item.posters.clone()) },
-                            backdrop: if data.item.still_path.is_empty() { None } else { Some(data.item.still_path.clone()) },
-                            server: session.fnos_server.clone(),
-                            token: session.fnos_token.clone(),
-                        };
-                        set_image_cache(item_id, img.clone());
-                        cached = Some(img);
-                    }
+    // 2. 缓存未命中，或列表缓存里没有该类型图片，调详情 API 补全（匿名请求使用服务账号）
+    let missing = cached
+        .as_ref()
+        .is_none_or(|c| !c.detailed && c.path(image_type, index).is_none());
+    if missing {
+        let credential = match session_credential {
+            Some(ref c) => Some(c.clone()),
+            None => service_credential(config).await,
+        };
+        if let Some((credential, fnos_guid)) = credential.zip(to_fnos_guid(item_id)) {
+            let result = fnos_get_play_info(&credential.server, &credential.token, &fnos_guid, config).await;
+
+            if result.success {
+                if let Some(ref data) = result.data {
+                    let mut img = CachedImage::from_fnos(&data.item.posters, &data.item.still_path);
+                    img.detailed = true;
+                    set_image_cache(item_id, img);
+                    cached = get_image_cache(item_id);
                 }
             }
         }
@@ -92,41 +142,134 @@ async fn do_proxy_image(
         None => return StatusCode::NOT_FOUND.into_response(),
     };
 
-    // 选择图片路径
-    let image_path = match image_type.to_lowercase().as_str() {
-        "primary" | "poster" => cached.poster.as_deref(),
-        "backdrop" | "thumb" | "banner" => {
-            cached.backdrop.as_deref().or(cached.poster.as_deref())
-        }
-        _ => cached.poster.as_deref(),
+    let Some(image_path) = cached.path(image_type, index) else {
+        return StatusCode::NOT_FOUND.into_response();
     };
 
-    let image_path = match image_path {
-        Some(p) if !p.is_empty() => p,
+    // 3. 匿名请求必须带有指向这张图片且未过期的签名标签，之后由服务账号拉取（或读取磁盘缓存）
+    if session_credential.is_none() && !tag.is_some_and(|tag| tag_authorizes(tag, &image_tag(image_path))) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    fetch_fnos_image(config, session_credential, image_path, query, headers).await
+}
+
+/// 用户头像代理
+/// 仅对已登录的请求或开启 PUBLIC_USER_LIST（登录页显示用户列表）时提供；
+/// 使用请求者自己的凭据，匿名请求使用服务账号，不借用该用户保存的凭据
+async fn proxy_user_image(
+    State(config): State<BridgeConfig>,
+    Path((user_id, _image_type)): Path<(String, String)>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    session: Option<Extension<SessionData>>,
+) -> Response {
+    if session.is_none() && !config.public_user_list {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let user = match get_known_user(&user_id) {
+        Some(u) if !u.avatar.is_empty() => u,
         _ => return StatusCode::NOT_FOUND.into_response(),
     };
+    let credential = session.map(|Extension(s)| ImageCredential {
+        server: s.fnos_server,
+        token: s.fnos_token,
+        service: false,
+    });
+    fetch_fnos_image(&config, credential, &user.avatar, &query, &headers).await
+}
+
+/// If-None-Match 是否命中
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| {
+            v.split(',')
+                .map(|t| t.trim().trim_start_matches("W/"))
+                .any(|t| t == etag)
+        })
+}
+
+fn image_response(data: Vec<u8>, content_type: &str, etag: &str) -> Response {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CACHE_CONTROL, "public, max-age=86400")
+        .header(header::ETAG, etag)
+        .body(Body::from(data))
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+/// 请求飞牛图片，按参数缩放/转码后返回给客户端
+/// 处理结果按 (图片路径, 参数) 缓存在磁盘，ETag 由图片哈希与参数派生
+/// 调用方须已完成授权检查；没有会话凭据时改用服务账号，磁盘缓存命中时不需要凭据
+async fn fetch_fnos_image(
+    config: &BridgeConfig,
+    credential: Option<ImageCredential>,
+    image_path: &str,
+    query: &HashMap<String, String>,
+    headers: &HeaderMap,
+) -> Response {
+    let options = ImageOptions::from_query(query);
+    let cache_key = image_cache_key(image_path, &options.cache_key());
+    let tag = image_tag(image_path);
+    let etag = format!("\"{}-{}\"", tag, &cache_key[..12]);
+
+    if etag_matches(headers, &etag) {
+        return Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, &etag)
+            .header(header::CACHE_CONTROL, "public, max-age=86400")
+            .body(Body::empty())
+            .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response());
+    }
+    if let Some((data, content_type)) = get_cached_image(&cache_key).await {
+        ensure_blur_hash(&tag, &data);
+        return image_response(data, content_type, &etag);
+    }
+
+    // 并发去重：同一缓存键只处理一次，等待者拿到锁后直接读缓存（未启用磁盘缓存时不合并）
+    let _in_flight = if config.image_cache_mb > 0 {
+        let guard = lock_image_key(&cache_key).await;
+        if let Some((data, content_type)) = get_cached_image(&cache_key).await {
+            return image_response(data, content_type, &etag);
+        }
+        Some(guard)
+    } else {
+        None
+    };
+
+    let credential = match credential {
+        Some(c) => c,
+        None => match service_credential(config).await {
+            Some(c) => c,
+            None => return StatusCode::NOT_FOUND.into_response(),
+        },
+    };
+    let server = credential.server.as_str();
 
     // 构造完整图片 URL
     let image_url = if image_path.starts_with("http") {
         image_path.to_string()
     } else if image_path.starts_with("/v/api/") {
-        format!("{}{}", cached.server, image_path)
+        format!("{}{}", server, image_path)
     } else {
         let clean = if image_path.starts_with('/') { image_path.to_string() } else { format!("/{}", image_path) };
-        format!("{}/v/api/v1/sys/img{}", cached.server, clean)
+        format!("{}/v/api/v1/sys/img{}", server, clean)
     };
 
-    // 添加尺寸参数
-    let fill_width = query.get("fillWidth").or(query.get("maxWidth"));
-    let final_url = if let Some(w) = fill_width {
-        if !image_url.contains("w=") {
+    // 让飞牛先按宽度缩小，减少传输量（fill 同时限定高度时可能需要更宽的原图，不传）
+    let upstream_width = options
+        .width
+        .or(options.fill_width.filter(|_| options.fill_height.is_none()))
+        .or(options.max_width);
+    let final_url = match upstream_width {
+        Some(w) if !image_url.contains("w=") => {
             let sep = if image_url.contains('?') { "&" } else { "?" };
             format!("{}{}w={}", image_url, sep, w)
-        } else {
-            image_url
         }
-    } else {
-        image_url
+        _ => image_url,
     };
 
     // 从 URL 提取 API 路径用于签名
@@ -144,13 +287,13 @@ async fn do_proxy_image(
 
     let upstream = client
         .get(&final_url)
-        .header("Authorization", &cached.token)
+        .header("Authorization", &credential.token)
         .header("Cookie", "mode=relay")
         .header("Authx", &authx)
         .send()
         .await;
 
-    match upstream {
+    let (bytes, upstream_type) = match upstream {
         Ok(resp) if resp.status().is_success() => {
             let content_type = resp
                 .headers()
@@ -158,16 +301,43 @@ async fn do_proxy_image(
                 .and_then(|v| v.to_str().ok())
                 .unwrap_or("image/jpeg")
                 .to_string();
+            match resp.bytes().await {
+                Ok(bytes) => (bytes.to_vec(), content_type),
+                Err(_) => return StatusCode::BAD_GATEWAY.into_response(),
+            }
+        }
+        Ok(resp) => {
+            // 服务账号 token 可能已过期，下次重新登录
+            if credential.service && matches!(resp.status().as_u16(), 401 | 403) {
+                invalidate_service_credential(&credential.token).await;
+            }
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        Err(_) => return StatusCode::NOT_FOUND.into_response(),
+    };
+    ensure_blur_hash(&tag, &bytes);
 
-            let bytes = resp.bytes().await.unwrap_or_default();
-
-            Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, content_type)
-                .header(header::CACHE_CONTROL, "public, max-age=86400")
-                .body(Body::from(bytes))
-                .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+    // 解码/编码较耗 CPU，放到阻塞线程；无法解码的格式原样返回
+    let processed = if options.is_noop() {
+        None
+    } else {
+        let source = bytes.clone();
+        let options = options.clone();
+        tokio::task::spawn_blocking(move || imaging::process(&source, &options))
+            .await
+            .ok()
+            .flatten()
+    };
+    let (data, content_type) = match processed {
+        Some((data, format)) => (data, format.content_type().to_string()),
+        None => {
+            let (ext, sniffed) = sniff_image(&bytes);
+            let content_type = if ext == "bin" { upstream_type } else { sniffed.to_string() };
+            (bytes, content_type)
         }
-        _ => StatusCode::NOT_FOUND.into_response(),
-    }
+    };
+    debug!("[IMAGE] 图片已处理: path={}, {} bytes", image_path, data.len());
+
+    put_cached_image(&cache_key, &data, config.image_cache_mb * 1024 * 1024).await;
+    image_response(data, &content_type, &etag)
 }
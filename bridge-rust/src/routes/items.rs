@@ -3,7 +3,7 @@
 use axum::{
     extract::{Path, Query, State},
     routing::get,
-    Json, Router,
+    Extension, Json, Router,
 };
 use serde::Deserialize;
 use serde_json::json;
@@ -13,11 +13,21 @@ use crate::config::BridgeConfig;
 use crate::mappers::id::*;
 use crate::mappers::item::*;
 use crate::mappers::media::build_media_sources;
+use crate::mappers::user::user_config;
 use crate::middleware::auth::require_auth;
+use crate::cache::first_seen::{date_added, record_first_seen};
 use crate::cache::item_list::cached_get_item_list;
+use crate::cache::item_meta::prefetch_item_meta;
+use crate::cache::play_history::last_played;
 use crate::cache::stream_list::cached_get_stream_list;
+use crate::routes::playlists::playlist_items_page;
 use crate::services::fnos::*;
+use crate::services::playlist::{delete_playlist, find_playlist, list_playlists};
+use crate::services::search::rank_items;
 use crate::services::session::SessionData;
+use crate::services::chapters::{get_chapters, map_chapters, schedule_chapter_images, schedule_chapter_probe};
+use crate::services::trickplay::{get_trickplay, schedule_trickplay};
+use crate::types::fnos::FnosPlayListItem;
 use crate::types::jellyfin::{BaseItemDto, ItemsResult};
 
 pub fn router() -> Router<BridgeConfig> {
@@ -30,13 +40,19 @@ pub fn router() -> Router<BridgeConfig> {
             "/Items/Filters",
             get(items_filters).layer(axum::middleware::from_fn(require_auth)),
         )
+        .route(
+            "/Items/Filters2",
+            get(items_filters2).layer(axum::middleware::from_fn(require_auth)),
+        )
         .route(
             "/Items/Latest",
             get(items_latest).layer(axum::middleware::from_fn(require_auth)),
         )
         .route(
             "/Items/{itemId}",
-            get(items_detail).layer(axum::middleware::from_fn(require_auth)),
+            get(items_detail)
+                .delete(items_delete)
+                .layer(axum::middleware::from_fn(require_auth)),
         )
         // 旧版路径兼容
         .route(
@@ -67,6 +83,8 @@ struct ItemsQuery {
     parent_id: Option<String>,
     #[serde(alias = "IncludeItemTypes", alias = "includeItemTypes")]
     include_item_types: Option<String>,
+    #[serde(alias = "ExcludeItemTypes", alias = "excludeItemTypes")]
+    exclude_item_types: Option<String>,
     #[serde(alias = "SearchTerm", alias = "searchTerm")]
     search_term: Option<String>,
     #[serde(alias = "SortBy", alias = "sortBy")]
@@ -83,6 +101,111 @@ struct ItemsQuery {
     recursive: Option<String>,
     #[serde(alias = "MediaTypes", alias = "mediaTypes")]
     media_types: Option<String>,
+    #[serde(alias = "NameStartsWith", alias = "nameStartsWith")]
+    name_starts_with: Option<String>,
+    #[serde(alias = "NameStartsWithOrGreater", alias = "nameStartsWithOrGreater")]
+    name_starts_with_or_greater: Option<String>,
+    #[serde(alias = "NameLessThan", alias = "nameLessThan")]
+    name_less_than: Option<String>,
+    #[serde(alias = "IsHD", alias = "isHd", alias = "isHD")]
+    is_hd: Option<String>,
+    #[serde(alias = "Is4K", alias = "is4K", alias = "is4k")]
+    is_4k: Option<String>,
+    #[serde(alias = "Years", alias = "years")]
+    years: Option<String>,
+    #[serde(alias = "MinCommunityRating", alias = "minCommunityRating")]
+    min_community_rating: Option<f64>,
+    #[serde(alias = "EnableUserData", alias = "enableUserData")]
+    enable_user_data: Option<String>,
+    #[serde(alias = "GroupItems", alias = "groupItems")]
+    group_items: Option<String>,
+    #[serde(alias = "IsPlayed", alias = "isPlayed")]
+    is_played: Option<String>,
+}
+
+/// 布尔查询参数（"true" / "false"，大小写不敏感）
+fn query_flag(value: &Option<String>) -> Option<bool> {
+    match value.as_deref()?.to_lowercase().as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// IsHD / Is4K / Years / MinCommunityRating 过滤
+/// 飞牛列表不返回字幕、分级等信息，HasSubtitles / OfficialRatings 不过滤
+fn apply_metadata_filters(items: &mut Vec<&FnosPlayListItem>, query: &ItemsQuery) {
+    if let Some(want) = query_flag(&query.is_hd) {
+        items.retain(|item| is_hd_item(item) == want);
+    }
+    if let Some(want) = query_flag(&query.is_4k) {
+        items.retain(|item| is_4k_item(item) == want);
+    }
+    if let Some(ref years) = query.years {
+        let years: Vec<i32> = years.split(',').filter_map(|y| y.trim().parse().ok()).collect();
+        if !years.is_empty() {
+            items.retain(|item| item.year().is_some_and(|y| years.contains(&y)));
+        }
+    }
+    if let Some(min) = query.min_community_rating {
+        items.retain(|item| item.vote_average.parse::<f64>().is_ok_and(|v| v >= min));
+    }
+}
+
+/// 按 StartIndex/Limit 分页
+fn paginate(all_dtos: Vec<BaseItemDto>, query: &ItemsQuery) -> ItemsResult {
+    let total = all_dtos.len() as i64;
+    let start = query.start_index.unwrap_or(0).max(0) as usize;
+    let limit = query.limit.unwrap_or(50).max(0) as usize;
+    let items: Vec<BaseItemDto> = all_dtos.into_iter().skip(start).take(limit).collect();
+    ItemsResult { items, total_record_count: total, start_index: start as i64 }
+}
+
+/// 按 DateCreated 排序（飞牛无添加时间排序列，以首播日期顺序请求后在此按添加时间稳定重排）
+fn apply_date_created_sort(mut dtos: Vec<BaseItemDto>, query: &ItemsQuery) -> Vec<BaseItemDto> {
+    let by_date = query.sort_by.as_deref().and_then(|s| s.split(',').next()).is_some_and(|f| f.trim() == "DateCreated");
+    if by_date && query.search_term.is_none() {
+        if query.sort_order.as_deref() == Some("Descending") {
+            dtos.sort_by(|a, b| b.date_created.cmp(&a.date_created));
+        } else {
+            dtos.sort_by(|a, b| a.date_created.cmp(&b.date_created));
+        }
+    }
+    dtos
+}
+
+/// 按 SortName 排序并应用 NameStartsWith / NameStartsWithOrGreater / NameLessThan（字母跳转栏）
+/// 飞牛按 sort_title 返回的顺序不区分拼音，按名称排序（或未指定排序）且非搜索时在此重排
+fn apply_name_query(mut dtos: Vec<BaseItemDto>, query: &ItemsQuery) -> Vec<BaseItemDto> {
+    let key = |dto: &BaseItemDto| dto.sort_name.clone().unwrap_or_else(|| dto.name.to_lowercase());
+
+    if let Some(prefix) = query.name_starts_with.as_deref().map(str::to_lowercase).filter(|p| !p.is_empty()) {
+        dtos.retain(|dto| key(dto).starts_with(&prefix));
+    }
+    if let Some(min) = query.name_starts_with_or_greater.as_deref().map(str::to_lowercase) {
+        dtos.retain(|dto| key(dto) >= min);
+    }
+    if let Some(max) = query.name_less_than.as_deref().map(str::to_lowercase) {
+        dtos.retain(|dto| key(dto) < max);
+    }
+
+    let by_name = query.sort_by.as_deref().is_none_or(|s| s.split(',').next().is_some_and(|f| f.contains("Name")));
+    if by_name && query.search_term.is_none() {
+        dtos.sort_by_cached_key(key);
+        if query.sort_order.as_deref() == Some("Descending") {
+            dtos.reverse();
+        }
+    }
+    dtos
+}
+
+/// 当前页中尚无视频元数据的项目在后台获取 stream/list
+fn prefetch_video_meta(dtos: &[BaseItemDto], session: &SessionData, config: &BridgeConfig) {
+    let guids: Vec<String> = dtos.iter()
+        .filter(|dto| dto.media_type.as_deref() == Some("Video") && dto.video_type.is_none())
+        .filter_map(|dto| to_fnos_guid(&dto.id))
+        .collect();
+    prefetch_item_meta(guids, &session.fnos_server, &session.fnos_token, config);
 }
 
 async fn items_list(
@@ -117,6 +240,22 @@ async fn items_list(
 
     let parent_id = query.parent_id.as_deref().unwrap_or("");
 
+    // ===== 播放列表 =====
+    // 播放列表媒体库，或 IncludeItemTypes=Playlist（"添加到播放列表"对话框）
+    let is_playlists_view = !parent_id.is_empty() && to_fnos_guid(parent_id).as_deref() == Some("view_playlists");
+    if is_playlists_view || include_item_types == "Playlist" {
+        let all_dtos: Vec<BaseItemDto> = list_playlists(&session.user_id)
+            .iter()
+            .map(|p| map_playlist_to_dto(p, &server_id))
+            .collect();
+        return Json(paginate(all_dtos, &query)).into_response();
+    }
+    if let Some(playlist) = find_playlist(&session.user_id, parent_id) {
+        let start = query.start_index.unwrap_or(0).max(0) as usize;
+        let limit = query.limit.unwrap_or(50).max(0) as usize;
+        return Json(playlist_items_page(&session, &playlist, start, limit, &config).await).into_response();
+    }
+
     // ===== 分支 1: 有 ParentId =====
     if !parent_id.is_empty() {
         let fnos_parent = to_fnos_guid(parent_id).unwrap_or_default();
@@ -165,12 +304,9 @@ async fn items_list(
             filtered.retain(|item| types.contains(&map_type(&item.item_type)));
         }
 
-        // 搜索过滤
+        // 搜索：按匹配程度过滤并排序
         if let Some(ref term) = query.search_term {
-            let lower = term.to_lowercase();
-            filtered.retain(|item| {
-                item.title.to_lowercase().contains(&lower) || item.tv_title.to_lowercase().contains(&lower)
-            });
+            filtered = rank_items(term, filtered);
         }
 
         // IsFavorite
@@ -190,10 +326,13 @@ async fn items_list(
         if filters.contains("IsUnplayed") {
             filtered.retain(|item| item.watched != 1);
         }
+        apply_metadata_filters(&mut filtered, &query);
 
         let all_dtos: Vec<BaseItemDto> = filtered.iter()
-            .map(|item| map_playlist_item_to_dto(item, &server_id, &session.fnos_server, &session.fnos_token))
+            .map(|item| map_playlist_item_to_dto(item, &server_id))
             .collect();
+        let all_dtos = apply_name_query(all_dtos, &query);
+        let all_dtos = apply_date_created_sort(all_dtos, &query);
 
         debug!("[ITEMS] 过滤后 {} 条", all_dtos.len());
 
@@ -206,6 +345,7 @@ async fn items_list(
             vec![]
         };
 
+        prefetch_video_meta(&paged, &session, &config);
         return Json(ItemsResult { items: paged, total_record_count: total, start_index: start as i64 }).into_response();
     }
 
@@ -231,12 +371,9 @@ async fn items_list(
         let list_data = result.data.unwrap();
         let mut filtered: Vec<_> = list_data.list.iter().collect();
 
-        // 搜索过滤
+        // 搜索：按匹配程度过滤并排序
         if let Some(ref term) = query.search_term {
-            let lower = term.to_lowercase();
-            filtered.retain(|item| {
-                item.title.to_lowercase().contains(&lower) || item.tv_title.to_lowercase().contains(&lower)
-            });
+            filtered = rank_items(term, filtered);
         }
 
         // IncludeItemTypes 过滤
@@ -262,10 +399,13 @@ async fn items_list(
         if filters.contains("IsUnplayed") {
             filtered.retain(|item| item.watched != 1);
         }
+        apply_metadata_filters(&mut filtered, &query);
 
         let all_dtos: Vec<BaseItemDto> = filtered.iter()
-            .map(|item| map_playlist_item_to_dto(item, &server_id, &session.fnos_server, &session.fnos_token))
+            .map(|item| map_playlist_item_to_dto(item, &server_id))
             .collect();
+        let all_dtos = apply_name_query(all_dtos, &query);
+        let all_dtos = apply_date_created_sort(all_dtos, &query);
 
         debug!("[ITEMS] 过滤后 {} 条", all_dtos.len());
 
@@ -278,6 +418,7 @@ async fn items_list(
             vec![]
         };
 
+        prefetch_video_meta(&paged, &session, &config);
         return Json(ItemsResult { items: paged, total_record_count: total, start_index: start as i64 }).into_response();
     }
 
@@ -318,44 +459,139 @@ async fn items_latest(
     }
 
     let list_data = result.data.unwrap();
-    let limit = query.limit.unwrap_or(16) as usize;
+    let limit = query.limit.unwrap_or(16).max(0) as usize;
 
     // 按 ParentId 过滤（虚拟媒体库）
     let parent_id = query.parent_id.as_deref().unwrap_or("");
-    let view_filter = if !parent_id.is_empty() {
+    let view_filter: &[&str] = if !parent_id.is_empty() {
         match to_fnos_guid(parent_id).as_deref() {
-            Some("view_movies") => "Movie",
-            Some("view_tvshows") => "Series",
-            _ => "",
+            Some("view_movies") => &["Movie"],
+            Some("view_tvshows") => &["Series", "Season", "Episode"],
+            _ => &[],
         }
     } else {
-        ""
+        &[]
     };
 
-    let include_item_types = query.include_item_types.as_deref().unwrap_or("");
+    let include_item_types = split_types(query.include_item_types.as_deref());
 
     let mut filtered: Vec<_> = list_data.list.iter().collect();
 
     // 虚拟媒体库类型过滤
     if !view_filter.is_empty() {
-        filtered.retain(|item| map_type(&item.item_type) == view_filter);
+        filtered.retain(|item| view_filter.contains(&map_type(&item.item_type)));
     }
 
     // IncludeItemTypes 过滤
     if !include_item_types.is_empty() {
-        let types: Vec<&str> = include_item_types.split(',').map(|t| t.trim()).collect();
-        filtered.retain(|item| types.contains(&map_type(&item.item_type)));
+        filtered.retain(|item| include_item_types.contains(&map_type(&item.item_type)));
+    }
+
+    // 按添加时间倒序（而非首播日期，添加时间相同时保持首播日期顺序）
+    filtered.sort_by_key(|item| std::cmp::Reverse(date_added(&item.guid).unwrap_or(0)));
+
+    // IsPlayed 过滤（分组前按单集过滤，分组的未看数随之变化）
+    if let Some(played) = query_flag(&query.is_played) {
+        filtered.retain(|item| (item.watched == 1) == played);
+    }
+
+    let items: Vec<BaseItemDto> = if query_flag(&query.group_items) != Some(false) {
+        group_latest(filtered)
+            .iter()
+            .take(limit)
+            .map(|group| latest_group_dto(group, &server_id))
+            .collect()
+    } else {
+        filtered
+            .iter()
+            .take(limit)
+            .map(|item| map_playlist_item_to_dto(item, &server_id))
+            .collect()
+    };
+
+    debug!("[LATEST] 飞牛返回 {} 条, view_filter={:?}, 过滤后 {} 条", list_data.list.len(), view_filter, items.len());
+
+    Json(items).into_response()
+}
+
+/// Latest 分组键：单集归入所属剧集（无剧集信息时归入所属季），其余项目各自成组
+fn latest_group_key(item: &FnosPlayListItem) -> &str {
+    if map_type(&item.item_type) == "Episode" {
+        if !item.ancestor_guid.is_empty() {
+            return &item.ancestor_guid;
+        }
+        if !item.parent_guid.is_empty() {
+            return &item.parent_guid;
+        }
     }
+    &item.guid
+}
 
-    let items: Vec<BaseItemDto> = filtered
+/// 将最近添加项目按剧集分组，组的顺序取组内最新添加项目的位置
+fn group_latest(items: Vec<&FnosPlayListItem>) -> Vec<Vec<&FnosPlayListItem>> {
+    let mut groups: Vec<Vec<&FnosPlayListItem>> = Vec::new();
+    let mut index: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    for item in items {
+        let key = latest_group_key(item);
+        match index.get(key) {
+            Some(&i) => groups[i].push(item),
+            None => {
+                index.insert(key, groups.len());
+                groups.push(vec![item]);
+            }
+        }
+    }
+    groups
+}
+
+/// 分组 → DTO：只有一个单集时直接返回该单集，多集时返回所属剧集（或季），
+/// ChildCount 为新增集数，UserData.UnplayedItemCount 为其中未看的集数
+fn latest_group_dto(group: &[&FnosPlayListItem], server_id: &str) -> BaseItemDto {
+    let episodes: Vec<&FnosPlayListItem> = group
         .iter()
-        .take(limit)
-        .map(|item| map_playlist_item_to_dto(item, &server_id, &session.fnos_server, &session.fnos_token))
+        .copied()
+        .filter(|item| map_type(&item.item_type) == "Episode")
         .collect();
+    let container = group.iter().find(|item| map_type(&item.item_type) != "Episode");
+    if episodes.len() <= 1 {
+        return map_playlist_item_to_dto(container.unwrap_or(&group[0]), server_id);
+    }
 
-    debug!("[LATEST] 飞牛返回 {} 条, view_filter={}, 过滤后 {} 条", list_data.list.len(), view_filter, items.len());
+    let container = match container {
+        Some(item) => (*item).clone(),
+        None => latest_container(episodes[0]),
+    };
+    let mut dto = map_playlist_item_to_dto(&container, server_id);
+    let unplayed = episodes.iter().filter(|item| item.watched != 1).count() as i32;
+    dto.child_count = Some(episodes.len() as i32);
+    if let Some(user_data) = dto.user_data.as_mut() {
+        user_data.unplayed_item_count = Some(unplayed);
+        user_data.played = unplayed == 0;
+    }
+    dto
+}
 
-    Json(items).into_response()
+/// 由单集构造所属剧集（无剧集信息时为所属季）条目，标题取自单集携带的父级字段，图片取自已缓存的父级图片
+fn latest_container(episode: &FnosPlayListItem) -> FnosPlayListItem {
+    let mut container = FnosPlayListItem {
+        tv_title: episode.tv_title.clone(),
+        ..Default::default()
+    };
+    if !episode.ancestor_guid.is_empty() {
+        container.guid = episode.ancestor_guid.clone();
+        container.item_type = "TV".into();
+        container.title = if !episode.tv_title.is_empty() {
+            episode.tv_title.clone()
+        } else {
+            episode.ancestor_name.clone()
+        };
+    } else {
+        container.guid = episode.parent_guid.clone();
+        container.item_type = "Season".into();
+        container.title = episode.parent_title.clone();
+        container.season_number = episode.season_number;
+    }
+    container
 }
 
 async fn items_detail(
@@ -374,6 +610,12 @@ async fn items_detail(
     debug!("[ITEMS] 请求 URI: {}", req.uri());
 
     let server_id = generate_server_id(&config.fnos_server);
+
+    // 桥接层播放列表
+    if let Some(playlist) = find_playlist(&session.user_id, &item_id) {
+        return Json(map_playlist_to_dto(&playlist, &server_id)).into_response();
+    }
+
     let fnos_guid = to_fnos_guid(&item_id);
 
     // 处理虚拟媒体库 ID
@@ -382,6 +624,7 @@ async fn items_detail(
             let (name, ct) = match guid.as_str() {
                 "view_movies" => ("电影", "movies"),
                 "view_tvshows" => ("电视剧", "tvshows"),
+                "view_playlists" => ("播放列表", "playlists"),
                 _ => ("未知", "unknown"),
             };
             return Json(make_collection_folder(name, &item_id, &server_id, ct)).into_response();
@@ -416,6 +659,23 @@ async fn items_detail(
     build_item_response(&session, &item_id, &fnos_guid, result.data.unwrap(), &server_id, &config).await.into_response()
 }
 
+/// DELETE /Items/{itemId} — 仅支持删除桥接层播放列表，飞牛媒体文件不允许删除
+async fn items_delete(
+    Path(item_id): Path<String>,
+    axum::Extension(session): axum::Extension<SessionData>,
+) -> axum::http::StatusCode {
+    use axum::http::StatusCode;
+
+    match find_playlist(&session.user_id, &item_id) {
+        Some(playlist) => {
+            debug!("[PLAYLIST] 删除播放列表: {}", playlist.name);
+            delete_playlist(&session.user_id, &playlist.id);
+            StatusCode::NO_CONTENT
+        }
+        None => StatusCode::FORBIDDEN,
+    }
+}
+
 async fn items_detail_compat(
     State(config): State<BridgeConfig>,
     Path((_user_id, item_id)): Path<(String, String)>,
@@ -424,6 +684,29 @@ async fn items_detail_compat(
     items_detail(State(config), Path(item_id), req).await
 }
 
+/// 继续观看中同一剧集最多显示的单集数
+const RESUME_PER_SERIES: usize = 1;
+
+/// 逗号分隔的类型列表
+fn split_types(types: Option<&str>) -> Vec<&str> {
+    types
+        .map(|t| t.split(',').map(str::trim).filter(|t| !t.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// 继续观看项目是否属于 ParentId（虚拟媒体库按类型，剧集 / 季按所属关系）
+fn resume_in_parent(item: &FnosPlayListItem, jf_type: &str, fnos_parent: &str) -> bool {
+    match fnos_parent {
+        "" => true,
+        "view_movies" => jf_type == "Movie",
+        "view_tvshows" => jf_type == "Episode",
+        p if p.starts_with("view_") => true,
+        p => item.parent_guid == p || item.ancestor_guid == p,
+    }
+}
+
+/// GET /UserItems/Resume - 继续观看
+/// 来自飞牛项目列表中有进度且未看完的项目，按桥接记录的最近播放时间倒序，同一剧集只保留最近播放的单集
 async fn items_resume(
     State(config): State<BridgeConfig>,
     Query(query): Query<ItemsQuery>,
@@ -446,6 +729,7 @@ async fn items_resume(
 
     let server_id = generate_server_id(&config.fnos_server);
 
+    // 飞牛没有播放记录接口，与 bridge-node 一样从全局项目列表（item/list，共用列表缓存）中取有进度的项目
     let result = cached_get_item_list(
         &session.fnos_server,
         &session.fnos_token,
@@ -455,27 +739,69 @@ async fn items_resume(
         &config,
     )
     .await;
+    let mut history: Vec<(Option<i64>, &FnosPlayListItem)> = match result.data.as_ref() {
+        Some(data) if result.success => data
+            .list
+            .iter()
+            .map(|item| (last_played(&session.user_id, &item.guid), item))
+            .collect(),
+        _ => {
+            warn!("[RESUME] 项目列表请求失败: {:?}，返回空列表", result.message);
+            return Json(ItemsResult { items: vec![], total_record_count: 0, start_index: 0 }).into_response();
+        }
+    };
+    // 稳定排序：按桥接记录的最近播放时间倒序，未经桥接播放的保持飞牛返回的顺序排在后面
+    history.sort_by_key(|(played_at, _)| std::cmp::Reverse(*played_at));
 
-    if !result.success || result.data.is_none() {
-        warn!("[RESUME] 飞牛请求失败: {:?}", result.message);
-        return Json(ItemsResult { items: vec![], total_record_count: 0, start_index: 0 }).into_response();
-    }
+    let fnos_parent = query.parent_id.as_deref().and_then(to_fnos_guid).unwrap_or_default();
+    let include_types = split_types(query.include_item_types.as_deref());
+    let exclude_types = split_types(query.exclude_item_types.as_deref());
 
-    let list_data = result.data.unwrap();
-    let limit = query.limit.unwrap_or(12) as usize;
+    let mut seen = std::collections::HashSet::new();
+    let mut per_series: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    let resumable: Vec<&(Option<i64>, &FnosPlayListItem)> = history
+        .iter()
+        .filter(|(_, item)| item.ts > 0.0 && item.watched != 1)
+        .filter(|(_, item)| {
+            let jf_type = map_type(&item.item_type);
+            (include_types.is_empty() || include_types.contains(&jf_type))
+                && !exclude_types.contains(&jf_type)
+                && resume_in_parent(item, jf_type, &fnos_parent)
+        })
+        .filter(|(_, item)| seen.insert(item.guid.as_str()))
+        .filter(|(_, item)| {
+            if map_type(&item.item_type) != "Episode" || item.ancestor_guid.is_empty() {
+                return true;
+            }
+            let count = per_series.entry(item.ancestor_guid.as_str()).or_default();
+            *count += 1;
+            *count <= RESUME_PER_SERIES
+        })
+        .collect();
 
-    let items: Vec<BaseItemDto> = list_data
-        .list
+    let total = resumable.len() as i64;
+    let start = query.start_index.unwrap_or(0).max(0) as usize;
+    let limit = query.limit.unwrap_or(12).max(0) as usize;
+    let with_user_data = query_flag(&query.enable_user_data) != Some(false);
+    let items: Vec<BaseItemDto> = resumable
         .iter()
-        .filter(|item| item.ts > 0.0 && item.watched == 0)
+        .skip(start)
         .take(limit)
-        .map(|item| map_playlist_item_to_dto(item, &server_id, &session.fnos_server, &session.fnos_token))
+        .map(|(played_at, item)| {
+            let mut dto = map_playlist_item_to_dto(item, &server_id);
+            if let Some(user_data) = dto.user_data.as_mut() {
+                user_data.last_played_date = played_at.and_then(format_timestamp);
+            }
+            if !with_user_data {
+                dto.user_data = None;
+            }
+            dto
+        })
         .collect();
 
-    debug!("[RESUME] 飞牛返回 {} 条, 过滤后 {} 条", list_data.list.len(), items.len());
+    debug!("[RESUME] 项目列表 {} 条, 可继续观看 {} 条", history.len(), total);
 
-    let total = items.len() as i64;
-    Json(ItemsResult { items, total_record_count: total, start_index: 0 }).into_response()
+    Json(ItemsResult { items, total_record_count: total, start_index: start as i64 }).into_response()
 }
 
 async fn build_item_response(
@@ -507,7 +833,8 @@ async fn build_item_response(
         play_info.item.guid = fnos_guid.to_string();
     }
 
-    let mut dto = map_play_info_to_dto(&play_info, server_id, &session.fnos_server, &session.fnos_token);
+    record_first_seen([play_info.item.guid.as_str()]);
+    let mut dto = map_play_info_to_dto(&play_info, server_id);
 
     // 对可播放项目，获取流信息并附加 MediaSources
     if dto.media_type.as_deref() == Some("Video") && !play_info.media_guid.is_empty() {
@@ -518,6 +845,8 @@ async fn build_item_response(
             config,
         )
         .await;
+        // 首次获取 stream/list 时元数据刚写入缓存
+        apply_video_meta(&mut dto, fnos_guid);
         if result.success {
             if let Some(sd) = result.data {
                 let files = sd["files"].as_array().cloned().unwrap_or_default();
@@ -532,6 +861,7 @@ async fn build_item_response(
                     &audio_streams,
                     &subtitle_streams,
                     play_info.item.duration,
+                    &user_config(&session.user_id),
                 );
 
                 // 注册 media_guid → item_guid 映射
@@ -544,6 +874,40 @@ async fn build_item_response(
                 if !media_sources.is_empty() {
                     dto.media_streams = media_sources[0]["MediaStreams"].as_array().map(|a| a.clone());
                 }
+
+                // 已生成的缩略图写入 Trickplay，未生成的在后台排队
+                let mut trickplay = std::collections::BTreeMap::new();
+                for ms in &media_sources {
+                    let Some(id) = ms["Id"].as_str() else { continue };
+                    match get_trickplay(id) {
+                        Some(entry) => {
+                            trickplay.insert(id.to_string(), entry.resolutions);
+                        }
+                        None => schedule_trickplay(
+                            config,
+                            &session.access_token,
+                            item_id,
+                            fnos_guid,
+                            id,
+                            play_info.item.duration,
+                        ),
+                    }
+                }
+                if !trickplay.is_empty() {
+                    dto.trickplay = Some(trickplay);
+                }
+
+                // 容器章节（取第一个版本）：尚未读取时在后台读取，缺少的章节图片在后台截取
+                if let Some(first_id) = media_sources.first().and_then(|ms| ms["Id"].as_str()) {
+                    match get_chapters(fnos_guid, first_id) {
+                        Some(chapters) if !chapters.is_empty() => {
+                            schedule_chapter_images(config, &session.access_token, item_id, fnos_guid, first_id, &chapters);
+                            dto.chapters = Some(map_chapters(fnos_guid, &chapters));
+                        }
+                        Some(_) => {}
+                        None => schedule_chapter_probe(config, &session.access_token, item_id, fnos_guid, first_id),
+                    }
+                }
                 dto.media_sources = Some(media_sources);
             }
         }
@@ -552,65 +916,106 @@ async fn build_item_response(
     Json(dto)
 }
 
-/// GET /Items/Filters - 返回过滤器选项
-async fn items_filters(
-    State(config): State<BridgeConfig>,
-    req: axum::extract::Request,
-) -> axum::response::Response {
-    use axum::response::IntoResponse;
-    use axum::http::StatusCode;
-
-    let session = match req.extensions().get::<SessionData>() {
-        Some(s) => s.clone(),
-        None => return (StatusCode::UNAUTHORIZED, Json(json!({"error":"Unauthorized"}))).into_response(),
+/// 过滤器选项的来源项目：按 ParentId（含虚拟媒体库）与 IncludeItemTypes 筛选
+async fn filter_source_items(
+    session: &SessionData,
+    config: &BridgeConfig,
+    query: &ItemsQuery,
+) -> Vec<FnosPlayListItem> {
+    let fnos_parent = query.parent_id.as_deref().and_then(to_fnos_guid).unwrap_or_default();
+    let (parent_guid, view_filter) = match fnos_parent.as_str() {
+        "view_movies" => ("", "Movie"),
+        "view_tvshows" => ("", "Series"),
+        p if p.starts_with("view_") => ("", ""),
+        p => (p, ""),
     };
 
-    // 获取所有项目来提取过滤器选项
     let result = cached_get_item_list(
         &session.fnos_server,
         &session.fnos_token,
-        "",
+        parent_guid,
         "sort_title",
         "ASC",
-        &config,
+        config,
     )
     .await;
+    let Some(list_data) = result.data.filter(|_| result.success) else {
+        return vec![];
+    };
 
-    if !result.success || result.data.is_none() {
-        return Json(json!({
-            "Genres": [],
-            "Tags": [],
-            "Years": [],
-        })).into_response();
-    }
+    let types: Vec<&str> = query.include_item_types.as_deref()
+        .map(|t| t.split(',').map(str::trim).filter(|t| !t.is_empty()).collect())
+        .unwrap_or_default();
+    list_data.list.into_iter()
+        .filter(|item| {
+            let jf_type = map_type(&item.item_type);
+            (view_filter.is_empty() || jf_type == view_filter)
+                && (types.is_empty() || types.contains(&jf_type))
+        })
+        .collect()
+}
 
-    let list_data = result.data.unwrap();
-    
-    // 提取唯一的年份（从 air_date 解析）
-    let mut years: std::collections::HashSet<i32> = std::collections::HashSet::new();
-    
-    for item in &list_data.list {
-        // 从 air_date 字段提取年份 (格式: "2023-01-15")
-        if !item.air_date.is_empty() {
-            if let Some(year_str) = item.air_date.split('-').next() {
-                if let Ok(year) = year_str.parse::<i32>() {
-                    if year > 1900 && year < 2100 {
-                        years.insert(year);
-                    }
+/// 过滤器选项
+/// 飞牛列表不返回类型、标签、分级、制作公司、字幕等信息，这些选项为空；
+/// 年份取自首播日期，IsHD / Is4K / Is3D 取自 stream/list 缓存
+#[derive(Default)]
+struct FilterOptions {
+    /// 降序
+    years: Vec<i32>,
+    is_hd: bool,
+    is_4k: bool,
+    is_3d: bool,
+}
+
+impl FilterOptions {
+    fn collect(items: &[FnosPlayListItem]) -> Self {
+        let mut options = Self::default();
+        for item in items {
+            if let Some(year) = item.year().filter(|y| *y > 1900 && *y < 2100) {
+                if !options.years.contains(&year) {
+                    options.years.push(year);
                 }
             }
+            options.is_hd |= is_hd_item(item);
+            options.is_4k |= is_4k_item(item);
+            options.is_3d |= is_3d_item(item);
         }
+        options.years.sort_unstable_by(|a, b| b.cmp(a));
+        options
     }
 
-    let years_vec: Vec<i32> = {
-        let mut v: Vec<i32> = years.into_iter().collect();
-        v.sort_unstable_by(|a, b| b.cmp(a)); // 降序排列
-        v
-    };
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "Genres": [],
+            "Tags": [],
+            "OfficialRatings": [],
+            "Studios": [],
+            "Years": self.years,
+            "HasSubtitles": false,
+            "HasTrailer": false,
+            "IsHD": self.is_hd,
+            "Is4K": self.is_4k,
+            "Is3D": self.is_3d,
+        })
+    }
+}
+
+/// GET /Items/Filters - 返回过滤器选项（旧版）
+async fn items_filters(
+    State(config): State<BridgeConfig>,
+    Query(query): Query<ItemsQuery>,
+    Extension(session): Extension<SessionData>,
+) -> Json<serde_json::Value> {
+    let items = filter_source_items(&session, &config, &query).await;
+    Json(FilterOptions::collect(&items).to_json())
+}
 
-    Json(json!({
-        "Genres": [], // 飞牛 API 不返回流派信息
-        "Tags": [],   // 飞牛没有标签概念
-        "Years": years_vec,
-    })).into_response()
+/// GET /Items/Filters2 - 返回过滤器选项（与旧版相同，Genres / Studios 为空）
+async fn items_filters2(
+    State(config): State<BridgeConfig>,
+    Query(query): Query<ItemsQuery>,
+    Extension(session): Extension<SessionData>,
+) -> Json<serde_json::Value> {
+    let items = filter_source_items(&session, &config, &query).await;
+    Json(FilterOptions::collect(&items).to_json())
 }
@@ -14,11 +14,14 @@ use tracing::{debug, error, warn};
 use crate::cache::stream_list::cached_get_stream_list;
 use crate::config::BridgeConfig;
 use crate::mappers::id::*;
-use crate::mappers::media::{build_media_sources, get_subtitle_info};
+use crate::mappers::media::{build_media_sources, get_subtitle_info, video_range};
+use crate::mappers::user::user_config;
 use crate::middleware::auth::{extract_token, require_auth};
 use crate::services::fnos::*;
-use crate::services::hls_session::{clear_hls_session, register_stream_meta, StreamMeta};
+use crate::services::device_profile::supports_video_range_type;
+use crate::services::hls_session::{clear_hls_session, hls_session_key, register_stream_meta, StreamMeta};
 use crate::services::session::SessionData;
+use crate::types::jellyfin::UserConfiguration;
 
 /// 反序列化：支持数字或字符串形式的 i32（jellyfin-web 有时发字符串 "1" 而非数字 1）
 fn deserialize_optional_i32_lenient<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
@@ -94,6 +97,27 @@ struct PlaybackInfoDto {
     enable_direct_play: Option<bool>,
     #[serde(rename = "EnableDirectStream", deserialize_with = "deserialize_optional_bool_lenient")]
     enable_direct_stream: Option<bool>,
+    #[serde(rename = "DeviceProfile")]
+    device_profile: Option<serde_json::Value>,
+}
+
+/// 片源为 HDR / 杜比视界时的转码选择
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HdrDecision {
+    /// 客户端 DeviceProfile 不支持片源的 VideoRangeType，必须转码
+    unsupported: bool,
+    /// 转码时映射为 SDR
+    forced_sdr: bool,
+}
+
+fn hdr_decision(vs: &serde_json::Value, profile: Option<&serde_json::Value>, user: &UserConfiguration) -> HdrDecision {
+    let (range, range_type) = video_range(vs);
+    if range != "HDR" {
+        return HdrDecision { unsupported: false, forced_sdr: false };
+    }
+    let codec = vs["codec_name"].as_str().unwrap_or("");
+    let unsupported = profile.is_some_and(|p| !supports_video_range_type(p, codec, range_type));
+    HdrDecision { unsupported, forced_sdr: unsupported || user.force_sdr_transcoding }
 }
 
 pub fn router() -> Router<BridgeConfig> {
@@ -151,6 +175,7 @@ async fn playback_info(
     let max_streaming_bitrate = body.max_streaming_bitrate;
     let enable_direct_play = body.enable_direct_play;
     let enable_direct_stream = body.enable_direct_stream;
+    let device_profile = body.device_profile;
 
     debug!(
         "[PlaybackInfo] item={}, MediaSourceId={}, AudioStreamIndex={:?}, MaxBitrate={:?}, EnableDP={:?}, EnableDS={:?}",
@@ -201,6 +226,7 @@ async fn playback_info(
     let audio_streams = sd["audio_streams"].as_array().cloned().unwrap_or_default();
     let subtitle_streams = sd["subtitle_streams"].as_array().cloned().unwrap_or_default();
 
+    let user_cfg = user_config(&session.user_id);
     let mut media_sources = build_media_sources(
         &item_id,
         &files,
@@ -208,6 +234,7 @@ async fn playback_info(
         &audio_streams,
         &subtitle_streams,
         play_info.item.duration,
+        &user_cfg,
     );
 
     // 如果客户端指定了 MediaSourceId，只返回那个版本
@@ -237,9 +264,27 @@ async fn playback_info(
         let fallback_audio = my_audio_streams.first().copied()
             .or_else(|| audio_streams.first());
 
+        // HDR / 杜比视界：客户端不支持片源动态范围时禁用 DirectStream，并在转码时映射为 SDR
+        let hdr = vs0
+            .map(|vs| hdr_decision(vs, device_profile.as_ref(), &user_cfg))
+            .unwrap_or(HdrDecision { unsupported: false, forced_sdr: false });
+        if hdr.unsupported {
+            debug!("  [HDR] 客户端不支持片源动态范围 → 转码为 SDR: mediaGuid={}", ms_id);
+            ms["SupportsDirectStream"] = json!(false);
+            ms["TranscodingSubProtocol"] = json!("hls");
+            ms["TranscodeReasons"] = json!(["VideoRangeTypeNotSupported"]);
+        }
+        // 映射为 SDR 的转码使用独立的会话键，不影响其他客户端正在播放的原动态范围会话
+        if hdr.forced_sdr {
+            ms["TranscodingUrl"] = json!(format!("/Videos/{}/hls/preset.m3u8", hls_session_key(&ms_id, true)));
+        }
+
         // 如果客户端指定了 AudioStreamIndex，找到对应的飞牛音频流
+        // 未指定时使用按用户语言偏好选出的 DefaultAudioStreamIndex
         let mut selected_audio = fallback_audio;
-        if let Some(req_audio_idx) = audio_stream_index {
+        let effective_audio_index = audio_stream_index
+            .or_else(|| ms["DefaultAudioStreamIndex"].as_i64().map(|i| i as i32));
+        if let Some(req_audio_idx) = effective_audio_index {
             if let Some(media_streams) = ms["MediaStreams"].as_array() {
                 let audio_in_ms: Vec<(usize, &serde_json::Value)> = media_streams.iter()
                     .enumerate()
@@ -331,6 +376,7 @@ async fn playback_info(
                     subtitle_guid: selected_subtitle_guid,
                     channels: audio["channels"].as_i64().unwrap_or(2) as i32,
                     duration: play_info.item.duration,
+                    forced_sdr: hdr.forced_sdr,
                 },
             );
         }
@@ -356,6 +402,16 @@ async fn playback_info(
             }
         }
 
+        // 走转码且映射为 SDR 时，视频流按转码后的动态范围返回
+        if hdr.forced_sdr && !ms["SupportsDirectStream"].as_bool().unwrap_or(false) {
+            if let Some(streams) = ms["MediaStreams"].as_array_mut() {
+                for stream in streams.iter_mut().filter(|s| s["Type"].as_str() == Some("Video")) {
+                    stream["VideoRange"] = json!("SDR");
+                    stream["VideoRangeType"] = json!("SDR");
+                }
+            }
+        }
+
         // 注入 api_key 到 TranscodingUrl
         if let Some(token) = &user_token {
             if let Some(url) = ms["TranscodingUrl"].as_str() {
@@ -369,6 +425,9 @@ async fn playback_info(
                 if let Some(audio_idx) = audio_stream_index {
                     new_url = format!("{}&AudioStreamIndex={}", new_url, audio_idx);
                 }
+                if hdr.unsupported {
+                    new_url = format!("{}&TranscodeReasons=VideoRangeTypeNotSupported", new_url);
+                }
                 ms["TranscodingUrl"] = json!(new_url);
             }
             if let Some(url) = ms["DirectStreamUrl"].as_str() {
@@ -0,0 +1,153 @@
+/// MediaSegments 路由 — 片头/片尾跳过
+
+use axum::{
+    extract::{Path, RawQuery, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+    Extension, Json, Router,
+};
+use serde_json::json;
+use tracing::debug;
+
+use crate::config::BridgeConfig;
+use crate::mappers::id::{derived_id, to_fnos_guid};
+use crate::mappers::item::seconds_to_ticks;
+use crate::middleware::auth::require_auth;
+use crate::services::fnos::fnos_get_play_info;
+use crate::services::media_segments::{find_segments, normalize_segment_type, set_segments, StoredSegment};
+use crate::services::session::SessionData;
+use crate::types::jellyfin::MediaSegmentDto;
+
+pub fn router() -> Router<BridgeConfig> {
+    Router::new().route(
+        "/MediaSegments/{itemId}",
+        get(segments_get)
+            .post(segments_set)
+            .delete(segments_delete)
+            .layer(axum::middleware::from_fn(require_auth)),
+    )
+}
+
+/// includeSegmentTypes 可重复出现，也可逗号分隔
+fn include_types(raw_query: Option<&str>) -> Vec<String> {
+    raw_query
+        .unwrap_or("")
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .filter(|(k, _)| k.eq_ignore_ascii_case("includeSegmentTypes"))
+        .flat_map(|(_, v)| {
+            v.replace("%2C", ",").replace("%2c", ",").split(',').map(String::from).collect::<Vec<_>>()
+        })
+        .filter_map(|v| normalize_segment_type(&v).map(String::from))
+        .collect()
+}
+
+/// GET /MediaSegments/{itemId}
+/// 桥接层保存的片段优先，其次使用飞牛的跳过片头/片尾设置
+async fn segments_get(
+    State(config): State<BridgeConfig>,
+    Path(item_id): Path<String>,
+    RawQuery(raw_query): RawQuery,
+    Extension(session): Extension<SessionData>,
+) -> axum::response::Response {
+    let Some(guid) = to_fnos_guid(&item_id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let result = fnos_get_play_info(&session.fnos_server, &session.fnos_token, &guid, &config).await;
+    let Some(info) = result.data.filter(|_| result.success) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let mut segments = find_segments(&[&guid, &info.parent_guid, &info.grand_guid]);
+    let has = |segments: &[StoredSegment], t: &str| segments.iter().any(|s| s.segment_type == t);
+
+    let duration = info.item.duration;
+    if let Some(opening) = info.play_config.skip_opening.filter(|s| *s > 0.0) {
+        if !has(&segments, "Intro") {
+            segments.push(StoredSegment {
+                segment_type: "Intro".into(),
+                start_ticks: 0,
+                end_ticks: seconds_to_ticks(opening),
+            });
+        }
+    }
+    if let Some(ending) = info.play_config.skip_ending.filter(|s| *s > 0.0 && *s < duration) {
+        if !has(&segments, "Outro") {
+            segments.push(StoredSegment {
+                segment_type: "Outro".into(),
+                start_ticks: seconds_to_ticks(duration - ending),
+                end_ticks: seconds_to_ticks(duration),
+            });
+        }
+    }
+
+    let include = include_types(raw_query.as_deref());
+    let mut items: Vec<MediaSegmentDto> = segments
+        .into_iter()
+        .filter(|s| include.is_empty() || include.contains(&s.segment_type))
+        .map(|s| MediaSegmentDto {
+            id: derived_id(&format!("{}:{}:{}", guid, s.segment_type, s.start_ticks)),
+            item_id: item_id.clone(),
+            segment_type: s.segment_type,
+            start_ticks: s.start_ticks,
+            end_ticks: s.end_ticks,
+        })
+        .collect();
+    items.sort_by_key(|s| s.start_ticks);
+
+    debug!("[SEGMENT] 片段: guid={}, count={}", guid, items.len());
+    let total = items.len();
+    Json(json!({
+        "Items": items,
+        "TotalRecordCount": total,
+        "StartIndex": 0,
+    }))
+    .into_response()
+}
+
+/// POST /MediaSegments/{itemId} — 桥接层扩展：保存片段（替换原有，仅管理员）
+/// itemId 为季或剧集时对其下所有单集生效
+async fn segments_set(
+    State(config): State<BridgeConfig>,
+    Extension(session): Extension<SessionData>,
+    Path(item_id): Path<String>,
+    Json(body): Json<Vec<StoredSegment>>,
+) -> axum::response::Response {
+    if !config.is_admin_user(&session.username) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Some(guid) = to_fnos_guid(&item_id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let mut segments = Vec::with_capacity(body.len());
+    for mut segment in body {
+        let Some(t) = normalize_segment_type(&segment.segment_type) else {
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": "Unknown segment type"}))).into_response();
+        };
+        if segment.start_ticks < 0 || segment.end_ticks <= segment.start_ticks {
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": "Invalid segment range"}))).into_response();
+        }
+        segment.segment_type = t.to_string();
+        segments.push(segment);
+    }
+    debug!("[SEGMENT] 保存片段: guid={}, count={}", guid, segments.len());
+    set_segments(&guid, segments);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// DELETE /MediaSegments/{itemId}（仅管理员）
+async fn segments_delete(
+    State(config): State<BridgeConfig>,
+    Extension(session): Extension<SessionData>,
+    Path(item_id): Path<String>,
+) -> StatusCode {
+    if !config.is_admin_user(&session.username) {
+        return StatusCode::FORBIDDEN;
+    }
+    let Some(guid) = to_fnos_guid(&item_id) else {
+        return StatusCode::NOT_FOUND;
+    };
+    set_segments(&guid, vec![]);
+    StatusCode::NO_CONTENT
+}
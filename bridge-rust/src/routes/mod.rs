@@ -7,3 +7,9 @@ pub mod images;
 pub mod mediainfo;
 pub mod playback;
 pub mod extras;
+pub mod displayprefs;
+pub mod playlists;
+pub mod subtitles;
+pub mod trickplay;
+pub mod mediasegments;
+pub mod search;
@@ -18,6 +18,7 @@ use crate::middleware::auth::require_auth;
 use crate::services::fnos::{fnos_get_play_info, fnos_record_play_status};
 use crate::services::hls_session::{get_hls_play_link, get_stream_meta};
 use crate::cache::item_list::update_item_progress;
+use crate::cache::play_history::record_played;
 use crate::services::session::{SessionData, NowPlayingState, set_now_playing, update_now_playing_progress, clear_now_playing};
 
 /// 播放信息缓存
@@ -126,6 +127,7 @@ async fn handle_play_report(
     if fnos_guid.is_empty() {
         return axum::http::StatusCode::NO_CONTENT;
     }
+    record_played(&session.user_id, &fnos_guid);
 
     let ts = ticks_to_seconds(position_ticks);
 
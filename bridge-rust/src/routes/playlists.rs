@@ -0,0 +1,252 @@
+/// Playlists 路由 — 桥接层本地播放列表
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, warn};
+
+use crate::config::BridgeConfig;
+use crate::mappers::id::{generate_server_id, register_item_type, to_fnos_guid, to_jellyfin_id};
+use crate::mappers::item::map_play_info_to_dto;
+use crate::middleware::auth::require_auth;
+use crate::services::fnos::fnos_get_play_info;
+use crate::services::playlist::{
+    add_items, create_playlist, find_playlist, move_entry, remove_entries, rename_playlist, Playlist,
+};
+use crate::services::session::SessionData;
+use crate::types::jellyfin::ItemsResult;
+
+pub fn router() -> Router<BridgeConfig> {
+    Router::new()
+        .route(
+            "/Playlists",
+            post(playlist_create).layer(axum::middleware::from_fn(require_auth)),
+        )
+        .route(
+            "/Playlists/{playlistId}",
+            get(playlist_get)
+                .post(playlist_update)
+                .layer(axum::middleware::from_fn(require_auth)),
+        )
+        .route(
+            "/Playlists/{playlistId}/Items",
+            get(playlist_items)
+                .post(playlist_add_items)
+                .delete(playlist_remove_items)
+                .layer(axum::middleware::from_fn(require_auth)),
+        )
+        .route(
+            "/Playlists/{playlistId}/Items/{itemId}/Move/{newIndex}",
+            post(playlist_move_item).layer(axum::middleware::from_fn(require_auth)),
+        )
+}
+
+/// 创建/更新请求体（Jellyfin 10.9 起通过 body 提交，旧版通过 query）
+#[derive(Deserialize, Default)]
+struct PlaylistBody {
+    #[serde(rename = "Name", default)]
+    name: Option<String>,
+    #[serde(rename = "Ids", default)]
+    ids: Option<Vec<String>>,
+    #[serde(rename = "MediaType", default)]
+    media_type: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct PlaylistQuery {
+    #[serde(alias = "Name", alias = "name")]
+    name: Option<String>,
+    #[serde(alias = "Ids", alias = "ids")]
+    ids: Option<String>,
+    #[serde(alias = "MediaType", alias = "mediaType")]
+    media_type: Option<String>,
+    #[serde(alias = "EntryIds", alias = "entryIds")]
+    entry_ids: Option<String>,
+    #[serde(alias = "StartIndex", alias = "startIndex")]
+    start_index: Option<i64>,
+    #[serde(alias = "Limit", alias = "limit")]
+    limit: Option<i64>,
+}
+
+fn split_ids(ids: &str) -> Vec<String> {
+    ids.split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Jellyfin ID → 飞牛 GUID，无法识别的 ID 跳过
+fn resolve_guids(ids: &[String]) -> Vec<String> {
+    ids.iter()
+        .filter_map(|id| {
+            let guid = to_fnos_guid(id);
+            if guid.is_none() {
+                warn!("[PLAYLIST] 无法转换 item_id: {}", id);
+            }
+            guid
+        })
+        .filter(|g| !g.starts_with("view_"))
+        .collect()
+}
+
+/// 可选的 JSON 请求体（旧版客户端只传 query，没有 body）
+fn parse_body(body: &str) -> PlaylistBody {
+    if body.trim().is_empty() {
+        return PlaylistBody::default();
+    }
+    serde_json::from_str(body).unwrap_or_default()
+}
+
+fn not_found() -> axum::response::Response {
+    (StatusCode::NOT_FOUND, Json(json!({"error": "Playlist not found"}))).into_response()
+}
+
+/// 播放列表一页条目 → BaseItemDto（带 PlaylistItemId）
+/// 先按 StartIndex / Limit 截取条目，再并发查询飞牛 play/info；总数为条目数（含已失效、本页被跳过的条目）
+pub(crate) async fn playlist_items_page(
+    session: &SessionData,
+    playlist: &Playlist,
+    start: usize,
+    limit: usize,
+    config: &BridgeConfig,
+) -> ItemsResult {
+    let server_id = generate_server_id(&config.fnos_server);
+
+    let entries: Vec<_> = playlist.items.iter().skip(start).take(limit).collect();
+    let results = futures_util::future::join_all(
+        entries.iter().map(|entry| fnos_get_play_info(&session.fnos_server, &session.fnos_token, &entry.guid, config)),
+    ).await;
+
+    let mut items = Vec::new();
+    for (entry, result) in entries.into_iter().zip(results) {
+        let Some(info) = result.data.filter(|_| result.success) else {
+            debug!("[PLAYLIST] 条目已失效: guid={}", entry.guid);
+            continue;
+        };
+        register_item_type(&info.item.guid, &info.item.item_type);
+        let mut dto = map_play_info_to_dto(&info, &server_id);
+        dto.playlist_item_id = Some(entry.entry_id.clone());
+        items.push(dto);
+    }
+    ItemsResult { items, total_record_count: playlist.items.len() as i64, start_index: start as i64 }
+}
+
+/// POST /Playlists
+async fn playlist_create(
+    Query(query): Query<PlaylistQuery>,
+    Extension(session): Extension<SessionData>,
+    body: String,
+) -> axum::response::Response {
+    let body = parse_body(&body);
+    let name = body.name.or(query.name).unwrap_or_default();
+    if name.trim().is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "Name is required"}))).into_response();
+    }
+    let ids = body.ids.unwrap_or_else(|| split_ids(query.ids.as_deref().unwrap_or("")));
+    let media_type = body.media_type.or(query.media_type).unwrap_or_default();
+
+    let playlist = create_playlist(&session.user_id, name.trim(), &media_type, &resolve_guids(&ids));
+    debug!("[PLAYLIST] 创建播放列表: name={}, items={}", playlist.name, playlist.items.len());
+    Json(json!({"Id": playlist.jellyfin_id()})).into_response()
+}
+
+/// GET /Playlists/{playlistId}
+async fn playlist_get(
+    Path(playlist_id): Path<String>,
+    Extension(session): Extension<SessionData>,
+) -> axum::response::Response {
+    let Some(playlist) = find_playlist(&session.user_id, &playlist_id) else {
+        return not_found();
+    };
+    let item_ids: Vec<String> = playlist.items.iter()
+        .map(|e| to_jellyfin_id(&e.guid))
+        .collect();
+    Json(json!({
+        "OpenAccess": false,
+        "Shares": [],
+        "ItemIds": item_ids,
+    }))
+    .into_response()
+}
+
+/// POST /Playlists/{playlistId} — 重命名
+async fn playlist_update(
+    Path(playlist_id): Path<String>,
+    Extension(session): Extension<SessionData>,
+    body: String,
+) -> StatusCode {
+    let Some(playlist) = find_playlist(&session.user_id, &playlist_id) else {
+        return StatusCode::NOT_FOUND;
+    };
+    if let Some(name) = parse_body(&body).name.filter(|n| !n.trim().is_empty()) {
+        rename_playlist(&session.user_id, &playlist.id, name.trim());
+    }
+    StatusCode::NO_CONTENT
+}
+
+/// GET /Playlists/{playlistId}/Items
+async fn playlist_items(
+    State(config): State<BridgeConfig>,
+    Path(playlist_id): Path<String>,
+    Query(query): Query<PlaylistQuery>,
+    Extension(session): Extension<SessionData>,
+) -> axum::response::Response {
+    let Some(playlist) = find_playlist(&session.user_id, &playlist_id) else {
+        return not_found();
+    };
+
+    let start = query.start_index.unwrap_or(0).max(0) as usize;
+    let limit = query.limit.map(|l| l.max(0) as usize).unwrap_or(playlist.items.len());
+    Json(playlist_items_page(&session, &playlist, start, limit, &config).await).into_response()
+}
+
+/// POST /Playlists/{playlistId}/Items?ids=
+async fn playlist_add_items(
+    Path(playlist_id): Path<String>,
+    Query(query): Query<PlaylistQuery>,
+    Extension(session): Extension<SessionData>,
+) -> StatusCode {
+    let Some(playlist) = find_playlist(&session.user_id, &playlist_id) else {
+        return StatusCode::NOT_FOUND;
+    };
+    let guids = resolve_guids(&split_ids(query.ids.as_deref().unwrap_or("")));
+    debug!("[PLAYLIST] 添加条目: playlist={}, count={}", playlist.name, guids.len());
+    add_items(&session.user_id, &playlist.id, &guids);
+    StatusCode::NO_CONTENT
+}
+
+/// DELETE /Playlists/{playlistId}/Items?entryIds=
+async fn playlist_remove_items(
+    Path(playlist_id): Path<String>,
+    Query(query): Query<PlaylistQuery>,
+    Extension(session): Extension<SessionData>,
+) -> StatusCode {
+    let Some(playlist) = find_playlist(&session.user_id, &playlist_id) else {
+        return StatusCode::NOT_FOUND;
+    };
+    let entry_ids = split_ids(query.entry_ids.as_deref().unwrap_or(""));
+    debug!("[PLAYLIST] 删除条目: playlist={}, count={}", playlist.name, entry_ids.len());
+    remove_entries(&session.user_id, &playlist.id, &entry_ids);
+    StatusCode::NO_CONTENT
+}
+
+/// POST /Playlists/{playlistId}/Items/{itemId}/Move/{newIndex}
+async fn playlist_move_item(
+    Path((playlist_id, entry_id, new_index)): Path<(String, String, usize)>,
+    Extension(session): Extension<SessionData>,
+) -> StatusCode {
+    let Some(playlist) = find_playlist(&session.user_id, &playlist_id) else {
+        return StatusCode::NOT_FOUND;
+    };
+    if !playlist.items.iter().any(|e| e.entry_id == entry_id) {
+        return StatusCode::NOT_FOUND;
+    }
+    move_entry(&session.user_id, &playlist.id, &entry_id, new_index);
+    StatusCode::NO_CONTENT
+}
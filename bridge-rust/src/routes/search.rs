@@ -0,0 +1,133 @@
+/// Search 路由 — 搜索提示（电视端 / Xbox 搜索页）
+
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Extension, Json, Router,
+};
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::cache::item_list::cached_get_item_list;
+use crate::config::BridgeConfig;
+use crate::mappers::id::{generate_server_id, to_fnos_guid};
+use crate::mappers::item::{map_media_type, map_playlist_item_to_dto, map_search_hint, map_type};
+use crate::middleware::auth::require_auth;
+use crate::services::search::rank_items;
+use crate::services::session::SessionData;
+use crate::types::jellyfin::SearchHintResult;
+
+pub fn router() -> Router<BridgeConfig> {
+    Router::new().route(
+        "/Search/Hints",
+        get(search_hints).layer(axum::middleware::from_fn(require_auth)),
+    )
+}
+
+#[derive(Deserialize, Default)]
+struct SearchHintsQuery {
+    #[serde(alias = "SearchTerm", alias = "searchTerm")]
+    search_term: Option<String>,
+    #[serde(alias = "StartIndex", alias = "startIndex")]
+    start_index: Option<i64>,
+    #[serde(alias = "Limit", alias = "limit")]
+    limit: Option<i64>,
+    #[serde(alias = "ParentId", alias = "parentId")]
+    parent_id: Option<String>,
+    #[serde(alias = "IncludeItemTypes", alias = "includeItemTypes")]
+    include_item_types: Option<String>,
+    #[serde(alias = "ExcludeItemTypes", alias = "excludeItemTypes")]
+    exclude_item_types: Option<String>,
+    #[serde(alias = "MediaTypes", alias = "mediaTypes")]
+    media_types: Option<String>,
+    #[serde(alias = "IsMovie", alias = "isMovie")]
+    is_movie: Option<String>,
+    #[serde(alias = "IsSeries", alias = "isSeries")]
+    is_series: Option<String>,
+    #[serde(alias = "IncludeMedia", alias = "includeMedia")]
+    include_media: Option<String>,
+}
+
+fn split_list(value: &Option<String>) -> Vec<&str> {
+    value
+        .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn is_true(value: &Option<String>) -> bool {
+    value.as_deref().is_some_and(|v| v.eq_ignore_ascii_case("true"))
+}
+
+fn is_false(value: &Option<String>) -> bool {
+    value.as_deref().is_some_and(|v| v.eq_ignore_ascii_case("false"))
+}
+
+/// GET /Search/Hints
+/// 只搜索媒体（没有人物 / 流派 / 工作室数据）
+async fn search_hints(
+    State(config): State<BridgeConfig>,
+    Extension(session): Extension<SessionData>,
+    Query(query): Query<SearchHintsQuery>,
+) -> Json<SearchHintResult> {
+    let empty = || Json(SearchHintResult { search_hints: vec![], total_record_count: 0 });
+    let term = query.search_term.as_deref().unwrap_or("").trim();
+    if term.is_empty() || is_false(&query.include_media) {
+        return empty();
+    }
+
+    // 虚拟媒体库只限定类型，真实目录作为飞牛 parent_guid
+    let fnos_parent = query.parent_id.as_deref().and_then(to_fnos_guid).unwrap_or_default();
+    let view_filter = match fnos_parent.as_str() {
+        "view_movies" => Some("Movie"),
+        "view_tvshows" => Some("Series"),
+        _ => None,
+    };
+    let parent_guid = if fnos_parent.starts_with("view_") { "" } else { fnos_parent.as_str() };
+
+    let result = cached_get_item_list(&session.fnos_server, &session.fnos_token, parent_guid, "sort_title", "ASC", &config).await;
+    let Some(list_data) = result.data.filter(|_| result.success) else {
+        return empty();
+    };
+
+    let include_types = split_list(&query.include_item_types);
+    let exclude_types = split_list(&query.exclude_item_types);
+    let media_types = split_list(&query.media_types);
+    let candidates: Vec<_> = list_data
+        .list
+        .iter()
+        .filter(|item| {
+            let jf_type = map_type(&item.item_type);
+            view_filter.is_none_or(|t| t == jf_type)
+                && (include_types.is_empty() || include_types.contains(&jf_type))
+                && !exclude_types.contains(&jf_type)
+                && (media_types.is_empty() || map_media_type(jf_type).is_some_and(|m| media_types.contains(&m)))
+                && (!is_true(&query.is_movie) || jf_type == "Movie")
+                && (!is_true(&query.is_series) || matches!(jf_type, "Series" | "Season" | "Episode"))
+        })
+        .collect();
+
+    let ranked = rank_items(term, candidates);
+    debug!("[SEARCH] \"{}\" 命中 {} 条", term, ranked.len());
+
+    let server_id = generate_server_id(&config.fnos_server);
+    let total = ranked.len() as i64;
+    let start = query.start_index.unwrap_or(0).max(0) as usize;
+    let limit = query.limit.unwrap_or(50).max(0) as usize;
+    let search_hints = ranked
+        .into_iter()
+        .skip(start)
+        .take(limit)
+        .map(|item| {
+            let dto = map_playlist_item_to_dto(item, &server_id);
+            let mut hint = map_search_hint(item, &dto);
+            hint.matched_term = Some(term.to_string());
+            hint
+        })
+        .collect();
+
+    Json(SearchHintResult { search_hints, total_record_count: total })
+}
@@ -7,13 +7,16 @@ use axum::{
 };
 use serde::Deserialize;
 use serde_json::json;
+use tracing::warn;
 
+use crate::cache::first_seen::record_first_seen;
 use crate::config::BridgeConfig;
 use crate::mappers::id::*;
 use crate::mappers::item::*;
 use crate::middleware::auth::require_auth;
 use crate::services::fnos::*;
 use crate::services::session::SessionData;
+use crate::types::fnos::FnosPlayListItem;
 use crate::types::jellyfin::{BaseItemDto, ItemsResult};
 
 pub fn router() -> Router<BridgeConfig> {
@@ -52,6 +55,14 @@ struct EpisodesQuery {
     start_index: Option<i64>,
     #[serde(rename = "Limit")]
     limit: Option<i64>,
+    #[serde(rename = "StartItemId")]
+    start_item_id: Option<String>,
+    #[serde(rename = "IsMissing")]
+    is_missing: Option<bool>,
+    #[serde(rename = "IsVirtualUnaired")]
+    is_virtual_unaired: Option<bool>,
+    #[serde(rename = "AdjustForSpecials")]
+    adjust_for_specials: Option<bool>,
 }
 
 async fn seasons(
@@ -87,11 +98,12 @@ async fn seasons(
     }
 
     let seasons = result.data.unwrap();
+    record_first_seen(seasons.iter().map(|s| s.guid.as_str()));
     let items: Vec<BaseItemDto> = seasons
         .iter()
         .map(|s| {
             register_item_type(&s.guid, "Season");
-            let mut dto = map_playlist_item_to_dto(s, &server_id, &session.fnos_server, &session.fnos_token);
+            let mut dto = map_playlist_item_to_dto(s, &server_id);
             dto.item_type = "Season".into();
             dto.index_number = Some(s.season_number);
             dto.series_id = Some(to_jellyfin_id(&fnos_guid));
@@ -99,6 +111,7 @@ async fn seasons(
             if dto.name.is_empty() {
                 dto.name = format!("第 {} 季", s.season_number);
             }
+            apply_parent_images(&mut dto);
             dto
         })
         .collect();
@@ -107,6 +120,118 @@ async fn seasons(
     Json(ItemsResult { items, total_record_count: total, start_index: 0 }).into_response()
 }
 
+/// 集列表条目：飞牛中的单集，或按季的总集数补出的缺失集
+enum EpisodeEntry<'a> {
+    Local(&'a FnosPlayListItem),
+    Missing { season: &'a FnosPlayListItem, number: i32 },
+}
+
+impl EpisodeEntry<'_> {
+    fn season_number(&self) -> i32 {
+        match self {
+            Self::Local(ep) => ep.season_number,
+            Self::Missing { season, .. } => season.season_number,
+        }
+    }
+
+    fn episode_number(&self) -> i32 {
+        match self {
+            Self::Local(ep) => ep.episode_number,
+            Self::Missing { number, .. } => *number,
+        }
+    }
+
+    fn air_date(&self) -> &str {
+        match self {
+            Self::Local(ep) => &ep.air_date,
+            Self::Missing { .. } => "",
+        }
+    }
+
+    fn is_missing(&self) -> bool {
+        matches!(self, Self::Missing { .. })
+    }
+}
+
+/// 第 season 季的播出区间 [该季首播日期, 下一季首播日期)，该季没有首播日期时为 None
+fn season_air_window(seasons: &[FnosPlayListItem], season: i32) -> Option<(&str, Option<&str>)> {
+    let start = seasons.iter()
+        .find(|s| s.season_number == season)
+        .map(|s| s.air_date.as_str())
+        .filter(|d| !d.is_empty())?;
+    let end = seasons.iter()
+        .filter(|s| s.season_number > season && !s.air_date.is_empty())
+        .min_by_key(|s| s.season_number)
+        .map(|s| s.air_date.as_str());
+    Some((start, end))
+}
+
+/// 季的单集，加上 number_of_episodes 多于本地集数时缺失的集号
+fn season_entries<'a>(season: Option<&'a FnosPlayListItem>, episodes: &'a [FnosPlayListItem]) -> Vec<EpisodeEntry<'a>> {
+    let mut entries: Vec<EpisodeEntry> = episodes.iter().map(EpisodeEntry::Local).collect();
+    if let Some(season) = season.filter(|s| s.number_of_episodes > episodes.len() as i32) {
+        entries.extend(
+            (1..=season.number_of_episodes)
+                .filter(|n| !episodes.iter().any(|ep| ep.episode_number == *n))
+                .map(|number| EpisodeEntry::Missing { season, number }),
+        );
+    }
+    entries
+}
+
+/// 将特别篇按首播日期插入正片之间（排在首播日期更晚的第一集之前），没有首播日期的排在最后
+fn place_specials<'a>(regular: Vec<EpisodeEntry<'a>>, specials: Vec<EpisodeEntry<'a>>) -> Vec<EpisodeEntry<'a>> {
+    let position = |special: &EpisodeEntry| -> usize {
+        let end = regular.len();
+        if special.air_date().is_empty() {
+            return end;
+        }
+        regular.iter()
+            .position(|e| !e.air_date().is_empty() && e.air_date() > special.air_date())
+            .unwrap_or(end)
+    };
+    let mut placed: Vec<(usize, EpisodeEntry)> = specials.into_iter().map(|s| (position(&s), s)).collect();
+    placed.sort_by_key(|(pos, _)| *pos);
+
+    let mut placed = placed.into_iter().peekable();
+    let mut ordered = Vec::new();
+    for (i, entry) in regular.into_iter().enumerate() {
+        while let Some((_, special)) = placed.next_if(|(pos, _)| *pos <= i) {
+            ordered.push(special);
+        }
+        ordered.push(entry);
+    }
+    ordered.extend(placed.map(|(_, special)| special));
+    ordered
+}
+
+/// 缺失集（LocationType=Virtual），Id 由季 GUID 与集号派生
+fn missing_episode_dto(season: &FnosPlayListItem, number: i32, series_id: &str, server_id: &str) -> BaseItemDto {
+    let season_name = if !season.title.is_empty() {
+        season.title.clone()
+    } else {
+        format!("第 {} 季", season.season_number)
+    };
+    BaseItemDto {
+        name: format!("第 {} 集", number),
+        server_id: server_id.to_string(),
+        id: derived_id(&format!("missing:{}:{}", season.guid, number)),
+        item_type: "Episode".into(),
+        location_type: Some("Virtual".into()),
+        index_number: Some(number),
+        parent_index_number: Some(season.season_number),
+        series_id: Some(series_id.to_string()),
+        series_name: Some(season.tv_title.clone()),
+        season_id: Some(to_jellyfin_id(&season.guid)),
+        season_name: Some(season_name),
+        parent_id: Some(to_jellyfin_id(&season.guid)),
+        ..Default::default()
+    }
+}
+
+/// GET /Shows/{seriesId}/Episodes
+/// 无 SeasonId / Season 时返回全部季的集，特别篇按首播日期插入正片之间（AdjustForSpecials=false 时按季号排列）；
+/// 按季请求时附带首播日期在该季播出区间内的特别篇。IsMissing=true 只返回缺失集
 async fn episodes(
     State(config): State<BridgeConfig>,
     Path(series_id): Path<String>,
@@ -122,79 +247,121 @@ async fn episodes(
     };
 
     let server_id = generate_server_id(&config.fnos_server);
+    let empty = || Json(ItemsResult { items: vec![], total_record_count: 0, start_index: 0 }).into_response();
+    let series_guid = to_fnos_guid(&series_id).unwrap_or_default();
+    let adjust_for_specials = query.adjust_for_specials != Some(false);
 
-    // 优先用 SeasonId 获取集列表
-    let season_guid = query
-        .season_id
-        .as_ref()
-        .and_then(|sid| to_fnos_guid(sid));
+    let seasons = if series_guid.is_empty() {
+        vec![]
+    } else {
+        let result = fnos_get_season_list(&session.fnos_server, &session.fnos_token, &series_guid, &config).await;
+        result.data.filter(|_| result.success).unwrap_or_default()
+    };
+    record_first_seen(seasons.iter().map(|s| s.guid.as_str()));
 
-    let season_guid = match season_guid {
-        Some(g) => g,
-        None => {
-            // 没有 SeasonId，用 series 的第一季
-            let series_guid = match to_fnos_guid(&series_id) {
-                Some(g) => g,
-                None => return Json(ItemsResult { items: vec![], total_record_count: 0, start_index: 0 }).into_response(),
-            };
-            let seasons_result = fnos_get_season_list(
-                &session.fnos_server,
-                &session.fnos_token,
-                &series_guid,
-                &config,
-            )
-            .await;
-            if !seasons_result.success || seasons_result.data.is_none() {
-                return Json(ItemsResult { items: vec![], total_record_count: 0, start_index: 0 }).into_response();
-            }
-            let seasons = seasons_result.data.unwrap();
-            let target_season = query.season.unwrap_or(1);
-            match seasons.iter().find(|s| s.season_number == target_season) {
-                Some(s) => s.guid.clone(),
-                None => match seasons.first() {
-                    Some(s) => s.guid.clone(),
-                    None => return Json(ItemsResult { items: vec![], total_record_count: 0, start_index: 0 }).into_response(),
-                },
-            }
+    // 要返回的季（guid, 季号）；按季请求时另取第 0 季以附带在该季播出的特别篇
+    let season_guid = query.season_id.as_deref().and_then(to_fnos_guid);
+    let targets: Vec<(String, Option<i32>)> = match (season_guid, query.season) {
+        (Some(guid), _) => {
+            let number = seasons.iter().find(|s| s.guid == guid).map(|s| s.season_number);
+            vec![(guid, number)]
+        }
+        (None, Some(number)) => seasons.iter()
+            .filter(|s| s.season_number == number)
+            .map(|s| (s.guid.clone(), Some(number)))
+            .collect(),
+        (None, None) => seasons.iter().map(|s| (s.guid.clone(), Some(s.season_number))).collect(),
+    };
+    if targets.is_empty() {
+        return empty();
+    }
+    let single_season = query.season_id.is_some() || query.season.is_some();
+    let special_season = match targets.first() {
+        Some((_, Some(number))) if single_season && adjust_for_specials && *number > 0 => {
+            let window = season_air_window(&seasons, *number);
+            seasons.iter().find(|s| s.season_number == 0).zip(window).map(|(s, window)| (s.guid.clone(), window))
         }
+        _ => None,
     };
 
-    let result = fnos_get_episode_list(
-        &session.fnos_server,
-        &session.fnos_token,
-        &season_guid,
-        &config,
-    )
-    .await;
+    // 各季的集列表并发请求
+    let guids: Vec<&String> = targets.iter().map(|(g, _)| g).chain(special_season.iter().map(|(g, _)| g)).collect();
+    let results = futures_util::future::join_all(
+        guids.iter().map(|guid| fnos_get_episode_list(&session.fnos_server, &session.fnos_token, guid, &config)),
+    ).await;
+    let mut fetched: Vec<(String, Vec<FnosPlayListItem>)> = Vec::new();
+    for (guid, result) in guids.into_iter().zip(results) {
+        match result.data {
+            Some(list) if result.success => {
+                record_first_seen(list.iter().map(|e| e.guid.as_str()));
+                fetched.push((guid.clone(), list));
+            }
+            _ => warn!("[SHOWS] 获取集列表失败: season={}", guid),
+        }
+    }
 
-    if !result.success || result.data.is_none() {
-        return Json(ItemsResult { items: vec![], total_record_count: 0, start_index: 0 }).into_response();
+    let mut regular: Vec<EpisodeEntry> = Vec::new();
+    let mut specials: Vec<EpisodeEntry> = Vec::new();
+    for (guid, list) in &fetched {
+        let season = seasons.iter().find(|s| &s.guid == guid);
+        let season_number = season.or(list.first()).map(|s| s.season_number);
+        let entries = season_entries(season, list);
+        match special_season.as_ref() {
+            // 按季请求时附带的第 0 季：只取首播日期在该季播出区间内的特别篇
+            Some((special_guid, (start, end))) if special_guid == guid => {
+                specials.extend(entries.into_iter().filter(|e| {
+                    let date = e.air_date();
+                    !date.is_empty() && date >= *start && end.is_none_or(|end| date < end)
+                }));
+            }
+            _ if season_number == Some(0) => specials.extend(entries),
+            _ => regular.extend(entries),
+        }
     }
+    regular.sort_by_key(|e| (e.season_number(), e.episode_number()));
+    specials.sort_by_key(|e| e.episode_number());
 
-    let episodes = result.data.unwrap();
-    let total = episodes.len() as i64;
-    
-    // 应用分页
-    let start = query.start_index.unwrap_or(0) as usize;
-    let limit = query.limit.unwrap_or(total as i64) as usize;
-    
-    let paged_episodes: Vec<_> = if start < episodes.len() {
-        episodes[start..episodes.len().min(start + limit)].to_vec()
+    let ordered = if adjust_for_specials {
+        place_specials(regular, specials)
     } else {
-        vec![]
+        specials.into_iter().chain(regular).collect()
     };
-    
-    let items: Vec<BaseItemDto> = paged_episodes
-        .iter()
-        .map(|ep| {
-            let mut dto = map_playlist_item_to_dto(ep, &server_id, &session.fnos_server, &session.fnos_token);
-            dto.series_id = Some(to_jellyfin_id(
-                &to_fnos_guid(&series_id).unwrap_or_default(),
-            ));
+
+    // IsMissing：true 只返回缺失集，否则只返回本地集
+    // IsVirtualUnaired=true：飞牛不提供缺失集的首播日期，没有可确认未播出的集，返回空；false 不过滤
+    if query.is_virtual_unaired == Some(true) {
+        return empty();
+    }
+    let want_missing = query.is_missing == Some(true);
+    let filtered = ordered.into_iter().filter(|e| e.is_missing() == want_missing);
+
+    let series_jf_id = to_jellyfin_id(&series_guid);
+    let dtos: Vec<BaseItemDto> = filtered
+        .map(|entry| {
+            let mut dto = match entry {
+                EpisodeEntry::Local(ep) => map_playlist_item_to_dto(ep, &server_id),
+                EpisodeEntry::Missing { season, number } => missing_episode_dto(season, number, &series_jf_id, &server_id),
+            };
+            dto.series_id = Some(series_jf_id.clone());
+            apply_parent_images(&mut dto);
             dto
         })
         .collect();
 
+    // StartItemId：从该集开始（之后再应用 StartIndex / Limit）
+    let dtos: Vec<BaseItemDto> = match query.start_item_id.as_deref().filter(|id| !id.is_empty()) {
+        Some(start_id) => {
+            let start_id = to_fnos_guid(start_id).map(|g| to_jellyfin_id(&g)).unwrap_or_else(|| start_id.to_string());
+            dtos.into_iter().skip_while(|dto| dto.id != start_id).collect()
+        }
+        None => dtos,
+    };
+
+    let total = dtos.len() as i64;
+    let start = query.start_index.unwrap_or(0).max(0) as usize;
+    let limit = query.limit.unwrap_or(total).max(0) as usize;
+    let items: Vec<BaseItemDto> = dtos.into_iter().skip(start).take(limit).collect();
+
     Json(ItemsResult { 
         items, 
         total_record_count: total, 
@@ -0,0 +1,161 @@
+/// Subtitles 路由 — 外挂字幕上传与删除（需要字幕管理权限，即飞牛管理员）
+
+use axum::{
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, post},
+    Extension, Json, Router,
+};
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, error};
+
+use crate::cache::stream_list::cached_get_stream_list;
+use crate::config::BridgeConfig;
+use crate::mappers::id::to_fnos_guid;
+use crate::mappers::media::local_subtitle_offset;
+use crate::middleware::auth::require_auth;
+use crate::services::local_subtitle::{add_subtitle, delete_subtitle, list_subtitles};
+use crate::services::session::SessionData;
+use crate::services::subtitle::SubtitleFormat;
+
+/// 上传字幕请求体上限（base64 后的 ASS 字幕可能有数 MB）
+const MAX_UPLOAD_BYTES: usize = 32 * 1024 * 1024;
+
+pub fn router() -> Router<BridgeConfig> {
+    Router::new()
+        .route(
+            "/Videos/{itemId}/Subtitles",
+            post(subtitle_upload)
+                .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES))
+                .layer(axum::middleware::from_fn(require_auth)),
+        )
+        .route(
+            "/Videos/{itemId}/Subtitles/{index}",
+            delete(subtitle_delete).layer(axum::middleware::from_fn(require_auth)),
+        )
+}
+
+/// UploadSubtitleDto
+#[derive(Deserialize)]
+struct UploadSubtitleBody {
+    #[serde(rename = "Language", default)]
+    language: String,
+    #[serde(rename = "Format", default)]
+    format: String,
+    #[serde(rename = "IsForced", default)]
+    is_forced: bool,
+    #[serde(rename = "IsHearingImpaired", default)]
+    is_hearing_impaired: bool,
+    /// base64 编码的字幕文件内容
+    #[serde(rename = "Data", default)]
+    data: String,
+}
+
+fn bad_request(message: &str) -> axum::response::Response {
+    (StatusCode::BAD_REQUEST, Json(json!({"error": message}))).into_response()
+}
+
+/// 可上传的文本字幕格式（也用作本地文件扩展名）
+fn upload_format(format: &str) -> Option<&'static str> {
+    match SubtitleFormat::from_name(format)? {
+        SubtitleFormat::Srt => Some("srt"),
+        SubtitleFormat::Ass => Some("ass"),
+        SubtitleFormat::Ssa => Some("ssa"),
+        SubtitleFormat::Vtt => Some("vtt"),
+        SubtitleFormat::Ttml | SubtitleFormat::Json => None,
+    }
+}
+
+/// 条目 ID → 飞牛 GUID，虚拟媒体库与播放列表不能挂字幕
+fn item_guid(item_id: &str) -> Option<String> {
+    to_fnos_guid(item_id).filter(|g| !g.starts_with("view_") && !g.starts_with("playlist_"))
+}
+
+/// POST /Videos/{itemId}/Subtitles
+async fn subtitle_upload(
+    State(config): State<BridgeConfig>,
+    Extension(session): Extension<SessionData>,
+    Path(item_id): Path<String>,
+    Json(body): Json<UploadSubtitleBody>,
+) -> axum::response::Response {
+    if !config.is_admin_user(&session.username) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Some(guid) = item_guid(&item_id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Some(format) = upload_format(&body.format) else {
+        return bad_request("Unsupported subtitle format");
+    };
+    if body.language.trim().is_empty() {
+        return bad_request("Language is required");
+    }
+    let data = match base64::engine::general_purpose::STANDARD.decode(body.data.trim()) {
+        Ok(data) if !data.is_empty() => data,
+        _ => return bad_request("Data must be non-empty base64"),
+    };
+
+    match add_subtitle(&guid, body.language.trim(), format, body.is_forced, body.is_hearing_impaired, &data) {
+        Ok(subtitle) => {
+            debug!("[SUBTITLE] 上传字幕: guid={}, id={}, format={}", guid, subtitle.id, format);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => {
+            error!("[SUBTITLE] 保存上传字幕失败: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct SubtitleDeleteQuery {
+    #[serde(rename = "MediaSourceId", alias = "mediaSourceId")]
+    media_source_id: Option<String>,
+}
+
+/// DELETE /Videos/{itemId}/Subtitles/{index}
+/// 只能删除桥接层上传的字幕，飞牛字幕返回 404
+/// 按条目的流列表还原本地字幕在 MediaSourceId 版本（未指定时为第一个版本）中的起始序号，从持久化的本地字幕中定位；
+/// 指定的版本不存在时返回 400
+async fn subtitle_delete(
+    State(config): State<BridgeConfig>,
+    Extension(session): Extension<SessionData>,
+    Path((item_id, index)): Path<(String, i32)>,
+    Query(query): Query<SubtitleDeleteQuery>,
+) -> StatusCode {
+    if !config.is_admin_user(&session.username) {
+        return StatusCode::FORBIDDEN;
+    }
+    let Some(guid) = item_guid(&item_id) else {
+        return StatusCode::NOT_FOUND;
+    };
+    let locals = list_subtitles(&guid);
+    if locals.is_empty() {
+        return StatusCode::NOT_FOUND;
+    }
+
+    let stream_list = cached_get_stream_list(&session.fnos_server, &session.fnos_token, &guid, &config).await;
+    let Some(sd) = stream_list.data.filter(|_| stream_list.success) else {
+        return StatusCode::NOT_FOUND;
+    };
+    let empty = vec![];
+    let Some(offset) = local_subtitle_offset(
+        sd["video_streams"].as_array().unwrap_or(&empty),
+        sd["audio_streams"].as_array().unwrap_or(&empty),
+        sd["subtitle_streams"].as_array().unwrap_or(&empty),
+        query.media_source_id.as_deref().filter(|id| !id.is_empty()),
+    ) else {
+        return StatusCode::BAD_REQUEST;
+    };
+    let local = usize::try_from(index - offset).ok().and_then(|i| locals.get(i));
+    match local {
+        Some(subtitle) if delete_subtitle(&subtitle.id) => {
+            debug!("[SUBTITLE] 删除字幕: guid={}, id={}", guid, subtitle.id);
+            StatusCode::NO_CONTENT
+        }
+        _ => StatusCode::NOT_FOUND,
+    }
+}
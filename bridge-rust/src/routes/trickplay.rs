@@ -0,0 +1,86 @@
+/// Trickplay 路由 — 拖动进度条缩略图
+
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Extension, Router,
+};
+use serde::Deserialize;
+
+use crate::config::BridgeConfig;
+use crate::mappers::id::to_fnos_guid;
+use crate::middleware::auth::optional_auth;
+use crate::services::session::SessionData;
+use crate::services::trickplay::{find_media_source, get_trickplay, sign_tile_tag, tile_path, tile_tag_authorizes, tiles_playlist};
+
+pub fn router() -> Router<BridgeConfig> {
+    Router::new().route(
+        "/Videos/{itemId}/Trickplay/{width}/{file}",
+        get(trickplay_file).layer(axum::middleware::from_fn(optional_auth)),
+    )
+}
+
+#[derive(Deserialize, Default)]
+struct TrickplayQuery {
+    #[serde(alias = "MediaSourceId", alias = "mediaSourceId")]
+    media_source_id: Option<String>,
+    tag: Option<String>,
+}
+
+/// GET /Videos/{itemId}/Trickplay/{width}/tiles.m3u8（需要会话）
+/// GET /Videos/{itemId}/Trickplay/{width}/{index}.jpg（会话，或播放列表签发的短期标签）
+async fn trickplay_file(
+    Path((item_id, width, file)): Path<(String, i32, String)>,
+    Query(query): Query<TrickplayQuery>,
+    session: Option<Extension<SessionData>>,
+) -> Response {
+    let media_source_id = query
+        .media_source_id
+        .filter(|id| !id.is_empty())
+        .or_else(|| to_fnos_guid(&item_id).and_then(|guid| find_media_source(&guid)));
+    let Some(media_source_id) = media_source_id else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    if file.eq_ignore_ascii_case("tiles.m3u8") {
+        if session.is_none() {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+        let info = get_trickplay(&media_source_id)
+            .and_then(|entry| entry.resolutions.get(&width.to_string()).cloned());
+        let Some(info) = info else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        return Response::builder()
+            .status(200)
+            .header("content-type", "application/x-mpegURL")
+            .header("access-control-allow-origin", "*")
+            .body(tiles_playlist(&info, &media_source_id, &sign_tile_tag(&media_source_id, width)).into())
+            .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response());
+    }
+
+    let authorized = session.is_some()
+        || query.tag.as_deref().is_some_and(|tag| tile_tag_authorizes(tag, &media_source_id, width));
+    if !authorized {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let index = file
+        .strip_suffix(".jpg")
+        .and_then(|i| i.parse::<usize>().ok());
+    let Some(path) = index.and_then(|i| tile_path(&media_source_id, width, i)) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => Response::builder()
+            .status(200)
+            .header("content-type", "image/jpeg")
+            .header("cache-control", "public, max-age=31536000")
+            .header("access-control-allow-origin", "*")
+            .body(bytes.into())
+            .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
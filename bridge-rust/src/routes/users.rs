@@ -1,7 +1,7 @@
 /// Users 路由 — 认证 + 用户信息
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     routing::{get, post},
     Json, Router,
 };
@@ -11,21 +11,25 @@ use tracing::{info, warn};
 
 use crate::config::BridgeConfig;
 use crate::mappers::id::{generate_server_id, to_jellyfin_id};
-use crate::mappers::user::map_user_to_jellyfin;
+use crate::mappers::user::{map_user_to_jellyfin, user_config};
 use crate::middleware::auth::require_auth;
 use crate::cache::user_info::cached_get_user_info;
 use crate::services::fnos::fnos_login;
-use crate::services::session::{create_session, SessionData};
+use crate::services::session::{create_session, list_known_users, set_session_profile, SessionData};
+use crate::services::user_config::set_user_config;
 use crate::types::fnos::FnosUserInfo;
 use crate::types::jellyfin::{
-    AuthenticationResult, PlayStateInfo, SessionInfoDto,
+    AuthenticationResult, PlayStateInfo, SessionInfoDto, UserConfiguration, UserDto,
 };
 
 pub fn router() -> Router<BridgeConfig> {
     Router::new()
         .route("/Users/AuthenticateByName", post(authenticate_by_name))
         .route("/Users/Public", get(users_public))
-        .route("/Users", get(users_list))
+        .route(
+            "/Users",
+            get(users_list).layer(axum::middleware::from_fn(require_auth)),
+        )
         .route(
             "/Users/Me",
             get(users_me).layer(axum::middleware::from_fn(require_auth)),
@@ -34,6 +38,23 @@ pub fn router() -> Router<BridgeConfig> {
             "/Users/{userId}",
             get(users_by_id).layer(axum::middleware::from_fn(require_auth)),
         )
+        // 用户配置
+        .route(
+            "/Users/Configuration",
+            post(update_configuration_query).layer(axum::middleware::from_fn(require_auth)),
+        )
+        .route(
+            "/Users/{userId}/Configuration",
+            get(get_configuration)
+                .post(update_configuration)
+                .layer(axum::middleware::from_fn(require_auth)),
+        )
+}
+
+#[derive(Deserialize, Default)]
+struct UserIdQuery {
+    #[serde(alias = "UserId", alias = "userId")]
+    user_id: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -120,8 +141,9 @@ async fn authenticate_by_name(
             user_info = data;
         }
     }
+    set_session_profile(&access_token, &user_info);
 
-    let user_dto = map_user_to_jellyfin(&user_info, &user_id, &server_id);
+    let user_dto = map_user_to_jellyfin(&user_info, &user_id, &server_id, config.is_admin_user(&body.username));
     let now = chrono::Utc::now().to_rfc3339();
 
     let session_info = SessionInfoDto {
@@ -155,12 +177,35 @@ async fn authenticate_by_name(
     Json(result).into_response()
 }
 
-async fn users_public() -> Json<Vec<()>> {
-    Json(vec![])
+/// 近期登录过的用户 → UserDto 列表
+fn known_user_dtos(server_id: &str, config: &BridgeConfig) -> Vec<UserDto> {
+    list_known_users()
+        .into_iter()
+        .map(|u| {
+            let info = FnosUserInfo {
+                username: u.username.clone(),
+                nickname: u.nickname.clone(),
+                avatar: u.avatar.clone(),
+                ..Default::default()
+            };
+            map_user_to_jellyfin(&info, &u.user_id, server_id, config.is_admin_user(&u.username))
+        })
+        .collect()
 }
 
-async fn users_list() -> Json<Vec<()>> {
-    Json(vec![])
+/// GET /Users/Public - 登录页用户选择（需开启 PUBLIC_USER_LIST）
+async fn users_public(State(config): State<BridgeConfig>) -> Json<Vec<UserDto>> {
+    if !config.public_user_list {
+        return Json(vec![]);
+    }
+    let server_id = generate_server_id(&config.fnos_server);
+    Json(known_user_dtos(&server_id, &config))
+}
+
+/// GET /Users - 近期登录过的用户
+async fn users_list(State(config): State<BridgeConfig>) -> Json<Vec<UserDto>> {
+    let server_id = generate_server_id(&config.fnos_server);
+    Json(known_user_dtos(&server_id, &config))
 }
 
 async fn users_me(
@@ -188,10 +233,11 @@ async fn users_me(
     if result.success {
         if let Some(data) = result.data {
             user_info = data;
+            set_session_profile(&session.access_token, &user_info);
         }
     }
 
-    let user_dto = map_user_to_jellyfin(&user_info, &session.user_id, &server_id);
+    let user_dto = map_user_to_jellyfin(&user_info, &session.user_id, &server_id, config.is_admin_user(&session.username));
     Json(user_dto).into_response()
 }
 
@@ -221,9 +267,77 @@ async fn users_by_id(
     if result.success {
         if let Some(data) = result.data {
             user_info = data;
+            set_session_profile(&session.access_token, &user_info);
         }
     }
 
-    let user_dto = map_user_to_jellyfin(&user_info, &session.user_id, &server_id);
+    let user_dto = map_user_to_jellyfin(&user_info, &session.user_id, &server_id, config.is_admin_user(&session.username));
     Json(user_dto).into_response()
 }
+
+/// GET /Users/{userId}/Configuration
+async fn get_configuration(
+    Path(user_id): Path<String>,
+    axum::Extension(session): axum::Extension<SessionData>,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use axum::http::StatusCode;
+
+    if !user_id.eq_ignore_ascii_case(&session.user_id) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    Json(user_config(&session.user_id)).into_response()
+}
+
+/// POST /Users/{userId}/Configuration
+async fn update_configuration(
+    Path(user_id): Path<String>,
+    axum::Extension(session): axum::Extension<SessionData>,
+    Json(body): Json<serde_json::Value>,
+) -> axum::http::StatusCode {
+    save_configuration(&session, &user_id, body)
+}
+
+/// POST /Users/Configuration?userId=
+async fn update_configuration_query(
+    Query(query): Query<UserIdQuery>,
+    axum::Extension(session): axum::Extension<SessionData>,
+    Json(body): Json<serde_json::Value>,
+) -> axum::http::StatusCode {
+    let user_id = query.user_id.unwrap_or_else(|| session.user_id.clone());
+    save_configuration(&session, &user_id, body)
+}
+
+/// 只允许修改自己的配置；请求体按字段合并到现有配置，缺省字段保持不变
+fn save_configuration(session: &SessionData, user_id: &str, body: serde_json::Value) -> axum::http::StatusCode {
+    use axum::http::StatusCode;
+
+    if !user_id.is_empty() && !user_id.eq_ignore_ascii_case(&session.user_id) {
+        warn!("[USER-CONFIG] 拒绝修改其他用户配置: session={}, target={}", session.user_id, user_id);
+        return StatusCode::FORBIDDEN;
+    }
+
+    let mut merged = serde_json::to_value(user_config(&session.user_id)).unwrap_or_default();
+    if let (Some(target), Some(patch)) = (merged.as_object_mut(), body.as_object()) {
+        for (k, v) in patch {
+            if !v.is_null() {
+                target.insert(k.clone(), v.clone());
+            }
+        }
+    }
+
+    match serde_json::from_value::<UserConfiguration>(merged) {
+        Ok(cfg) => {
+            info!(
+                "[USER-CONFIG] 保存用户配置: user={}, audio={:?}, subtitle={:?}, mode={}",
+                session.user_id, cfg.audio_language_preference, cfg.subtitle_language_preference, cfg.subtitle_mode
+            );
+            set_user_config(&session.user_id, cfg);
+            StatusCode::NO_CONTENT
+        }
+        Err(e) => {
+            warn!("[USER-CONFIG] 用户配置格式错误: {}", e);
+            StatusCode::BAD_REQUEST
+        }
+    }
+}
@@ -18,22 +18,22 @@ pub fn router() -> Router<BridgeConfig> {
 async fn user_views(State(config): State<BridgeConfig>) -> Json<ItemsResult> {
     let server_id = generate_server_id(&config.fnos_server);
 
-    let movies = make_collection_folder(
-        "电影",
-        &to_jellyfin_id("view_movies"),
+    let mut items = vec![
+        make_collection_folder("电影", &to_jellyfin_id("view_movies"), &server_id, "movies"),
+        make_collection_folder("电视剧", &to_jellyfin_id("view_tvshows"), &server_id, "tvshows"),
+    ];
+    // 播放列表由桥接层保存
+    items.push(make_collection_folder(
+        "播放列表",
+        &to_jellyfin_id("view_playlists"),
         &server_id,
-        "movies",
-    );
-    let tvshows = make_collection_folder(
-        "电视剧",
-        &to_jellyfin_id("view_tvshows"),
-        &server_id,
-        "tvshows",
-    );
+        "playlists",
+    ));
 
+    let total = items.len() as i64;
     Json(ItemsResult {
-        items: vec![movies, tvshows],
-        total_record_count: 2,
+        items,
+        total_record_count: total,
         start_index: 0,
     })
 }
@@ -0,0 +1,324 @@
+/// 章节
+/// 飞牛接口不返回章节，章节时间由 ffprobe 经桥接层自身的直链代理读取容器章节，
+/// 结果与截取的章节图片一起按飞牛 GUID 缓存在磁盘（.chapters/{guid}/chapters.json、{index}.jpg）
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::LazyLock;
+use std::time::UNIX_EPOCH;
+use tokio::sync::Semaphore;
+use tracing::{debug, info, warn};
+
+use crate::config::BridgeConfig;
+use crate::mappers::item::seconds_to_ticks;
+use crate::services::trickplay::{acquire_ffmpeg_slot, ffmpeg_available, run_ffmpeg, stream_input_url, FfmpegTicket};
+use crate::types::jellyfin::ChapterInfoDto;
+
+/// 章节图片宽度
+const CHAPTER_IMAGE_WIDTH: i32 = 480;
+
+/// 容器章节
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chapter {
+    pub start_seconds: f64,
+    pub end_seconds: Option<f64>,
+    pub name: String,
+}
+
+/// 磁盘上的章节索引
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ChapterEntry {
+    /// 读取章节的版本
+    media_source_id: String,
+    chapters: Vec<Chapter>,
+}
+
+/// 飞牛 GUID → 已读取的章节（没有章节的条目记为空列表）
+static CHAPTER_INDEX: LazyLock<DashMap<String, ChapterEntry>> = LazyLock::new(|| {
+    let map = DashMap::new();
+    if let Ok(dirs) = std::fs::read_dir(chapter_root()) {
+        for dir in dirs.flatten() {
+            let Ok(content) = std::fs::read_to_string(dir.path().join("chapters.json")) else {
+                continue;
+            };
+            if let Ok(entry) = serde_json::from_str::<ChapterEntry>(&content) {
+                map.insert(dir.file_name().to_string_lossy().to_string(), entry);
+            }
+        }
+        info!("[CHAPTER] 已恢复 {} 个条目的章节", map.len());
+    }
+    map
+});
+
+/// 正在读取章节或已失败的条目（本次运行内不重复调度）
+static PROBE_JOBS: LazyLock<DashMap<String, ()>> = LazyLock::new(DashMap::new);
+
+/// 正在截图或已失败的条目（本次运行内不重复调度）
+static CHAPTER_JOBS: LazyLock<DashMap<String, ()>> = LazyLock::new(DashMap::new);
+
+/// ffprobe 只读取容器头部，与 ffmpeg 任务分开限流
+static PROBE_PERMITS: LazyLock<Semaphore> = LazyLock::new(|| Semaphore::new(2));
+
+/// ffprobe 不可用时不再调度任务
+static FFPROBE_MISSING: AtomicBool = AtomicBool::new(false);
+
+fn chapter_root() -> PathBuf {
+    PathBuf::from(".chapters")
+}
+
+fn is_safe_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 章节图片路径（不检查是否存在）
+pub fn chapter_image_path(item_guid: &str, index: usize) -> Option<PathBuf> {
+    is_safe_id(item_guid).then(|| chapter_root().join(item_guid).join(format!("{}.jpg", index)))
+}
+
+/// 已读取的章节；尚未读取或读取的不是该版本时为 None
+pub fn get_chapters(item_guid: &str, media_source_id: &str) -> Option<Vec<Chapter>> {
+    CHAPTER_INDEX
+        .get(item_guid)
+        .filter(|e| e.media_source_id == media_source_id)
+        .map(|e| e.chapters.clone())
+}
+
+/// 解析 `ffprobe -show_chapters -of json` 的输出
+/// start_time / end_time 是秒数字符串，章节名在 tags.title
+fn parse_ffprobe_chapters(output: &serde_json::Value) -> Vec<Chapter> {
+    let Some(list) = output["chapters"].as_array() else {
+        return vec![];
+    };
+    let seconds = |v: &serde_json::Value| v.as_str().and_then(|s| s.trim().parse::<f64>().ok());
+    let mut chapters: Vec<Chapter> = list
+        .iter()
+        .filter_map(|c| {
+            let start_seconds = seconds(&c["start_time"])?;
+            Some(Chapter {
+                start_seconds: start_seconds.max(0.0),
+                end_seconds: seconds(&c["end_time"]),
+                name: c["tags"]["title"].as_str().unwrap_or("").trim().to_string(),
+            })
+        })
+        .collect();
+    chapters.sort_by(|a, b| a.start_seconds.total_cmp(&b.start_seconds));
+    for (i, chapter) in chapters.iter_mut().enumerate() {
+        if chapter.name.is_empty() {
+            chapter.name = format!("章节 {}", i + 1);
+        }
+    }
+    chapters
+}
+
+/// 尚未读取章节时在后台用 ffprobe 读取
+/// `access_token` 用于 ffprobe 访问桥接层自身的直链代理
+pub fn schedule_chapter_probe(
+    config: &BridgeConfig,
+    access_token: &str,
+    item_id: &str,
+    item_guid: &str,
+    media_source_id: &str,
+) {
+    if FFPROBE_MISSING.load(Ordering::Relaxed)
+        || access_token.is_empty()
+        || !is_safe_id(item_guid)
+        || get_chapters(item_guid, media_source_id).is_some()
+    {
+        return;
+    }
+    match PROBE_JOBS.entry(item_guid.to_string()) {
+        dashmap::Entry::Occupied(_) => return,
+        dashmap::Entry::Vacant(slot) => {
+            slot.insert(());
+        }
+    }
+
+    let input_url = stream_input_url(config, item_id, media_source_id);
+    let access_token = access_token.to_string();
+    let ffprobe = config.ffprobe_path.clone();
+    let item_guid = item_guid.to_string();
+    let media_source_id = media_source_id.to_string();
+
+    tokio::spawn(async move {
+        let Ok(_permit) = PROBE_PERMITS.acquire().await else {
+            return;
+        };
+        let ticket = FfmpegTicket::issue(&access_token, &media_source_id);
+        let Some(output) = run_ffprobe(&ffprobe, &input_url, &ticket.header(), &item_guid).await else {
+            // 失败的条目保留在 PROBE_JOBS 中，本次运行不再重试
+            return;
+        };
+        let entry = ChapterEntry { media_source_id, chapters: parse_ffprobe_chapters(&output) };
+        debug!("[CHAPTER] 章节已读取: guid={}, count={}", item_guid, entry.chapters.len());
+        let dir = chapter_root().join(&item_guid);
+        let saved = std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::write(dir.join("chapters.json"), serde_json::to_string_pretty(&entry)?));
+        if let Err(e) = saved {
+            warn!("[CHAPTER] 保存章节失败: {}", e);
+        }
+        CHAPTER_INDEX.insert(item_guid.clone(), entry);
+        PROBE_JOBS.remove(&item_guid);
+    });
+}
+
+/// 运行 ffprobe 读取章节，返回解析后的 JSON；找不到可执行文件时禁用后续任务
+async fn run_ffprobe(ffprobe: &str, input_url: &str, auth_header: &str, context: &str) -> Option<serde_json::Value> {
+    let output = tokio::process::Command::new(ffprobe)
+        .args([
+            "-hide_banner", "-loglevel", "error", "-headers", auth_header,
+            "-show_chapters", "-of", "json", "-i", input_url,
+        ])
+        .kill_on_drop(true)
+        .output()
+        .await;
+    match output {
+        Ok(o) if o.status.success() => serde_json::from_slice(&o.stdout).ok(),
+        Ok(o) => {
+            warn!("[FFPROBE] 执行失败: {}, {}", context, String::from_utf8_lossy(&o.stderr).trim());
+            None
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            warn!("[FFPROBE] 未找到 ffprobe（{}），章节读取已禁用", ffprobe);
+            FFPROBE_MISSING.store(true, Ordering::Relaxed);
+            None
+        }
+        Err(e) => {
+            warn!("[FFPROBE] 启动失败: {}", e);
+            None
+        }
+    }
+}
+
+/// 章节 → ChapterInfo，已截图的附带 ImageTag
+pub fn map_chapters(item_guid: &str, chapters: &[Chapter]) -> Vec<ChapterInfoDto> {
+    chapters
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| {
+            let modified = chapter_image_path(item_guid, i)
+                .and_then(|p| std::fs::metadata(p).ok())
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok());
+            ChapterInfoDto {
+                start_position_ticks: seconds_to_ticks(chapter.start_seconds),
+                name: chapter.name.clone(),
+                image_tag: modified.map(|d| format!("{:x}", d.as_secs())),
+                image_date_modified: modified
+                    .and_then(|d| chrono::DateTime::from_timestamp(d.as_secs() as i64, 0))
+                    .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+                    .unwrap_or_else(|| "0001-01-01T00:00:00Z".to_string()),
+            }
+        })
+        .collect()
+}
+
+/// 缺少章节图片时在后台截图
+/// `access_token` 用于 ffmpeg 访问桥接层自身的直链代理
+pub fn schedule_chapter_images(
+    config: &BridgeConfig,
+    access_token: &str,
+    item_id: &str,
+    item_guid: &str,
+    media_source_id: &str,
+    chapters: &[Chapter],
+) {
+    if chapters.is_empty()
+        || !ffmpeg_available()
+        || !config.enable_trickplay
+        || access_token.is_empty()
+        || !is_safe_id(item_guid)
+    {
+        return;
+    }
+    let missing: Vec<(usize, f64)> = chapters
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| chapter_image_path(item_guid, *i).is_some_and(|p| !p.is_file()))
+        .map(|(i, c)| {
+            // 章节开头常是黑场，往后取一点（不超过章节中点）
+            let offset = c.end_seconds.map(|end| ((end - c.start_seconds) / 2.0).min(5.0)).unwrap_or(5.0);
+            (i, c.start_seconds + offset.max(0.0))
+        })
+        .collect();
+    if missing.is_empty() {
+        return;
+    }
+    match CHAPTER_JOBS.entry(item_guid.to_string()) {
+        dashmap::Entry::Occupied(_) => return,
+        dashmap::Entry::Vacant(slot) => {
+            slot.insert(());
+        }
+    }
+
+    let input_url = stream_input_url(config, item_id, media_source_id);
+    let access_token = access_token.to_string();
+    let ffmpeg = config.ffmpeg_path.clone();
+    let dir = chapter_root().join(item_guid);
+    let item_guid = item_guid.to_string();
+    let media_source_id = media_source_id.to_string();
+
+    tokio::spawn(async move {
+        let Some(_permit) = acquire_ffmpeg_slot().await else {
+            return;
+        };
+        let ticket = FfmpegTicket::issue(&access_token, &media_source_id);
+        let auth_header = ticket.header();
+        if std::fs::create_dir_all(&dir).is_err() {
+            return;
+        }
+        debug!("[CHAPTER] 开始截取章节图片: guid={}, count={}", item_guid, missing.len());
+        let scale = format!("scale={}:-2", CHAPTER_IMAGE_WIDTH);
+        let mut done = 0;
+        for (index, seconds) in &missing {
+            let target = dir.join(format!("{}.jpg", index));
+            let position = format!("{:.3}", seconds);
+            let args: Vec<std::ffi::OsString> = [
+                "-hide_banner", "-loglevel", "error", "-nostdin", "-threads", "1",
+                "-ss", &position, "-headers", &auth_header, "-i", &input_url,
+                "-an", "-sn", "-dn", "-frames:v", "1", "-vf", &scale, "-q:v", "4", "-y",
+            ]
+            .iter()
+            .map(Into::into)
+            .chain(std::iter::once(target.into_os_string()))
+            .collect();
+            if !run_ffmpeg(&ffmpeg, &args, &item_guid).await {
+                // 失败的条目保留在 CHAPTER_JOBS 中，本次运行不再重试
+                return;
+            }
+            done += 1;
+        }
+        info!("[CHAPTER] 章节图片完成: guid={}, count={}", item_guid, done);
+        CHAPTER_JOBS.remove(&item_guid);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ffprobe_chapters_in_start_order() {
+        let output = serde_json::json!({
+            "chapters": [
+                { "id": 1, "time_base": "1/1000", "start": 90000, "start_time": "90.000000",
+                  "end": 180000, "end_time": "180.000000", "tags": { "title": "Part 2" } },
+                { "id": 0, "time_base": "1/1000", "start": 0, "start_time": "0.000000",
+                  "end": 90000, "end_time": "90.000000", "tags": {} }
+            ]
+        });
+        let chapters = parse_ffprobe_chapters(&output);
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].start_seconds, 0.0);
+        assert_eq!(chapters[0].name, "章节 1");
+        assert_eq!(chapters[1].name, "Part 2");
+        assert_eq!(chapters[1].end_seconds, Some(180.0));
+    }
+
+    #[test]
+    fn no_chapters_when_output_has_none() {
+        assert!(parse_ffprobe_chapters(&serde_json::json!({})).is_empty());
+        assert!(parse_ffprobe_chapters(&serde_json::json!({ "chapters": [] })).is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+/// 客户端 DeviceProfile 解析
+/// PlaybackInfo 请求携带的 DeviceProfile 中，CodecProfiles 的 VideoRangeType 条件声明了
+/// 客户端对各视频编码可显示的动态范围（jellyfin-web 例：h264 → "SDR"，hevc → "SDR|HDR10|HLG"）
+
+use serde_json::Value;
+
+/// 客户端是否能显示该编码、该动态范围类型的视频
+/// 没有针对 VideoRangeType 的条件时视为支持
+pub fn supports_video_range_type(profile: &Value, codec: &str, range_type: &str) -> bool {
+    let Some(codec_profiles) = profile["CodecProfiles"].as_array() else {
+        return true;
+    };
+    codec_profiles
+        .iter()
+        .filter(|p| p["Type"].as_str().is_none_or(|t| t == "Video"))
+        .filter(|p| {
+            p["Codec"].as_str().is_none_or(|codecs| {
+                codecs.is_empty() || codecs.split(',').any(|c| c.trim().eq_ignore_ascii_case(codec))
+            })
+        })
+        .flat_map(|p| p["Conditions"].as_array().into_iter().flatten())
+        .filter(|c| c["Property"].as_str() == Some("VideoRangeType"))
+        .all(|c| condition_matches(c, range_type))
+}
+
+fn condition_matches(condition: &Value, value: &str) -> bool {
+    let expected = condition["Value"].as_str().unwrap_or("");
+    match condition["Condition"].as_str().unwrap_or("") {
+        "EqualsAny" => expected.split('|').any(|v| v.eq_ignore_ascii_case(value)),
+        "Equals" => expected.eq_ignore_ascii_case(value),
+        "NotEquals" => !expected.eq_ignore_ascii_case(value),
+        _ => true,
+    }
+}
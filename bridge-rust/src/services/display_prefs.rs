@@ -0,0 +1,107 @@
+/// DisplayPreferences 持久化
+/// 按 (用户, 客户端, 偏好 ID) 保存排序、视图样式和首页分区设置
+
+use dashmap::DashMap;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::{info, warn};
+
+use crate::types::jellyfin::DisplayPreferencesDto;
+
+/// jellyfin-web 首页分区默认顺序（homesection0 ~ homesection9）
+const DEFAULT_HOME_SECTIONS: &[&str] = &[
+    "smalllibrarytiles",
+    "resume",
+    "resumeaudio",
+    "resumebook",
+    "livetv",
+    "nextup",
+    "latestmedia",
+    "none",
+    "none",
+    "none",
+];
+
+/// "{userId}:{client}:{id}" → DisplayPreferences
+static PREFS: LazyLock<DashMap<String, DisplayPreferencesDto>> = LazyLock::new(|| {
+    let map = DashMap::new();
+    if let Some(data) = load_prefs_from_file() {
+        for (k, v) in data {
+            map.insert(k, v);
+        }
+        info!("[PREFS] 已恢复 {} 条显示偏好", map.len());
+    }
+    map
+});
+
+fn prefs_file_path() -> PathBuf {
+    PathBuf::from(".display_prefs.json")
+}
+
+fn load_prefs_from_file() -> Option<std::collections::HashMap<String, DisplayPreferencesDto>> {
+    let content = std::fs::read_to_string(prefs_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_prefs() {
+    let mut data = std::collections::HashMap::new();
+    for entry in PREFS.iter() {
+        data.insert(entry.key().clone(), entry.value().clone());
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&data) {
+        if let Err(e) = std::fs::write(prefs_file_path(), json) {
+            warn!("[PREFS] 保存显示偏好失败: {}", e);
+        }
+    }
+}
+
+fn make_key(user_id: &str, client: &str, id: &str) -> String {
+    format!("{}:{}:{}", user_id.to_lowercase(), client, id)
+}
+
+/// 默认显示偏好（与 Jellyfin 服务端新建时一致）
+fn default_prefs(id: &str, client: &str) -> DisplayPreferencesDto {
+    let mut custom_prefs = std::collections::BTreeMap::new();
+    for (i, section) in DEFAULT_HOME_SECTIONS.iter().enumerate() {
+        custom_prefs.insert(format!("homesection{}", i), Some(section.to_string()));
+    }
+    DisplayPreferencesDto {
+        id: id.to_string(),
+        view_type: None,
+        sort_by: "SortName".into(),
+        index_by: None,
+        remember_indexing: false,
+        primary_image_height: 250,
+        primary_image_width: 250,
+        custom_prefs,
+        scroll_direction: "Horizontal".into(),
+        show_backdrop: true,
+        remember_sorting: false,
+        sort_order: "Ascending".into(),
+        show_sidebar: false,
+        client: Some(client.to_string()),
+    }
+}
+
+/// 获取显示偏好，未保存过则返回默认值
+pub fn get_display_prefs(user_id: &str, client: &str, id: &str) -> DisplayPreferencesDto {
+    match PREFS.get(&make_key(user_id, client, id)) {
+        Some(v) => v.value().clone(),
+        None => default_prefs(id, client),
+    }
+}
+
+/// 保存显示偏好
+pub fn set_display_prefs(user_id: &str, client: &str, id: &str, mut prefs: DisplayPreferencesDto) {
+    prefs.id = id.to_string();
+    prefs.client = Some(client.to_string());
+    // 客户端未提交的首页分区保留默认值，避免首页被清空
+    for (i, section) in DEFAULT_HOME_SECTIONS.iter().enumerate() {
+        prefs
+            .custom_prefs
+            .entry(format!("homesection{}", i))
+            .or_insert_with(|| Some(section.to_string()));
+    }
+    PREFS.insert(make_key(user_id, client, id), prefs);
+    save_prefs();
+}
@@ -0,0 +1,164 @@
+/// 汉字处理
+/// 繁体转简体（zhconv，OpenCC 词库，按词转换）、拼音 / 拼音首字母（pinyin，Unicode 汉字读音数据），
+/// 以及按拼音生成的 SortName
+
+use pinyin::ToPinyin;
+use std::sync::OnceLock;
+use zhconv::{zhconv, Variant};
+
+/// SortName 排序规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortCollation {
+    /// 汉字转为拼音（按拼音字母排序，字母跳转栏可用）
+    #[default]
+    Pinyin,
+    /// 保留原文（按 Unicode 码位排序）
+    Unicode,
+}
+
+impl SortCollation {
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "unicode" | "none" => Self::Unicode,
+            _ => Self::Pinyin,
+        }
+    }
+}
+
+static COLLATION: OnceLock<SortCollation> = OnceLock::new();
+
+/// 启动时设置排序规则（未设置时按拼音）
+pub fn set_sort_collation(collation: SortCollation) {
+    let _ = COLLATION.set(collation);
+}
+
+/// SortName 中数字补齐的位数（使 "第2集" 排在 "第10集" 之前）
+const SORT_NUMBER_WIDTH: usize = 6;
+
+/// SortName 忽略的开头冠词
+const SORT_REMOVE_WORDS: &[&str] = &["the ", "a ", "an "];
+
+/// 是否为 CJK 统一表意文字
+pub fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF)
+}
+
+/// 归一化：全角转半角、繁转简、小写，标点与空白合并为单个空格
+pub fn normalize(text: &str) -> String {
+    let text = zhconv(text, Variant::ZhHans);
+    let mut out = String::with_capacity(text.len());
+    let mut pending_space = false;
+    for c in text.chars() {
+        let c = match c as u32 {
+            0xFF01..=0xFF5E => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            0x3000 => ' ',
+            _ => c,
+        };
+        if c.is_alphanumeric() || is_cjk(c) {
+            if pending_space && !out.is_empty() {
+                out.push(' ');
+            }
+            pending_space = false;
+            out.extend(c.to_lowercase());
+        } else {
+            pending_space = true;
+        }
+    }
+    out
+}
+
+/// pinyin 默认读音不适合影视标题的多音字（长安、长歌行 → chang；似水年华 → si）
+const READING_OVERRIDES: &[(char, &str)] = &[('长', "chang"), ('長', "chang"), ('似', "si")];
+
+/// 单个汉字的拼音（不带声调；多音字取最常用的读音，非汉字返回 None）
+pub fn pinyin(c: char) -> Option<&'static str> {
+    READING_OVERRIDES
+        .iter()
+        .find(|(h, _)| *h == c)
+        .map(|(_, p)| *p)
+        .or_else(|| c.to_pinyin().map(|p| p.plain()))
+}
+
+/// 拼音首字母串（输入应已归一化）：汉字取首字母，字母数字原样保留，其余忽略
+pub fn pinyin_initials(normalized: &str) -> String {
+    normalized
+        .chars()
+        .filter_map(|c| {
+            if c.is_ascii_alphanumeric() {
+                Some(c)
+            } else if is_cjk(c) {
+                pinyin(c).and_then(|p| p.chars().next())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// 由标题生成 SortName：归一化、去掉开头冠词、数字补零，汉字按排序规则转为拼音
+/// 例：「长安十二时辰」→ "chang an shi er shi chen"
+pub fn sort_name(title: &str) -> String {
+    let mut text = normalize(title);
+    if let Some(rest) = SORT_REMOVE_WORDS.iter().find_map(|w| text.strip_prefix(w)) {
+        text = rest.to_string();
+    }
+    let collation = COLLATION.get().copied().unwrap_or_default();
+
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() {
+            let mut number = String::from(c);
+            while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                number.push(d);
+            }
+            out.push_str(&format!("{:0>width$}", number, width = SORT_NUMBER_WIDTH));
+            continue;
+        }
+        match pinyin(c).filter(|_| collation == SortCollation::Pinyin) {
+            // 汉字音节之间用空格分隔
+            Some(syllable) => {
+                if !out.is_empty() && !out.ends_with(' ') {
+                    out.push(' ');
+                }
+                out.push_str(syllable);
+                if chars.peek().is_some_and(|n| *n != ' ') {
+                    out.push(' ');
+                }
+            }
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_folds_width_case_punctuation_and_traditional() {
+        assert_eq!(normalize("ＡＢＣ　１２３"), "abc 123");
+        assert_eq!(normalize("  The Office: (US)!! "), "the office us");
+        assert_eq!(normalize("長安十二時辰"), "长安十二时辰");
+        // 按词转换：傢俱 → 家具，乾隆 不转为 干隆
+        assert_eq!(normalize("傢俱"), "家具");
+        assert_eq!(normalize("乾隆王朝"), "乾隆王朝");
+    }
+
+    #[test]
+    fn pinyin_initials_keep_ascii_and_drop_punctuation() {
+        assert_eq!(pinyin_initials(&normalize("长安十二时辰")), "casesc");
+        assert_eq!(pinyin_initials(&normalize("繁花 2023")), "fh2023");
+        assert_eq!(pinyin_initials(&normalize("漫长的季节")), "mcdjj");
+    }
+
+    #[test]
+    fn sort_name_uses_pinyin_pads_numbers_and_drops_articles() {
+        assert_eq!(sort_name("长安十二时辰"), "chang an shi er shi chen");
+        assert_eq!(sort_name("長安十二時辰"), "chang an shi er shi chen");
+        assert_eq!(sort_name("The Wire"), "wire");
+        assert!(sort_name("第2集") < sort_name("第10集"));
+        assert_eq!(sort_name("Top Gun 2"), "top gun 000002");
+    }
+}
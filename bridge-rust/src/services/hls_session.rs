@@ -3,6 +3,7 @@
 use dashmap::DashMap;
 use regex::Regex;
 use serde_json::json;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::LazyLock;
 use tracing::{debug, error, info};
 
@@ -23,6 +24,8 @@ pub struct StreamMeta {
     pub subtitle_guid: String,
     pub channels: i32,
     pub duration: f64,
+    /// HDR / 杜比视界转码时映射为 SDR（客户端不支持片源的动态范围）
+    pub forced_sdr: bool,
 }
 
 /// HLS 会话信息
@@ -39,9 +42,22 @@ struct HlsSession {
 /// mediaGuid → 流元数据
 static STREAM_META_MAP: LazyLock<DashMap<String, StreamMeta>> = LazyLock::new(DashMap::new);
 
-/// mediaGuid → HLS 会话
+/// 会话键（见 `hls_session_key`）→ HLS 会话
 static HLS_SESSION_MAP: LazyLock<DashMap<String, HlsSession>> = LazyLock::new(DashMap::new);
 
+/// 客户端（access token）→ 当前一次性转码会话
+/// 每个客户端同时只保留一个：新的跳转会替换旧会话，旧的渐进式流随即停止拉取分片，
+/// 飞牛没有停止转码的接口，停止拉取后上游会话空闲回收
+static ONE_OFF_SESSIONS: LazyLock<DashMap<String, OneOffSession>> = LazyLock::new(DashMap::new);
+
+static NEXT_ONE_OFF_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Clone)]
+struct OneOffSession {
+    id: u64,
+    session_guid: String,
+}
+
 /// 注册流元数据
 pub fn register_stream_meta(media_guid: &str, meta: StreamMeta) {
     STREAM_META_MAP.insert(media_guid.to_string(), meta);
@@ -52,10 +68,32 @@ pub fn get_stream_meta(media_guid: &str) -> Option<StreamMeta> {
     STREAM_META_MAP.get(media_guid).map(|v| v.value().clone())
 }
 
-/// 获取 HLS 会话的 play_link
+/// 映射为 SDR 的转码会话键后缀
+const SDR_KEY_SUFFIX: &str = "_sdr";
+
+/// HLS 会话键：同一版本映射为 SDR 与保持原动态范围的转码分别缓存，
+/// 不同客户端按各自能力播放时不会互相替换会话。会话键即 TranscodingUrl 中的路径段
+pub fn hls_session_key(media_guid: &str, forced_sdr: bool) -> String {
+    if forced_sdr {
+        format!("{}{}", media_guid, SDR_KEY_SUFFIX)
+    } else {
+        media_guid.to_string()
+    }
+}
+
+/// 解析会话键，返回 (mediaGuid, forced_sdr)
+fn parse_hls_session_key(key: &str) -> (&str, bool) {
+    match key.strip_suffix(SDR_KEY_SUFFIX) {
+        Some(media_guid) => (media_guid, true),
+        None => (key, false),
+    }
+}
+
+/// 获取 HLS 会话的 play_link（优先原动态范围的会话）
 pub fn get_hls_play_link(media_guid: &str) -> String {
     HLS_SESSION_MAP
         .get(media_guid)
+        .or_else(|| HLS_SESSION_MAP.get(&hls_session_key(media_guid, true)))
         .map(|v| v.play_link.clone())
         .unwrap_or_default()
 }
@@ -67,26 +105,93 @@ fn now_millis() -> i64 {
         .as_millis() as i64
 }
 
-/// 获取或创建 HLS 转码会话
+/// 获取或创建 HLS 转码会话，`session_key` 由 `hls_session_key` 生成
 pub async fn get_or_create_hls_session(
     server: &str,
     token: &str,
-    media_guid: &str,
+    session_key: &str,
     config: &BridgeConfig,
 ) -> Option<(String, String)> {
     // 检查缓存
-    if let Some(cached) = HLS_SESSION_MAP.get(media_guid) {
+    if let Some(cached) = HLS_SESSION_MAP.get(session_key) {
         return Some((cached.session_guid.clone(), cached.play_link.clone()));
     }
 
-    // 获取流元数据
-    let meta = STREAM_META_MAP.get(media_guid)?;
-    let meta = meta.value().clone();
+    // 获取流元数据，动态范围以会话键为准
+    let (media_guid, forced_sdr) = parse_hls_session_key(session_key);
+    let mut meta = get_stream_meta(media_guid)?;
+    meta.forced_sdr = forced_sdr;
+
+    let (session_guid, play_link) = start_transcode(server, token, &meta, 0.0, config).await?;
+
+    HLS_SESSION_MAP.insert(
+        session_key.to_string(),
+        HlsSession {
+            play_link: play_link.clone(),
+            session_guid: session_guid.clone(),
+            fnos_server: server.to_string(),
+            fnos_token: token.to_string(),
+            created_at: now_millis(),
+        },
+    );
+
+    debug!(
+        "[HLS] 转码会话已创建: key={} → sessionGuid={}",
+        session_key, session_guid
+    );
+
+    Some((session_guid, play_link))
+}
+
+/// 从指定位置（秒）启动一次性转码会话，不写入会话缓存
+/// 用于渐进式流的跳转播放，避免影响 HLS 播放器正在使用的会话
+/// 会话登记在 `client` 名下并替换该客户端之前的一次性会话，返回 (sessionGuid, playLink, 登记 ID)
+pub async fn start_hls_session_at(
+    server: &str,
+    token: &str,
+    client: &str,
+    media_guid: &str,
+    start_seconds: f64,
+    config: &BridgeConfig,
+) -> Option<(String, String, u64)> {
+    let meta = get_stream_meta(media_guid)?;
+    let (session_guid, play_link) = start_transcode(server, token, &meta, start_seconds, config).await?;
+
+    let id = NEXT_ONE_OFF_ID.fetch_add(1, Ordering::Relaxed);
+    let previous = ONE_OFF_SESSIONS.insert(
+        client.to_string(),
+        OneOffSession { id, session_guid: session_guid.clone() },
+    );
+    if let Some(prev) = previous {
+        debug!("[HLS] 替换一次性转码会话: {} → {}", prev.session_guid, session_guid);
+    }
+    Some((session_guid, play_link, id))
+}
+
+/// 一次性会话是否仍是该客户端的当前会话（被新的跳转替换后返回 false）
+pub fn is_current_one_off_session(client: &str, id: u64) -> bool {
+    ONE_OFF_SESSIONS.get(client).is_some_and(|s| s.id == id)
+}
+
+/// 渐进式流结束或断开时释放一次性会话登记（已被替换时不做处理）
+pub fn release_one_off_session(client: &str, id: u64) {
+    if let Some((_, s)) = ONE_OFF_SESSIONS.remove_if(client, |_, s| s.id == id) {
+        debug!("[HLS] 释放一次性转码会话: {}", s.session_guid);
+    }
+}
 
+/// 调用 play/play 启动飞牛转码，返回 (sessionGuid, playLink)
+async fn start_transcode(
+    server: &str,
+    token: &str,
+    meta: &StreamMeta,
+    start_seconds: f64,
+    config: &BridgeConfig,
+) -> Option<(String, String)> {
     info!("[HLS] 启动转码会话");
     debug!(
-        "[HLS] mediaGuid={}, audio_guid={}, audio_encoder={}, channels={}",
-        media_guid, meta.audio_guid, meta.audio_encoder, meta.channels
+        "[HLS] mediaGuid={}, audio_guid={}, audio_encoder={}, channels={}, forced_sdr={}, start={}",
+        meta.media_guid, meta.audio_guid, meta.audio_encoder, meta.channels, meta.forced_sdr, start_seconds
     );
 
     let result = fnos_start_play(
@@ -98,12 +203,12 @@ pub async fn get_or_create_hls_session(
             "video_encoder": meta.video_encoder,
             "resolution": meta.resolution,
             "bitrate": meta.bitrate,
-            "startTimestamp": 0,
+            "startTimestamp": start_seconds.max(0.0).floor() as i64,
             "audio_encoder": meta.audio_encoder,
             "audio_guid": meta.audio_guid,
             "subtitle_guid": meta.subtitle_guid,
             "channels": meta.channels,
-            "forced_sdr": 0,
+            "forced_sdr": meta.forced_sdr as i32,
         }),
         config,
     )
@@ -128,28 +233,12 @@ pub async fn get_or_create_hls_session(
         .as_str()
         .to_string();
 
-    HLS_SESSION_MAP.insert(
-        media_guid.to_string(),
-        HlsSession {
-            play_link: play_link.clone(),
-            session_guid: session_guid.clone(),
-            fnos_server: server.to_string(),
-            fnos_token: token.to_string(),
-            created_at: now_millis(),
-        },
-    );
-
-    debug!(
-        "[HLS] 转码会话已创建: mediaGuid={} → sessionGuid={}",
-        media_guid, session_guid
-    );
-
     Some((session_guid, play_link))
 }
 
 /// 获取已缓存的 HLS 会话
-pub fn get_cached_hls_session(media_guid: &str) -> Option<(String, String, String)> {
-    let cached = HLS_SESSION_MAP.get(media_guid)?;
+pub fn get_cached_hls_session(session_key: &str) -> Option<(String, String, String)> {
+    let cached = HLS_SESSION_MAP.get(session_key)?;
     Some((
         cached.session_guid.clone(),
         cached.fnos_server.clone(),
@@ -157,7 +246,13 @@ pub fn get_cached_hls_session(media_guid: &str) -> Option<(String, String, Strin
     ))
 }
 
-/// 清除 HLS 会话缓存
+/// 清除某个版本的 HLS 会话缓存（两种动态范围都清除）
 pub fn clear_hls_session(media_guid: &str) {
     HLS_SESSION_MAP.remove(media_guid);
+    HLS_SESSION_MAP.remove(&hls_session_key(media_guid, true));
+}
+
+/// 清除单个会话键的 HLS 会话缓存（上游会话已失效）
+pub fn expire_hls_session(session_key: &str) {
+    HLS_SESSION_MAP.remove(session_key);
 }
@@ -0,0 +1,171 @@
+/// 图片代理凭据
+/// 图片缓存只保存路径，请求飞牛图片时按以下顺序选择凭据：
+/// 1. 请求所属会话的飞牛 token
+/// 2. 匿名请求带有桥接层签发且未过期的标签（{hash}-{expiry}-{sig}）时，读取磁盘缓存，
+///    未缓存时使用服务账号（FNOS_SERVICE_USERNAME / FNOS_SERVICE_PASSWORD）登录得到的 token
+///
+/// 客户端直接拼接图片 URL（通常不带认证头），签名随 tag 参数带回；
+/// 过期时间按天对齐，同一天内同一张图片的标签不变，ETag / BlurHash 只按其中的图片哈希索引
+
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha1::Sha1;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::config::BridgeConfig;
+use crate::services::fnos::fnos_login;
+
+/// 服务账号登录失败后的重试间隔（逐次翻倍，避免密码错误时反复登录导致账号被锁）
+const LOGIN_RETRY_MIN: Duration = Duration::from_secs(30);
+const LOGIN_RETRY_MAX: Duration = Duration::from_secs(3600);
+
+/// 签名标签的对齐周期：签发的标签在当前周期结束后再有效一个周期
+const TAG_PERIOD_SECS: u64 = 86400;
+
+/// 飞牛凭据 (服务器地址, token)
+#[derive(Debug, Clone)]
+pub struct ImageCredential {
+    pub server: String,
+    pub token: String,
+    /// 是否为服务账号（请求失败时需要重新登录）
+    pub service: bool,
+}
+
+/// 签名密钥（首次使用时生成并持久化，重启后已签发的 tag 仍然有效）
+static SIGNING_KEY: LazyLock<Vec<u8>> = LazyLock::new(|| {
+    let path = signing_key_path();
+    if let Some(key) = std::fs::read_to_string(&path).ok().and_then(|s| decode_hex(s.trim())) {
+        if key.len() >= 16 {
+            info!("[IMAGE] 已恢复图片签名密钥");
+            return key;
+        }
+    }
+    let mut key = vec![0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    if let Err(e) = std::fs::write(&path, encode_hex(&key)) {
+        warn!("[IMAGE] 保存图片签名密钥失败: {}", e);
+    }
+    key
+});
+
+/// 服务账号登录状态
+#[derive(Default)]
+struct ServiceLogin {
+    credential: Option<ImageCredential>,
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+/// 同一时间只有一个登录请求，其余请求等待其结果（失败时在退避期内直接返回）
+static SERVICE_LOGIN: LazyLock<Mutex<ServiceLogin>> = LazyLock::new(|| Mutex::new(ServiceLogin::default()));
+
+fn signing_key_path() -> PathBuf {
+    PathBuf::from(".image_signing_key")
+}
+
+fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn image_mac(hash: &str, expiry: u64) -> Option<Hmac<Sha1>> {
+    let mut mac = Hmac::<Sha1>::new_from_slice(&SIGNING_KEY).ok()?;
+    mac.update(format!("{}-{:x}", hash, expiry).as_bytes());
+    Some(mac)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// 为图片标签附加过期时间与签名
+pub fn sign_image_tag(hash: &str) -> String {
+    sign_tag_until(hash, (unix_now() / TAG_PERIOD_SECS + 2) * TAG_PERIOD_SECS)
+}
+
+/// 签发 ttl_secs 秒后过期的标签（Trickplay 拼图等短期授权）
+pub fn sign_short_lived_tag(hash: &str, ttl_secs: u64) -> String {
+    sign_tag_until(hash, unix_now() + ttl_secs)
+}
+
+fn sign_tag_until(hash: &str, expiry: u64) -> String {
+    match image_mac(hash, expiry) {
+        Some(mac) => format!("{}-{:x}-{}", hash, expiry, encode_hex(&mac.finalize().into_bytes()[..8])),
+        None => hash.to_string(),
+    }
+}
+
+/// 标签中的图片哈希部分（BlurHash、ETag 等按此索引）
+pub fn image_tag_hash(tag: &str) -> &str {
+    tag.split_once('-').map(|(hash, _)| hash).unwrap_or(tag)
+}
+
+/// 校验签名标签（签名正确且未过期），返回图片哈希
+pub fn verify_image_tag(tag: &str) -> Option<&str> {
+    let mut parts = tag.splitn(3, '-');
+    let (hash, expiry, sig) = (parts.next()?, parts.next()?, parts.next()?);
+    let expiry = u64::from_str_radix(expiry, 16).ok()?;
+    if expiry <= unix_now() {
+        return None;
+    }
+    let sig = decode_hex(sig)?;
+    image_mac(hash, expiry)?.verify_truncated_left(&sig).ok()?;
+    Some(hash)
+}
+
+/// 签名标签是否授权读取该图片（只允许读取磁盘缓存或以服务账号拉取，不借用任何用户的会话凭据）
+pub fn tag_authorizes(tag: &str, expected_hash: &str) -> bool {
+    verify_image_tag(tag) == Some(expected_hash)
+}
+
+/// 服务账号凭据（未配置或处于登录失败退避期时返回 None）
+pub async fn service_credential(config: &BridgeConfig) -> Option<ImageCredential> {
+    if config.service_username.is_empty() {
+        return None;
+    }
+    let mut state = SERVICE_LOGIN.lock().await;
+    if let Some(ref credential) = state.credential {
+        return Some(credential.clone());
+    }
+    if state.retry_at.is_some_and(|t| Instant::now() < t) {
+        return None;
+    }
+    match fnos_login(config, &config.service_username, &config.service_password).await {
+        Ok((token, server)) => {
+            info!("[IMAGE] 服务账号 {} 登录成功", config.service_username);
+            let credential = ImageCredential { server, token, service: true };
+            *state = ServiceLogin { credential: Some(credential.clone()), ..Default::default() };
+            Some(credential)
+        }
+        Err(e) => {
+            let delay = LOGIN_RETRY_MIN
+                .saturating_mul(2u32.saturating_pow(state.failures))
+                .min(LOGIN_RETRY_MAX);
+            state.failures += 1;
+            state.retry_at = Some(Instant::now() + delay);
+            warn!("[IMAGE] 服务账号登录失败: {}，{} 秒后重试", e, delay.as_secs());
+            None
+        }
+    }
+}
+
+/// 服务账号 token 失效，下次使用时重新登录
+pub async fn invalidate_service_credential(token: &str) {
+    let mut state = SERVICE_LOGIN.lock().await;
+    if state.credential.as_ref().is_some_and(|c| c.token == token) {
+        state.credential = None;
+    }
+}
@@ -0,0 +1,153 @@
+/// 本地外挂字幕存储
+/// 飞牛未刮削到字幕时由客户端上传，桥接层保存在本地并合并到 MediaStreams
+/// 以飞牛 GUID 为键，ID 映射重建后仍然有效
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// 本地字幕在 SubtitleInfo.guid 中的前缀，用于区分飞牛字幕
+pub const LOCAL_SUBTITLE_PREFIX: &str = "local_";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalSubtitle {
+    pub id: String,
+    /// 所属条目的飞牛 GUID
+    pub item_guid: String,
+    pub language: String,
+    /// 文件格式（srt / ass / vtt ...）
+    pub format: String,
+    #[serde(default)]
+    pub is_forced: bool,
+    #[serde(default)]
+    pub is_hearing_impaired: bool,
+    pub created_at: i64,
+}
+
+impl LocalSubtitle {
+    /// 注册到 SubtitleInfo 时使用的伪 GUID（"local_{id}"）
+    pub fn subtitle_guid(&self) -> String {
+        format!("{}{}", LOCAL_SUBTITLE_PREFIX, self.id)
+    }
+
+    fn file_path(&self) -> PathBuf {
+        subtitle_dir().join(format!("{}.{}", self.id, self.format))
+    }
+}
+
+/// subtitle id → LocalSubtitle
+static LOCAL_SUBTITLES: LazyLock<DashMap<String, LocalSubtitle>> = LazyLock::new(|| {
+    let map = DashMap::new();
+    if let Some(data) = load_subtitles_from_file() {
+        for (k, v) in data {
+            map.insert(k, v);
+        }
+        info!("[SUBTITLE] 已恢复 {} 条本地字幕", map.len());
+    }
+    map
+});
+
+fn subtitle_file_path() -> PathBuf {
+    PathBuf::from(".local_subtitles.json")
+}
+
+fn subtitle_dir() -> PathBuf {
+    PathBuf::from(".subtitles")
+}
+
+fn load_subtitles_from_file() -> Option<std::collections::HashMap<String, LocalSubtitle>> {
+    let content = std::fs::read_to_string(subtitle_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_subtitles() {
+    let mut data = std::collections::HashMap::new();
+    for entry in LOCAL_SUBTITLES.iter() {
+        data.insert(entry.key().clone(), entry.value().clone());
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&data) {
+        if let Err(e) = std::fs::write(subtitle_file_path(), json) {
+            warn!("[SUBTITLE] 保存本地字幕索引失败: {}", e);
+        }
+    }
+}
+
+/// 保存上传的字幕文件
+pub fn add_subtitle(
+    item_guid: &str,
+    language: &str,
+    format: &str,
+    is_forced: bool,
+    is_hearing_impaired: bool,
+    data: &[u8],
+) -> std::io::Result<LocalSubtitle> {
+    let subtitle = LocalSubtitle {
+        id: Uuid::new_v4().simple().to_string(),
+        item_guid: item_guid.to_string(),
+        language: language.to_string(),
+        format: format.to_lowercase(),
+        is_forced,
+        is_hearing_impaired,
+        created_at: chrono::Utc::now().timestamp_millis(),
+    };
+    std::fs::create_dir_all(subtitle_dir())?;
+    std::fs::write(subtitle.file_path(), data)?;
+    LOCAL_SUBTITLES.insert(subtitle.id.clone(), subtitle.clone());
+    save_subtitles();
+    Ok(subtitle)
+}
+
+/// 条目的本地字幕，按上传时间排序
+pub fn list_subtitles(item_guid: &str) -> Vec<LocalSubtitle> {
+    let mut list: Vec<LocalSubtitle> = LOCAL_SUBTITLES
+        .iter()
+        .filter(|s| s.item_guid == item_guid)
+        .map(|s| s.value().clone())
+        .collect();
+    list.sort_by_key(|s| s.created_at);
+    list
+}
+
+/// 根据 SubtitleInfo.guid 查找本地字幕（非本地字幕返回 None）
+pub fn find_subtitle(subtitle_guid: &str) -> Option<LocalSubtitle> {
+    let id = subtitle_guid.strip_prefix(LOCAL_SUBTITLE_PREFIX)?;
+    LOCAL_SUBTITLES.get(id).map(|s| s.value().clone())
+}
+
+/// 读取本地字幕文件内容
+pub fn read_subtitle(subtitle: &LocalSubtitle) -> Option<Vec<u8>> {
+    std::fs::read(subtitle.file_path()).ok()
+}
+
+/// 删除本地字幕及其文件
+pub fn delete_subtitle(id: &str) -> bool {
+    let Some((_, subtitle)) = LOCAL_SUBTITLES.remove(id) else {
+        return false;
+    };
+    if let Err(e) = std::fs::remove_file(subtitle.file_path()) {
+        warn!("[SUBTITLE] 删除字幕文件失败: {}", e);
+    }
+    save_subtitles();
+    true
+}
+
+/// 本地字幕 → 与飞牛 subtitle_streams 相同结构的 JSON，供 build_media_sources 合并
+pub fn subtitle_stream_values(item_guid: &str) -> Vec<serde_json::Value> {
+    list_subtitles(item_guid)
+        .iter()
+        .map(|s| {
+            serde_json::json!({
+                "guid": s.subtitle_guid(),
+                "codec_name": s.format,
+                "language": s.language,
+                "title": if s.is_hearing_impaired { "SDH" } else { "" },
+                "is_external": 1,
+                "is_bitmap": false,
+                "forced": if s.is_forced { 1 } else { 0 },
+            })
+        })
+        .collect()
+}
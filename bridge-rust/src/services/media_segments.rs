@@ -0,0 +1,85 @@
+/// 片头/片尾片段存储
+/// 飞牛只提供按剧集设置的跳过片头/片尾秒数（play_config），桥接层另外保存手动设置的片段
+/// 以飞牛 GUID 为键；保存在季或剧集上的片段对其下所有单集生效
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::{info, warn};
+
+/// 保存的片段（时间为 ticks）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredSegment {
+    #[serde(rename = "Type")]
+    pub segment_type: String,
+    #[serde(rename = "StartTicks")]
+    pub start_ticks: i64,
+    #[serde(rename = "EndTicks")]
+    pub end_ticks: i64,
+}
+
+/// 飞牛 GUID → 片段列表
+static SEGMENTS: LazyLock<DashMap<String, Vec<StoredSegment>>> = LazyLock::new(|| {
+    let map = DashMap::new();
+    if let Some(data) = load_segments_from_file() {
+        for (k, v) in data {
+            map.insert(k, v);
+        }
+        info!("[SEGMENT] 已恢复 {} 个条目的片段", map.len());
+    }
+    map
+});
+
+fn segment_file_path() -> PathBuf {
+    PathBuf::from(".media_segments.json")
+}
+
+fn load_segments_from_file() -> Option<std::collections::HashMap<String, Vec<StoredSegment>>> {
+    let content = std::fs::read_to_string(segment_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_segments() {
+    let mut data = std::collections::HashMap::new();
+    for entry in SEGMENTS.iter() {
+        data.insert(entry.key().clone(), entry.value().clone());
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&data) {
+        if let Err(e) = std::fs::write(segment_file_path(), json) {
+            warn!("[SEGMENT] 保存片段失败: {}", e);
+        }
+    }
+}
+
+/// Jellyfin MediaSegmentType，大小写归一化；未知类型返回 None
+pub fn normalize_segment_type(value: &str) -> Option<&'static str> {
+    match value.to_lowercase().as_str() {
+        "intro" => Some("Intro"),
+        "outro" => Some("Outro"),
+        "recap" => Some("Recap"),
+        "preview" => Some("Preview"),
+        "commercial" => Some("Commercial"),
+        "unknown" => Some("Unknown"),
+        _ => None,
+    }
+}
+
+/// 替换条目的片段（空列表即删除）
+pub fn set_segments(guid: &str, segments: Vec<StoredSegment>) {
+    if segments.is_empty() {
+        SEGMENTS.remove(guid);
+    } else {
+        SEGMENTS.insert(guid.to_string(), segments);
+    }
+    save_segments();
+}
+
+/// 按单集 → 季 → 剧集的顺序查找保存的片段
+pub fn find_segments(guids: &[&str]) -> Vec<StoredSegment> {
+    guids
+        .iter()
+        .filter(|g| !g.is_empty())
+        .find_map(|g| SEGMENTS.get(*g).map(|s| s.value().clone()))
+        .unwrap_or_default()
+}
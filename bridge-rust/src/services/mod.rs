@@ -1,3 +1,15 @@
 pub mod session;
 pub mod fnos;
 pub mod hls_session;
+pub mod display_prefs;
+pub mod user_config;
+pub mod playlist;
+pub mod subtitle;
+pub mod local_subtitle;
+pub mod trickplay;
+pub mod chapters;
+pub mod media_segments;
+pub mod image_auth;
+pub mod device_profile;
+pub mod hanzi;
+pub mod search;
@@ -0,0 +1,176 @@
+/// 播放列表存储
+/// 飞牛没有播放列表接口，由桥接层按用户保存在本地
+/// 条目以飞牛 GUID 保存，ID 映射重建后仍然有效
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::mappers::id::to_jellyfin_id;
+
+/// 播放列表 ID 在 ID 映射中的前缀（与 "view_" 虚拟媒体库同理）
+pub const PLAYLIST_GUID_PREFIX: &str = "playlist_";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaylistEntry {
+    /// PlaylistItemId，用于删除和移动
+    pub entry_id: String,
+    /// 飞牛 GUID
+    pub guid: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Playlist {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub media_type: String,
+    #[serde(default)]
+    pub items: Vec<PlaylistEntry>,
+    pub created_at: i64,
+}
+
+impl Playlist {
+    /// 映射层使用的伪 GUID（"playlist_{id}"）
+    pub fn fnos_guid(&self) -> String {
+        format!("{}{}", PLAYLIST_GUID_PREFIX, self.id)
+    }
+
+    /// 对外的 Jellyfin ID
+    pub fn jellyfin_id(&self) -> String {
+        to_jellyfin_id(&self.fnos_guid())
+    }
+}
+
+/// playlist id → Playlist
+static PLAYLISTS: LazyLock<DashMap<String, Playlist>> = LazyLock::new(|| {
+    let map = DashMap::new();
+    if let Some(data) = load_playlists_from_file() {
+        for (k, v) in data {
+            map.insert(k, v);
+        }
+        info!("[PLAYLIST] 已恢复 {} 个播放列表", map.len());
+    }
+    map
+});
+
+fn playlist_file_path() -> PathBuf {
+    PathBuf::from(".playlists.json")
+}
+
+fn load_playlists_from_file() -> Option<std::collections::HashMap<String, Playlist>> {
+    let content = std::fs::read_to_string(playlist_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_playlists() {
+    let mut data = std::collections::HashMap::new();
+    for entry in PLAYLISTS.iter() {
+        data.insert(entry.key().clone(), entry.value().clone());
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&data) {
+        if let Err(e) = std::fs::write(playlist_file_path(), json) {
+            warn!("[PLAYLIST] 保存播放列表失败: {}", e);
+        }
+    }
+}
+
+fn new_entries(guids: &[String]) -> Vec<PlaylistEntry> {
+    guids
+        .iter()
+        .map(|g| PlaylistEntry {
+            entry_id: Uuid::new_v4().simple().to_string(),
+            guid: g.clone(),
+        })
+        .collect()
+}
+
+/// 创建播放列表
+pub fn create_playlist(user_id: &str, name: &str, media_type: &str, guids: &[String]) -> Playlist {
+    let playlist = Playlist {
+        id: Uuid::new_v4().simple().to_string(),
+        user_id: user_id.to_lowercase(),
+        name: name.to_string(),
+        media_type: media_type.to_string(),
+        items: new_entries(guids),
+        created_at: chrono::Utc::now().timestamp_millis(),
+    };
+    PLAYLISTS.insert(playlist.id.clone(), playlist.clone());
+    save_playlists();
+    playlist
+}
+
+/// 根据 Jellyfin ID 查找用户的播放列表
+/// 逐个比对而非依赖 ID 反向映射，重启后客户端持有的旧 ID 仍可解析
+pub fn find_playlist(user_id: &str, jellyfin_id: &str) -> Option<Playlist> {
+    let lower = jellyfin_id.to_lowercase();
+    list_playlists(user_id).into_iter().find(|p| p.jellyfin_id() == lower)
+}
+
+/// 列出用户的播放列表，按名称排序
+pub fn list_playlists(user_id: &str) -> Vec<Playlist> {
+    let lower = user_id.to_lowercase();
+    let mut list: Vec<Playlist> = PLAYLISTS
+        .iter()
+        .filter(|p| p.user_id == lower)
+        .map(|p| p.value().clone())
+        .collect();
+    list.sort_by(|a, b| a.name.cmp(&b.name));
+    list
+}
+
+/// 修改用户的播放列表，返回修改后的副本；不存在或不属于该用户返回 None
+fn update_playlist(user_id: &str, id: &str, f: impl FnOnce(&mut Playlist)) -> Option<Playlist> {
+    let updated = {
+        let mut entry = PLAYLISTS.get_mut(id)?;
+        if entry.user_id != user_id.to_lowercase() {
+            return None;
+        }
+        f(entry.value_mut());
+        entry.value().clone()
+    };
+    save_playlists();
+    Some(updated)
+}
+
+/// 追加条目
+pub fn add_items(user_id: &str, id: &str, guids: &[String]) -> Option<Playlist> {
+    update_playlist(user_id, id, |p| p.items.extend(new_entries(guids)))
+}
+
+/// 按 PlaylistItemId 删除条目
+pub fn remove_entries(user_id: &str, id: &str, entry_ids: &[String]) -> Option<Playlist> {
+    update_playlist(user_id, id, |p| {
+        p.items.retain(|e| !entry_ids.contains(&e.entry_id));
+    })
+}
+
+/// 移动条目到新位置
+pub fn move_entry(user_id: &str, id: &str, entry_id: &str, new_index: usize) -> Option<Playlist> {
+    update_playlist(user_id, id, |p| {
+        if let Some(pos) = p.items.iter().position(|e| e.entry_id == entry_id) {
+            let entry = p.items.remove(pos);
+            let idx = new_index.min(p.items.len());
+            p.items.insert(idx, entry);
+        }
+    })
+}
+
+/// 重命名播放列表
+pub fn rename_playlist(user_id: &str, id: &str, name: &str) -> Option<Playlist> {
+    update_playlist(user_id, id, |p| p.name = name.to_string())
+}
+
+/// 删除播放列表
+pub fn delete_playlist(user_id: &str, id: &str) -> bool {
+    let lower = user_id.to_lowercase();
+    let removed = PLAYLISTS.remove_if(id, |_, p| p.user_id == lower).is_some();
+    if removed {
+        save_playlists();
+    }
+    removed
+}
@@ -0,0 +1,102 @@
+/// 搜索匹配与排序
+/// 标题、原标题、剧集名按匹配程度打分：完全匹配 > 前缀 > 包含 > 分词全部命中 > 拼音首字母
+/// 匹配前统一全角转半角、繁体转简体、小写，并把标点视为分隔符（见 services::hanzi）
+
+use crate::mappers::item::map_type;
+use crate::services::hanzi::{is_cjk, normalize, pinyin_initials};
+use crate::types::fnos::FnosPlayListItem;
+
+/// 匹配得分
+const SCORE_EXACT: u32 = 100;
+const SCORE_PREFIX: u32 = 90;
+const SCORE_CONTAINS: u32 = 70;
+const SCORE_TOKENS: u32 = 60;
+const SCORE_INITIALS_EXACT: u32 = 55;
+const SCORE_INITIALS_PREFIX: u32 = 50;
+const SCORE_INITIALS_CONTAINS: u32 = 40;
+
+/// 预处理后的搜索词
+pub struct SearchMatcher {
+    term: String,
+    tokens: Vec<String>,
+    /// 纯字母数字的搜索词同时按拼音首字母匹配
+    initials: Option<String>,
+}
+
+impl SearchMatcher {
+    /// 归一化后为空时返回 None
+    pub fn new(term: &str) -> Option<Self> {
+        let term = normalize(term);
+        if term.is_empty() {
+            return None;
+        }
+        let tokens = term.split(' ').map(str::to_string).collect();
+        let compact: String = term.chars().filter(|c| *c != ' ').collect();
+        let initials = (compact.len() >= 2 && compact.chars().all(|c| c.is_ascii_alphanumeric())).then_some(compact);
+        Some(Self { term, tokens, initials })
+    }
+
+    /// 单个文本的得分，0 表示不匹配
+    pub fn score(&self, text: &str) -> u32 {
+        let text = normalize(text);
+        if text.is_empty() {
+            return 0;
+        }
+        if text == self.term {
+            return SCORE_EXACT;
+        }
+        if text.starts_with(&self.term) {
+            return SCORE_PREFIX;
+        }
+        if text.contains(&self.term) {
+            return SCORE_CONTAINS;
+        }
+        if self.tokens.len() > 1 && self.tokens.iter().all(|t| text.contains(t.as_str())) {
+            return SCORE_TOKENS;
+        }
+        if let Some(ref initials) = self.initials {
+            let text_initials = pinyin_initials(&text);
+            // 纯英文标题的首字母没有意义，只对含汉字的标题做拼音匹配
+            if text.chars().any(is_cjk) {
+                if text_initials == *initials {
+                    return SCORE_INITIALS_EXACT;
+                }
+                if text_initials.starts_with(initials.as_str()) {
+                    return SCORE_INITIALS_PREFIX;
+                }
+                if text_initials.contains(initials.as_str()) {
+                    return SCORE_INITIALS_CONTAINS;
+                }
+            }
+        }
+        0
+    }
+
+    /// 多个字段中的最高得分
+    pub fn best_score<'a>(&self, texts: impl IntoIterator<Item = &'a str>) -> u32 {
+        texts.into_iter().map(|t| self.score(t)).max().unwrap_or(0)
+    }
+
+    /// 飞牛项目得分：标题、原标题；非单集还匹配所属剧集名
+    pub fn score_item(&self, item: &FnosPlayListItem) -> u32 {
+        let mut fields = vec![item.title.as_str(), item.original_title.as_str()];
+        if map_type(&item.item_type) != "Episode" {
+            fields.push(item.tv_title.as_str());
+        }
+        self.best_score(fields)
+    }
+}
+
+/// 按搜索词过滤并排序（得分高的在前，同分时标题短的在前，其余保持原顺序）
+pub fn rank_items<'a>(term: &str, items: Vec<&'a FnosPlayListItem>) -> Vec<&'a FnosPlayListItem> {
+    let Some(matcher) = SearchMatcher::new(term) else {
+        return items;
+    };
+    let mut scored: Vec<(u32, &FnosPlayListItem)> = items
+        .into_iter()
+        .map(|item| (matcher.score_item(item), item))
+        .filter(|(score, _)| *score > 0)
+        .collect();
+    scored.sort_by_key(|(score, item)| (std::cmp::Reverse(*score), item.title.chars().count()));
+    scored.into_iter().map(|(_, item)| item).collect()
+}
@@ -9,6 +9,8 @@ use std::sync::LazyLock;
 use tracing::{info, warn};
 use uuid::Uuid;
 
+use crate::types::fnos::FnosUserInfo;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionData {
     pub fnos_token: String,
@@ -23,6 +25,25 @@ pub struct SessionData {
     pub last_activity: i64,
     #[serde(default)]
     pub access_token: String,
+    /// 飞牛昵称（登录后从 user/info 补充）
+    #[serde(default)]
+    pub nickname: String,
+    /// 飞牛头像路径（登录后从 user/info 补充）
+    #[serde(default)]
+    pub avatar: String,
+}
+
+/// 近期登录过的用户（按 user_id 聚合会话）
+#[derive(Debug, Clone)]
+pub struct KnownUser {
+    pub user_id: String,
+    pub username: String,
+    pub nickname: String,
+    pub avatar: String,
+    pub fnos_server: String,
+    pub fnos_token: String,
+    pub last_login: i64,
+    pub last_activity: i64,
 }
 
 /// 正在播放状态
@@ -105,6 +126,8 @@ pub fn create_session(
             created_at: now,
             last_activity: now,
             access_token: access_token.clone(),
+            nickname: String::new(),
+            avatar: String::new(),
         },
     );
     save_sessions();
@@ -123,6 +146,71 @@ pub fn get_session(access_token: &str) -> Option<SessionData> {
     Some(data)
 }
 
+/// 更新会话中的用户资料（昵称、头像）
+pub fn set_session_profile(access_token: &str, profile: &FnosUserInfo) {
+    let changed = match SESSIONS.get_mut(access_token) {
+        Some(mut entry) => {
+            let changed = entry.nickname != profile.nickname || entry.avatar != profile.avatar;
+            entry.nickname = profile.nickname.clone();
+            entry.avatar = profile.avatar.clone();
+            changed
+        }
+        None => false,
+    };
+    if changed {
+        save_sessions();
+    }
+}
+
+/// 列出近期登录过的用户，按最后活动时间倒序
+/// 同一用户的多个会话合并，凭据取最近活动的会话
+pub fn list_known_users() -> Vec<KnownUser> {
+    let mut users: std::collections::HashMap<String, KnownUser> = std::collections::HashMap::new();
+    for entry in SESSIONS.iter() {
+        let s = entry.value();
+        if s.user_id.is_empty() {
+            continue;
+        }
+        match users.get_mut(&s.user_id) {
+            Some(u) => {
+                u.last_login = u.last_login.max(s.created_at);
+                if s.last_activity > u.last_activity {
+                    u.last_activity = s.last_activity;
+                    u.fnos_server = s.fnos_server.clone();
+                    u.fnos_token = s.fnos_token.clone();
+                    if !s.nickname.is_empty() {
+                        u.nickname = s.nickname.clone();
+                    }
+                    if !s.avatar.is_empty() {
+                        u.avatar = s.avatar.clone();
+                    }
+                }
+            }
+            None => {
+                users.insert(s.user_id.clone(), KnownUser {
+                    user_id: s.user_id.clone(),
+                    username: s.username.clone(),
+                    nickname: s.nickname.clone(),
+                    avatar: s.avatar.clone(),
+                    fnos_server: s.fnos_server.clone(),
+                    fnos_token: s.fnos_token.clone(),
+                    last_login: s.created_at,
+                    last_activity: s.last_activity,
+                });
+            }
+        }
+    }
+    let mut list: Vec<KnownUser> = users.into_values().collect();
+    list.sort_by_key(|u| std::cmp::Reverse(u.last_activity));
+    list
+}
+
+/// 根据 user_id 查找近期登录过的用户
+pub fn get_known_user(user_id: &str) -> Option<KnownUser> {
+    let lower = user_id.to_lowercase();
+    list_known_users().into_iter().find(|u| u.user_id == lower)
+}
+
 /// 删除会话
 pub fn remove_session(access_token: &str) -> bool {
     let result = SESSIONS.remove(access_token).is_some();
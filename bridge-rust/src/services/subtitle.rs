@@ -0,0 +1,639 @@
+/// 字幕格式转换
+/// SRT / ASS / SSA / WebVTT → 统一的字幕条目模型 → vtt / srt / ass / ttml / json
+/// 自动识别 UTF-8 / UTF-16 / GBK / Big5 编码
+
+use encoding_rs::{Encoding, BIG5, GB18030, UTF_16BE, UTF_16LE};
+use serde_json::json;
+
+/// 字幕条目，文本中只保留 <i>/<b>/<u> 标签，换行为 '\n'
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub text: String,
+}
+
+/// 支持输出的字幕格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubtitleFormat {
+    Vtt,
+    Srt,
+    Ass,
+    Ssa,
+    Ttml,
+    Json,
+}
+
+impl SubtitleFormat {
+    /// 从路由中的格式名解析（Stream.vtt / Stream.srt / Stream.js 等）
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "vtt" | "webvtt" => Some(Self::Vtt),
+            "srt" | "subrip" => Some(Self::Srt),
+            "ass" => Some(Self::Ass),
+            "ssa" => Some(Self::Ssa),
+            "ttml" | "dfxp" => Some(Self::Ttml),
+            "json" | "js" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Vtt => "text/vtt; charset=utf-8",
+            Self::Srt => "application/x-subrip; charset=utf-8",
+            Self::Ass | Self::Ssa => "text/x-ssa; charset=utf-8",
+            Self::Ttml => "application/ttml+xml; charset=utf-8",
+            Self::Json => "application/json; charset=utf-8",
+        }
+    }
+}
+
+/// 转换选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConvertOptions {
+    /// 时间轴偏移（毫秒），所有条目时间减去该值（从中途开始的转码流）
+    pub offset_ms: i64,
+    /// 截止时间（毫秒，原始时间轴），之后开始的条目丢弃
+    pub end_ms: Option<i64>,
+    /// VTT 输出 X-TIMESTAMP-MAP，供 HLS 分段字幕对齐 MPEG-TS 时间戳
+    pub vtt_time_map: bool,
+}
+
+impl ConvertOptions {
+    /// 按截止时间与偏移调整单个条目，不在窗口内的返回 None
+    fn window(&self, start_ms: i64, end_ms: i64) -> Option<(i64, i64)> {
+        if self.end_ms.is_some_and(|limit| start_ms >= limit) {
+            return None;
+        }
+        let (start, end) = (start_ms - self.offset_ms, end_ms - self.offset_ms);
+        (end > 0).then_some((start.max(0), end))
+    }
+}
+
+/// 图形字幕（无法转换为文本格式）
+pub fn is_image_subtitle(codec: &str) -> bool {
+    let codec = codec.to_lowercase();
+    ["pgs", "dvd_sub", "dvdsub", "vobsub", "dvb_sub", "dvbsub", "xsub"]
+        .iter()
+        .any(|c| codec.contains(c))
+}
+
+/// 中文高频字（简繁共用或各自常见），用于区分 GBK 与 Big5 解码结果
+const COMMON_HAN: &str = "的一是不了在人有我他这個们們中来來上大为為和国國地到以说說时時要就出会會可也你对對生能而子那得于於着著下自之年过過发發后後作里裡用道行所然家种種事成方多经經么麼去法学學如都同现現当當没沒动動面起看定天分还還进進好小部其些主样樣理心她本前开開但因只从從想实實";
+
+/// 字节 → 文本，识别 BOM / UTF-8，否则在 GBK(GB18030) 与 Big5 之间择优
+pub fn decode_text(bytes: &[u8]) -> String {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return text.into_owned();
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_string();
+    }
+    // 无 BOM 的 UTF-16：ASCII 字符的另一半字节为 0
+    let zeros_even = bytes.iter().step_by(2).filter(|b| **b == 0).count();
+    let zeros_odd = bytes.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+    if bytes.len() >= 4 && zeros_odd > bytes.len() / 4 {
+        return UTF_16LE.decode_without_bom_handling(bytes).0.into_owned();
+    }
+    if bytes.len() >= 4 && zeros_even > bytes.len() / 4 {
+        return UTF_16BE.decode_without_bom_handling(bytes).0.into_owned();
+    }
+
+    let score = |encoding: &'static Encoding| {
+        let (text, had_errors) = encoding.decode_without_bom_handling(bytes);
+        let hits = text.chars().filter(|c| COMMON_HAN.contains(*c)).count() as i64;
+        let errors = text.chars().filter(|c| *c == '\u{FFFD}').count() as i64;
+        let penalty = if had_errors { errors * 10 } else { 0 };
+        (hits - penalty, text.into_owned())
+    };
+    let (gbk_score, gbk_text) = score(GB18030);
+    let (big5_score, big5_text) = score(BIG5);
+    if big5_score > gbk_score {
+        big5_text
+    } else {
+        gbk_text
+    }
+}
+
+/// 解析字幕文本，自动识别 WebVTT / ASS / SSA / SRT
+pub fn parse(text: &str) -> Vec<Cue> {
+    let text = text.trim_start_matches('\u{feff}');
+    let head = text.trim_start();
+    let mut cues = if head.starts_with("WEBVTT") {
+        parse_timed_blocks(text)
+    } else if is_ass(text) {
+        parse_ass(text)
+    } else {
+        parse_timed_blocks(text)
+    };
+    cues.sort_by_key(|c| c.start_ms);
+    cues
+}
+
+/// 是否为 ASS/SSA 字幕
+pub fn is_ass(text: &str) -> bool {
+    text.contains("[Script Info]") || text.lines().any(|l| l.trim_start().starts_with("Dialogue:"))
+}
+
+/// 解析时间戳："00:01:02,345" / "00:01:02.345" / "01:02.345" / ASS 的 "0:01:02.34"
+fn parse_timestamp(s: &str) -> Option<i64> {
+    let s = s.trim().replace(',', ".");
+    let (hms, frac) = match s.split_once('.') {
+        Some((a, b)) => (a.to_string(), b.to_string()),
+        None => (s.clone(), String::new()),
+    };
+    let parts: Vec<i64> = hms.split(':').map(|p| p.trim().parse::<i64>().ok()).collect::<Option<_>>()?;
+    let secs = match parts.as_slice() {
+        [h, m, s] => h * 3600 + m * 60 + s,
+        [m, s] => m * 60 + s,
+        [s] => *s,
+        _ => return None,
+    };
+    // 小数部分按位数换算为毫秒（ASS 为百分之一秒）
+    let frac_digits: String = frac.chars().take_while(|c| c.is_ascii_digit()).take(3).collect();
+    let ms = if frac_digits.is_empty() {
+        0
+    } else {
+        frac_digits.parse::<i64>().ok()? * 10_i64.pow(3 - frac_digits.len() as u32)
+    };
+    Some(secs * 1000 + ms)
+}
+
+/// 解析 SRT / WebVTT：以 "-->" 行为起点的时间块
+fn parse_timed_blocks(text: &str) -> Vec<Cue> {
+    let normalized = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut cues = Vec::new();
+    let mut lines = normalized.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let Some((start, rest)) = line.split_once("-->") else {
+            continue;
+        };
+        // VTT 时间行后可能带有 cue 设置（align:start 等）
+        let end = rest.split_whitespace().next().unwrap_or("");
+        let (Some(start_ms), Some(end_ms)) = (parse_timestamp(start), parse_timestamp(end)) else {
+            continue;
+        };
+
+        let mut body = Vec::new();
+        while let Some(next) = lines.peek() {
+            if next.trim().is_empty() {
+                break;
+            }
+            body.push(lines.next().unwrap_or_default().trim_end().to_string());
+        }
+        let text = clean_markup(&body.join("\n"));
+        if !strip_tags(&text).trim().is_empty() {
+            cues.push(Cue { start_ms, end_ms, text });
+        }
+    }
+    cues
+}
+
+/// SRT / WebVTT 中会出现的标签名，其余的 `<` 按普通文本保留（如 "a < b > c"）
+const MARKUP_TAGS: &[&str] = &["i", "b", "u", "s", "font", "c", "v", "lang", "ruby", "rt", "span"];
+
+/// `<` 之后是否为可识别的标签：已知标签名，或 WebVTT 的时间戳标签 <00:01.000>
+fn is_markup_tag(inner: &str) -> bool {
+    let name = inner.strip_prefix('/').unwrap_or(inner);
+    let name = name.split(|c: char| c.is_whitespace() || c == '.').next().unwrap_or("");
+    MARKUP_TAGS.iter().any(|t| name.eq_ignore_ascii_case(t))
+        || (inner.contains(':') && inner.chars().all(|c| c.is_ascii_digit() || c == ':' || c == '.'))
+}
+
+/// 只保留 <i>/<b>/<u> 标签，去掉 <font>、VTT 的 <c.xxx>、<v Name> 等
+fn clean_markup(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('<') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos..];
+        match after.find('>').filter(|end| is_markup_tag(&after[1..*end])) {
+            Some(end) => {
+                let tag = after[1..end].trim().to_lowercase();
+                if matches!(tag.as_str(), "i" | "/i" | "b" | "/b" | "u" | "/u") {
+                    out.push_str(&format!("<{}>", tag));
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('<');
+                rest = &after[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out.replace("&nbsp;", " ").replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
+
+/// 解析 ASS/SSA 的 [Events] 段
+fn parse_ass(text: &str) -> Vec<Cue> {
+    let mut cues = Vec::new();
+    let mut in_events = false;
+    // 默认字段顺序（Format 行缺失时使用）
+    let mut fields: Vec<String> = ["layer", "start", "end", "style", "name", "marginl", "marginr", "marginv", "effect", "text"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+    for raw in text.lines() {
+        let line = raw.trim();
+        if line.starts_with('[') {
+            in_events = line.eq_ignore_ascii_case("[Events]");
+            continue;
+        }
+        if !in_events && !line.starts_with("Dialogue:") {
+            continue;
+        }
+        if let Some(format) = line.strip_prefix("Format:") {
+            fields = format.split(',').map(|f| f.trim().to_lowercase()).collect();
+            continue;
+        }
+        let Some(dialogue) = line.strip_prefix("Dialogue:") else {
+            continue;
+        };
+
+        // Text 是最后一个字段，可能包含逗号
+        let values: Vec<&str> = dialogue.splitn(fields.len(), ',').collect();
+        let get = |name: &str| fields.iter().position(|f| f == name).and_then(|i| values.get(i)).map(|v| v.trim());
+        let (Some(start_ms), Some(end_ms)) = (get("start").and_then(parse_timestamp), get("end").and_then(parse_timestamp)) else {
+            continue;
+        };
+        let raw_text = fields.iter().position(|f| f == "text").and_then(|i| values.get(i)).copied().unwrap_or("");
+        let text = ass_to_markup(raw_text);
+        if !strip_tags(&text).trim().is_empty() {
+            cues.push(Cue { start_ms, end_ms, text });
+        }
+    }
+    cues
+}
+
+/// ASS 文本 → 简单标签文本：\N 换行，{\i1} 斜体等，其余覆盖标签丢弃
+fn ass_to_markup(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('{') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos..];
+        let Some(end) = after.find('}') else {
+            out.push_str(after);
+            rest = "";
+            break;
+        };
+        for tag in after[1..end].split('\\').map(|t| t.trim()) {
+            match tag {
+                "i1" => out.push_str("<i>"),
+                "i0" => out.push_str("</i>"),
+                "b1" => out.push_str("<b>"),
+                "b0" => out.push_str("</b>"),
+                "u1" => out.push_str("<u>"),
+                "u0" => out.push_str("</u>"),
+                _ => {}
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out.replace("\\N", "\n").replace("\\n", "\n").replace("\\h", " ")
+}
+
+fn strip_tags(text: &str) -> String {
+    ["<i>", "</i>", "<b>", "</b>", "<u>", "</u>"]
+        .iter()
+        .fold(text.to_string(), |acc, t| acc.replace(t, ""))
+}
+
+fn format_timestamp(ms: i64, sep: char) -> String {
+    let ms = ms.max(0);
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        sep,
+        ms % 1000
+    )
+}
+
+fn format_ass_timestamp(ms: i64) -> String {
+    let ms = ms.max(0);
+    format!("{}:{:02}:{:02}.{:02}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000 / 10)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// WebVTT 文本转义：& 与 < 必须转义（> 一并转义以免出现 "-->"），保留 <i>/<b>/<u>
+fn escape_vtt(text: &str) -> String {
+    ["i", "/i", "b", "/b", "u", "/u"].iter().fold(
+        text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"),
+        |acc, tag| acc.replace(&format!("&lt;{}&gt;", tag), &format!("<{}>", tag)),
+    )
+}
+
+/// 渲染为 WebVTT
+pub fn render_vtt(cues: &[Cue], time_map: bool) -> String {
+    let mut out = String::from("WEBVTT\n");
+    if time_map {
+        out.push_str("X-TIMESTAMP-MAP=MPEGTS:900000,LOCAL:00:00:00.000\n");
+    }
+    out.push('\n');
+    for cue in cues {
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_timestamp(cue.start_ms, '.'),
+            format_timestamp(cue.end_ms, '.'),
+            escape_vtt(&cue.text)
+        ));
+    }
+    out
+}
+
+fn render_srt(cues: &[Cue]) -> String {
+    let mut out = String::new();
+    for (i, cue) in cues.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_timestamp(cue.start_ms, ','),
+            format_timestamp(cue.end_ms, ','),
+            cue.text
+        ));
+    }
+    out
+}
+
+fn render_ass(cues: &[Cue], ssa: bool) -> String {
+    let mut out = String::from("[Script Info]\nScriptType: ");
+    out.push_str(if ssa { "v4.00\n" } else { "v4.00+\n" });
+    out.push_str("PlayResX: 1920\nPlayResY: 1080\n\n");
+    if ssa {
+        out.push_str("[V4 Styles]\nFormat: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, TertiaryColour, BackColour, Bold, Italic, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, AlphaLevel, Encoding\n");
+        out.push_str("Style: Default,Arial,64,16777215,65535,0,0,0,0,1,2,1,2,20,20,40,0,1\n\n");
+        out.push_str("[Events]\nFormat: Marked, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n");
+    } else {
+        out.push_str("[V4+ Styles]\nFormat: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n");
+        out.push_str("Style: Default,Arial,64,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,0,0,0,0,100,100,0,0,1,2,1,2,20,20,40,1\n\n");
+        out.push_str("[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n");
+    }
+    let first_field = if ssa { "Marked=0" } else { "0" };
+    for cue in cues {
+        let text = cue.text
+            .replace("<i>", "{\\i1}").replace("</i>", "{\\i0}")
+            .replace("<b>", "{\\b1}").replace("</b>", "{\\b0}")
+            .replace("<u>", "{\\u1}").replace("</u>", "{\\u0}")
+            .replace('\n', "\\N");
+        out.push_str(&format!(
+            "Dialogue: {},{},{},Default,,0,0,0,,{}\n",
+            first_field,
+            format_ass_timestamp(cue.start_ms),
+            format_ass_timestamp(cue.end_ms),
+            text
+        ));
+    }
+    out
+}
+
+fn render_ttml(cues: &[Cue]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<tt xmlns=\"http://www.w3.org/ns/ttml\">\n<body>\n<div>\n",
+    );
+    for cue in cues {
+        let text = escape_xml(&strip_tags(&cue.text)).replace('\n', "<br/>");
+        out.push_str(&format!(
+            "<p begin=\"{}\" end=\"{}\">{}</p>\n",
+            format_timestamp(cue.start_ms, '.'),
+            format_timestamp(cue.end_ms, '.'),
+            text
+        ));
+    }
+    out.push_str("</div>\n</body>\n</tt>\n");
+    out
+}
+
+/// Jellyfin TrackEvents JSON（ticks = 100ns）
+fn render_json(cues: &[Cue]) -> String {
+    let events: Vec<serde_json::Value> = cues
+        .iter()
+        .enumerate()
+        .map(|(i, cue)| {
+            json!({
+                "Id": (i + 1).to_string(),
+                "Text": strip_tags(&cue.text),
+                "StartPositionTicks": cue.start_ms * 10_000,
+                "EndPositionTicks": cue.end_ms * 10_000,
+            })
+        })
+        .collect();
+    json!({ "TrackEvents": events }).to_string()
+}
+
+/// 渲染为指定格式
+pub fn render(cues: &[Cue], format: SubtitleFormat, time_map: bool) -> String {
+    match format {
+        SubtitleFormat::Vtt => render_vtt(cues, time_map),
+        SubtitleFormat::Srt => render_srt(cues),
+        SubtitleFormat::Ass => render_ass(cues, false),
+        SubtitleFormat::Ssa => render_ass(cues, true),
+        SubtitleFormat::Ttml => render_ttml(cues),
+        SubtitleFormat::Json => render_json(cues),
+    }
+}
+
+/// ASS 原文的时间轴调整：只改写 Dialogue 行的 Start/End，保留样式与特效
+fn shift_ass(text: &str, options: &ConvertOptions) -> String {
+    if options.offset_ms == 0 && options.end_ms.is_none() {
+        return text.to_string();
+    }
+    let mut fields: Vec<String> = ["layer", "start", "end", "style", "name", "marginl", "marginr", "marginv", "effect", "text"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let mut out = String::with_capacity(text.len());
+
+    for raw in text.lines() {
+        let line = raw.trim();
+        if let Some(format) = line.strip_prefix("Format:").filter(|f| f.to_lowercase().contains("start")) {
+            fields = format.split(',').map(|f| f.trim().to_lowercase()).collect();
+        }
+        let Some(dialogue) = line.strip_prefix("Dialogue:") else {
+            out.push_str(raw);
+            out.push('\n');
+            continue;
+        };
+
+        let mut values: Vec<String> = dialogue.trim_start().splitn(fields.len(), ',').map(String::from).collect();
+        let start_idx = fields.iter().position(|f| f == "start");
+        let end_idx = fields.iter().position(|f| f == "end");
+        let times = start_idx.zip(end_idx).and_then(|(si, ei)| {
+            let start = parse_timestamp(values.get(si)?)?;
+            let end = parse_timestamp(values.get(ei)?)?;
+            Some((si, ei, start, end))
+        });
+        let Some((si, ei, start, end)) = times else {
+            out.push_str(raw);
+            out.push('\n');
+            continue;
+        };
+        let Some((start, end)) = options.window(start, end) else {
+            continue;
+        };
+        values[si] = format_ass_timestamp(start);
+        values[ei] = format_ass_timestamp(end);
+        out.push_str("Dialogue: ");
+        out.push_str(&values.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// 转换字幕文件为目标格式
+/// 源文件与目标同为 ASS/SSA 时保留原文（样式、特效不丢失），只统一为 UTF-8
+pub fn convert(bytes: &[u8], format: SubtitleFormat, options: &ConvertOptions) -> String {
+    let text = decode_text(bytes);
+    if matches!(format, SubtitleFormat::Ass | SubtitleFormat::Ssa) && is_ass(&text) {
+        return shift_ass(text.trim_start_matches('\u{feff}'), options);
+    }
+    let cues: Vec<Cue> = parse(&text)
+        .into_iter()
+        .filter_map(|cue| {
+            let (start_ms, end_ms) = options.window(cue.start_ms, cue.end_ms)?;
+            Some(Cue { start_ms, end_ms, text: cue.text })
+        })
+        .collect();
+    render(&cues, format, options.vtt_time_map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue(start_ms: i64, end_ms: i64, text: &str) -> Cue {
+        Cue { start_ms, end_ms, text: text.to_string() }
+    }
+
+    #[test]
+    fn srt_crlf_bom_and_missing_trailing_blank_line() {
+        let text = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,500\r\nHello\r\nWorld\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nLast";
+        assert_eq!(parse(text), vec![cue(1000, 2500, "Hello\nWorld"), cue(3000, 4000, "Last")]);
+    }
+
+    #[test]
+    fn srt_markup_and_entities() {
+        let text = "1\n00:00:01,000 --> 00:00:02,000\n<font color=\"#fff\"><I>a &lt; b &amp; c</I></font>\n";
+        assert_eq!(parse(text), vec![cue(1000, 2000, "<i>a < b & c</i>")]);
+    }
+
+    #[test]
+    fn srt_unclosed_tag_is_kept_as_text() {
+        let text = "1\n00:00:01,000 --> 00:00:02,000\n1 <2\n";
+        assert_eq!(parse(text)[0].text, "1 <2");
+    }
+
+    #[test]
+    fn srt_comparison_signs_are_not_tags() {
+        let text = "1\n00:00:01,000 --> 00:00:02,000\na < b > c\n<i>x</i> <3 <unknown>\n";
+        assert_eq!(parse(text)[0].text, "a < b > c\n<i>x</i> <3 <unknown>");
+    }
+
+    #[test]
+    fn vtt_timestamp_tags_are_dropped() {
+        let text = "WEBVTT\n\n00:01.000 --> 00:03.000\nOne <00:02.000>Two\n";
+        assert_eq!(parse(text)[0].text, "One Two");
+    }
+
+    #[test]
+    fn srt_skips_bad_timestamps_and_empty_cues() {
+        let text = "1\n00:00:xx,000 --> 00:00:02,000\nBad\n\n2\n00:00:03,000 --> 00:00:04,000\n<b></b>\n\n3\n00:00:05,000 --> 00:00:06,000\nOk\n";
+        assert_eq!(parse(text), vec![cue(5000, 6000, "Ok")]);
+    }
+
+    #[test]
+    fn vtt_cue_settings_and_short_timestamps() {
+        let text = "WEBVTT\n\n01:02.5 --> 01:03.250 align:start line:0\n<v Bob><c.yellow>Hi</c></v>\n";
+        assert_eq!(parse(text), vec![cue(62_500, 63_250, "Hi")]);
+    }
+
+    #[test]
+    fn ass_custom_format_commas_and_override_tags() {
+        let text = "[Script Info]\nTitle: x\n\n[Events]\nFormat: Layer, Start, End, Style, Text\n\
+            Dialogue: 0,0:00:01.50,0:00:02.00,Default,{\\pos(1,2)\\i1}One, two{\\i0}\\NThree\\hFour\n\
+            Comment: 0,0:00:03.00,0:00:04.00,Default,Hidden\n";
+        assert_eq!(parse(text), vec![cue(1500, 2000, "<i>One, two</i>\nThree Four")]);
+    }
+
+    #[test]
+    fn ass_default_format_and_sorting() {
+        let text = "Dialogue: 0,0:00:05.00,0:00:06.00,Default,,0,0,0,,Second\nDialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,First\n";
+        assert_eq!(parse(text), vec![cue(1000, 2000, "First"), cue(5000, 6000, "Second")]);
+    }
+
+    #[test]
+    fn ass_shift_keeps_styles_and_drops_windowed_lines() {
+        let text = "[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n\
+            Dialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Gone\n\
+            Dialogue: 0,0:00:11.00,0:00:12.00,Sign,,0,0,0,,{\\an8}Kept, here\n";
+        let options = ConvertOptions { offset_ms: 10_000, ..Default::default() };
+        let out = convert(text.as_bytes(), SubtitleFormat::Ass, &options);
+        assert!(!out.contains("Gone"));
+        assert!(out.contains("Dialogue: 0,0:00:01.00,0:00:02.00,Sign,,0,0,0,,{\\an8}Kept, here"));
+    }
+
+    #[test]
+    fn vtt_escapes_text_but_keeps_style_tags() {
+        let out = render_vtt(&[cue(0, 1000, "<i>a < b & c --> d</i>")], false);
+        assert!(out.contains("<i>a &lt; b &amp; c --&gt; d</i>"));
+    }
+
+    #[test]
+    fn ttml_escapes_and_strips_tags() {
+        let out = render_ttml(&[cue(1000, 2000, "<b>Tom & \"Jerry\"</b>\n<1>")]);
+        assert!(out.contains("<p begin=\"00:00:01.000\" end=\"00:00:02.000\">Tom &amp; &quot;Jerry&quot;<br/>&lt;1&gt;</p>"));
+    }
+
+    #[test]
+    fn ttml_empty_input_is_well_formed() {
+        let out = render_ttml(&[]);
+        assert!(out.starts_with("<?xml"));
+        assert!(out.trim_end().ends_with("</tt>"));
+    }
+
+    #[test]
+    fn decode_gbk_without_bom() {
+        // "你好，世界" 的 GBK 编码
+        let bytes = [0xC4, 0xE3, 0xBA, 0xC3, 0xA3, 0xAC, 0xCA, 0xC0, 0xBD, 0xE7];
+        assert_eq!(decode_text(&bytes), "你好，世界");
+    }
+
+    #[test]
+    fn decode_big5_without_bom() {
+        // "我們的時間" 的 Big5 编码
+        let bytes = [0xA7, 0xDA, 0xAD, 0xCC, 0xAA, 0xBA, 0xAE, 0xC9, 0xB6, 0xA1];
+        assert_eq!(decode_text(&bytes), "我們的時間");
+    }
+
+    #[test]
+    fn decode_utf16_with_bom() {
+        let le = [0xFF, 0xFE, b'H', 0x00, b'i', 0x00, 0x60, 0x4F];
+        assert_eq!(decode_text(&le), "Hi你");
+        let be = [0xFE, 0xFF, 0x00, b'H', 0x00, b'i', 0x4F, 0x60];
+        assert_eq!(decode_text(&be), "Hi你");
+    }
+
+    #[test]
+    fn decode_utf8_with_bom() {
+        let bytes = [0xEF, 0xBB, 0xBF, b'1', 0xE4, 0xBD, 0xA0];
+        assert_eq!(decode_text(&bytes), "1你");
+    }
+
+    #[test]
+    fn convert_applies_offset_and_end_window() {
+        let text = "1\n00:00:01,000 --> 00:00:03,000\nA\n\n2\n00:00:05,000 --> 00:00:06,000\nB\n\n3\n00:00:09,000 --> 00:00:10,000\nC\n";
+        let options = ConvertOptions { offset_ms: 2000, end_ms: Some(9000), vtt_time_map: false };
+        let out = convert(text.as_bytes(), SubtitleFormat::Srt, &options);
+        assert_eq!(out, "1\n00:00:00,000 --> 00:00:01,000\nA\n\n2\n00:00:03,000 --> 00:00:04,000\nB\n\n");
+    }
+}
@@ -0,0 +1,391 @@
+/// Trickplay 缩略图生成
+/// 飞牛没有提供预览帧接口，由 ffmpeg 子进程经桥接层自身的直链代理抽帧拼图
+/// 后台任务逐个执行，结果按 mediaSourceId 缓存在磁盘（.trickplay/{mediaSourceId}/{width}/{n}.jpg）
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::LazyLock;
+use tokio::sync::Semaphore;
+use tracing::{debug, info, warn};
+
+use crate::config::BridgeConfig;
+use crate::mappers::item::image_tag;
+use crate::services::image_auth::{sign_short_lived_tag, tag_authorizes};
+use crate::types::jellyfin::TrickplayInfoDto;
+
+/// 缩略图宽度、拼图布局与间隔（与 Jellyfin 默认值一致）
+pub const TRICKPLAY_WIDTH: i32 = 320;
+const TILE_WIDTH: i32 = 10;
+const TILE_HEIGHT: i32 = 10;
+const INTERVAL_MS: i32 = 10_000;
+
+/// 磁盘上的缩略图索引
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrickplayEntry {
+    /// 所属条目的飞牛 GUID
+    pub item_guid: String,
+    /// 宽度 → 缩略图信息
+    pub resolutions: BTreeMap<String, TrickplayInfoDto>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobState {
+    Running,
+    Failed,
+}
+
+/// mediaSourceId → 已生成的缩略图索引
+static TRICKPLAY_INDEX: LazyLock<DashMap<String, TrickplayEntry>> = LazyLock::new(|| {
+    let map = DashMap::new();
+    if let Ok(dirs) = std::fs::read_dir(trickplay_root()) {
+        for dir in dirs.flatten() {
+            let Ok(content) = std::fs::read_to_string(dir.path().join("info.json")) else {
+                continue;
+            };
+            if let Ok(entry) = serde_json::from_str::<TrickplayEntry>(&content) {
+                map.insert(dir.file_name().to_string_lossy().to_string(), entry);
+            }
+        }
+        info!("[TRICKPLAY] 已恢复 {} 个缩略图索引", map.len());
+    }
+    map
+});
+
+/// mediaSourceId → 任务状态（失败的任务在本次运行内不再重试）
+static TRICKPLAY_JOBS: LazyLock<DashMap<String, JobState>> = LazyLock::new(DashMap::new);
+
+/// 同时只运行一个 ffmpeg 任务
+static JOB_PERMITS: LazyLock<Semaphore> = LazyLock::new(|| Semaphore::new(1));
+
+/// ffmpeg 不可用时不再调度任务
+static FFMPEG_MISSING: AtomicBool = AtomicBool::new(false);
+
+/// ffmpeg 任务票据 → (用户访问令牌, 可读取的 mediaSourceId)
+/// ffmpeg 的命令行可被同机其他进程看到，只传入随任务结束作废、只能读取单个版本直链的随机票据
+static FFMPEG_TICKETS: LazyLock<DashMap<String, (String, String)>> = LazyLock::new(DashMap::new);
+
+fn trickplay_root() -> PathBuf {
+    PathBuf::from(".trickplay")
+}
+
+fn resolution_dir(media_source_id: &str, width: i32) -> PathBuf {
+    trickplay_root().join(media_source_id).join(width.to_string())
+}
+
+/// mediaSourceId 用作目录名，只允许安全字符
+fn is_safe_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 已生成的缩略图信息
+pub fn get_trickplay(media_source_id: &str) -> Option<TrickplayEntry> {
+    TRICKPLAY_INDEX.get(media_source_id).map(|e| e.value().clone())
+}
+
+/// 条目下任意已生成缩略图的 mediaSourceId（请求未带 MediaSourceId 时使用）
+pub fn find_media_source(item_guid: &str) -> Option<String> {
+    TRICKPLAY_INDEX
+        .iter()
+        .find(|e| e.item_guid == item_guid)
+        .map(|e| e.key().clone())
+}
+
+/// 拼图文件路径
+pub fn tile_path(media_source_id: &str, width: i32, index: usize) -> Option<PathBuf> {
+    if !is_safe_id(media_source_id) {
+        return None;
+    }
+    let path = resolution_dir(media_source_id, width).join(format!("{}.jpg", index));
+    path.is_file().then_some(path)
+}
+
+/// 拼图授权标签的有效期（秒）：播放列表中的拼图 URL 不带访问令牌，只带短期签名标签
+const TILE_TAG_TTL_SECS: u64 = 6 * 3600;
+
+/// 拼图授权标签中的哈希（同一版本、同一宽度的拼图共用）
+fn tile_tag_hash(media_source_id: &str, width: i32) -> String {
+    image_tag(&format!("trickplay:{}:{}", media_source_id, width))
+}
+
+/// 签发拼图授权标签
+pub fn sign_tile_tag(media_source_id: &str, width: i32) -> String {
+    sign_short_lived_tag(&tile_tag_hash(media_source_id, width), TILE_TAG_TTL_SECS)
+}
+
+/// 标签是否授权读取该版本、该宽度的拼图
+pub fn tile_tag_authorizes(tag: &str, media_source_id: &str, width: i32) -> bool {
+    tag_authorizes(tag, &tile_tag_hash(media_source_id, width))
+}
+
+/// HLS 图片播放列表（EXT-X-IMAGES-ONLY），图片 URL 相对于播放列表，以短期签名标签授权
+pub fn tiles_playlist(info: &TrickplayInfoDto, media_source_id: &str, tag: &str) -> String {
+    let per_tile = (info.tile_width * info.tile_height).max(1);
+    let thumb_seconds = info.interval as f64 / 1000.0;
+    let tile_count = (info.thumbnail_count + per_tile - 1) / per_tile;
+
+    let mut out = String::from("#EXTM3U\n");
+    out.push_str(&format!(
+        "#EXT-X-TARGETDURATION:{}\n",
+        (thumb_seconds * per_tile as f64).ceil() as i64
+    ));
+    out.push_str("#EXT-X-VERSION:7\n#EXT-X-MEDIA-SEQUENCE:1\n#EXT-X-PLAYLIST-TYPE:VOD\n#EXT-X-IMAGES-ONLY\n");
+    for i in 0..tile_count {
+        // 只有最后一张拼图可能不满
+        let thumbs = if i == tile_count - 1 { info.thumbnail_count - i * per_tile } else { per_tile };
+        out.push_str(&format!("#EXTINF:{},\n", format_decimal(thumb_seconds * thumbs as f64)));
+        out.push_str(&format!(
+            "#EXT-X-TILES:RESOLUTION={}x{},LAYOUT={}x{},DURATION={}\n",
+            info.width, info.height, info.tile_width, info.tile_height, format_decimal(thumb_seconds)
+        ));
+        out.push_str(&format!("{}.jpg?MediaSourceId={}&tag={}\n", i, media_source_id, tag));
+    }
+    out.push_str("#EXT-X-ENDLIST\n");
+    out
+}
+
+fn format_decimal(value: f64) -> String {
+    let s = format!("{:.3}", value);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// 缓存未命中时在后台生成缩略图
+/// `access_token` 用于 ffmpeg 访问桥接层自身的直链代理
+pub fn schedule_trickplay(
+    config: &BridgeConfig,
+    access_token: &str,
+    item_id: &str,
+    item_guid: &str,
+    media_source_id: &str,
+    duration: f64,
+) {
+    if !ffmpeg_available()
+        || !config.enable_trickplay
+        || access_token.is_empty()
+        || !is_safe_id(media_source_id)
+        || TRICKPLAY_INDEX.contains_key(media_source_id)
+    {
+        return;
+    }
+    match TRICKPLAY_JOBS.entry(media_source_id.to_string()) {
+        dashmap::Entry::Occupied(_) => return,
+        dashmap::Entry::Vacant(slot) => {
+            slot.insert(JobState::Running);
+        }
+    }
+
+    let input_url = stream_input_url(config, item_id, media_source_id);
+    let access_token = access_token.to_string();
+    let ffmpeg = config.ffmpeg_path.clone();
+    let item_guid = item_guid.to_string();
+    let media_source_id = media_source_id.to_string();
+
+    tokio::spawn(async move {
+        let Some(_permit) = acquire_ffmpeg_slot().await else {
+            return;
+        };
+        let ticket = FfmpegTicket::issue(&access_token, &media_source_id);
+        debug!("[TRICKPLAY] 开始生成: mediaSourceId={}", media_source_id);
+        match generate(&ffmpeg, &input_url, &ticket.header(), &item_guid, &media_source_id, duration).await {
+            Some(entry) => {
+                info!("[TRICKPLAY] 生成完成: mediaSourceId={}", media_source_id);
+                TRICKPLAY_INDEX.insert(media_source_id.clone(), entry);
+                TRICKPLAY_JOBS.remove(&media_source_id);
+            }
+            None => {
+                TRICKPLAY_JOBS.insert(media_source_id, JobState::Failed);
+            }
+        }
+    });
+}
+
+/// ffmpeg 读取桥接层自身直链代理的地址
+pub(crate) fn stream_input_url(config: &BridgeConfig, item_id: &str, media_source_id: &str) -> String {
+    format!(
+        "http://127.0.0.1:{}/Videos/{}/stream?static=true&mediaSourceId={}",
+        config.port, item_id, media_source_id
+    )
+}
+
+/// ffmpeg 任务期间有效的票据，释放时作废
+pub(crate) struct FfmpegTicket(String);
+
+impl FfmpegTicket {
+    pub(crate) fn issue(access_token: &str, media_source_id: &str) -> Self {
+        let ticket = uuid::Uuid::new_v4().simple().to_string();
+        FFMPEG_TICKETS.insert(ticket.clone(), (access_token.to_string(), media_source_id.to_string()));
+        Self(ticket)
+    }
+
+    /// 通过 -headers 传给 ffmpeg，不出现在 URL 中（ffmpeg 报错与请求日志会输出 URL）
+    pub(crate) fn header(&self) -> String {
+        format!("X-Emby-Token: {}\r\n", self.0)
+    }
+}
+
+impl Drop for FfmpegTicket {
+    fn drop(&mut self) {
+        FFMPEG_TICKETS.remove(&self.0);
+    }
+}
+
+/// 票据 → 访问令牌（仅直链代理使用，且只对签发时的 mediaSourceId 有效）
+pub fn resolve_ffmpeg_ticket(ticket: &str, media_source_id: &str) -> Option<String> {
+    FFMPEG_TICKETS
+        .get(ticket)
+        .filter(|t| t.value().1 == media_source_id)
+        .map(|t| t.value().0.clone())
+}
+
+/// ffmpeg 是否可用（启动失败后不再调度任务）
+pub(crate) fn ffmpeg_available() -> bool {
+    !FFMPEG_MISSING.load(Ordering::Relaxed)
+}
+
+/// 获取 ffmpeg 任务槽位，同时只运行一个 ffmpeg 任务
+pub(crate) async fn acquire_ffmpeg_slot() -> Option<tokio::sync::SemaphorePermit<'static>> {
+    JOB_PERMITS.acquire().await.ok()
+}
+
+/// 运行 ffmpeg，失败时记录 stderr；找不到可执行文件时禁用后续任务
+pub(crate) async fn run_ffmpeg(ffmpeg: &str, args: &[std::ffi::OsString], context: &str) -> bool {
+    let output = tokio::process::Command::new(ffmpeg)
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await;
+    match output {
+        Ok(o) if o.status.success() => true,
+        Ok(o) => {
+            warn!("[FFMPEG] 执行失败: {}, {}", context, String::from_utf8_lossy(&o.stderr).trim());
+            false
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            warn!("[FFMPEG] 未找到 ffmpeg（{}），缩略图生成已禁用", ffmpeg);
+            FFMPEG_MISSING.store(true, Ordering::Relaxed);
+            false
+        }
+        Err(e) => {
+            warn!("[FFMPEG] 启动失败: {}", e);
+            false
+        }
+    }
+}
+
+/// 运行 ffmpeg 抽帧拼图并写入索引
+async fn generate(
+    ffmpeg: &str,
+    input_url: &str,
+    auth_header: &str,
+    item_guid: &str,
+    media_source_id: &str,
+    duration: f64,
+) -> Option<TrickplayEntry> {
+    let final_dir = resolution_dir(media_source_id, TRICKPLAY_WIDTH);
+    let tmp_dir = trickplay_root().join(format!("{}.tmp", media_source_id));
+    let _ = std::fs::remove_dir_all(&tmp_dir);
+    std::fs::create_dir_all(&tmp_dir).ok()?;
+
+    // 只解码关键帧，按间隔取帧缩放后拼成 10x10 的大图
+    let filter = format!(
+        "fps=1000/{},scale={}:-2,tile={}x{}",
+        INTERVAL_MS, TRICKPLAY_WIDTH, TILE_WIDTH, TILE_HEIGHT
+    );
+    let args: Vec<std::ffi::OsString> = [
+        "-hide_banner", "-loglevel", "error", "-nostdin", "-threads", "1",
+        "-skip_frame", "nokey", "-headers", auth_header, "-i", input_url,
+        "-an", "-sn", "-dn", "-vf", &filter, "-q:v", "5",
+        "-f", "image2", "-start_number", "0",
+    ]
+    .iter()
+    .map(Into::into)
+    .chain(std::iter::once(tmp_dir.join("%d.jpg").into_os_string()))
+    .collect();
+    if !run_ffmpeg(ffmpeg, &args, media_source_id).await {
+        let _ = std::fs::remove_dir_all(&tmp_dir);
+        return None;
+    }
+
+    let info = describe_tiles(&tmp_dir, duration)?;
+    let _ = std::fs::remove_dir_all(&final_dir);
+    std::fs::create_dir_all(final_dir.parent()?).ok()?;
+    std::fs::rename(&tmp_dir, &final_dir).ok()?;
+
+    let mut resolutions = BTreeMap::new();
+    resolutions.insert(TRICKPLAY_WIDTH.to_string(), info);
+    let entry = TrickplayEntry { item_guid: item_guid.to_string(), resolutions };
+    if let Ok(json) = serde_json::to_string_pretty(&entry) {
+        if let Err(e) = std::fs::write(trickplay_root().join(media_source_id).join("info.json"), json) {
+            warn!("[TRICKPLAY] 保存缩略图索引失败: {}", e);
+        }
+    }
+    Some(entry)
+}
+
+/// 根据生成的拼图计算 TrickplayInfo
+fn describe_tiles(dir: &std::path::Path, duration: f64) -> Option<TrickplayInfoDto> {
+    let mut sizes = Vec::new();
+    while let Ok(meta) = std::fs::metadata(dir.join(format!("{}.jpg", sizes.len()))) {
+        sizes.push(meta.len());
+    }
+    if sizes.is_empty() {
+        return None;
+    }
+
+    let first = std::fs::read(dir.join("0.jpg")).ok()?;
+    let (image_width, image_height) = jpeg_dimensions(&first)?;
+    let per_tile = TILE_WIDTH * TILE_HEIGHT;
+    let tiles = sizes.len() as i32;
+
+    // 最后一张拼图的实际张数无法从图片得知，按时长估算
+    let estimated = (duration * 1000.0 / INTERVAL_MS as f64).ceil() as i32;
+    let thumbnail_count = if estimated > 0 {
+        estimated.clamp((tiles - 1) * per_tile + 1, tiles * per_tile)
+    } else {
+        tiles * per_tile
+    };
+
+    let tile_seconds = (per_tile * INTERVAL_MS) as f64 / 1000.0;
+    let max_size = sizes.iter().copied().max().unwrap_or(0);
+    Some(TrickplayInfoDto {
+        width: image_width as i32 / TILE_WIDTH,
+        height: image_height as i32 / TILE_HEIGHT,
+        tile_width: TILE_WIDTH,
+        tile_height: TILE_HEIGHT,
+        thumbnail_count,
+        interval: INTERVAL_MS,
+        bandwidth: (max_size as f64 * 8.0 / tile_seconds).ceil() as i32,
+    })
+}
+
+/// 读取 JPEG 的 SOF 段得到 (宽, 高)
+pub fn jpeg_dimensions(data: &[u8]) -> Option<(u16, u16)> {
+    if data.get(..2)? != [0xFF, 0xD8] {
+        return None;
+    }
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            pos += 1;
+            continue;
+        }
+        let marker = data[pos + 1];
+        // 填充字节与无长度的标记
+        if marker == 0xFF || marker == 0x01 || (0xD0..=0xD9).contains(&marker) {
+            pos += if marker == 0xFF { 1 } else { 2 };
+            continue;
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let is_sof = (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+        if is_sof {
+            let seg = data.get(pos + 4..pos + 9)?;
+            let height = u16::from_be_bytes([seg[1], seg[2]]);
+            let width = u16::from_be_bytes([seg[3], seg[4]]);
+            return Some((width, height));
+        }
+        pos += 2 + len;
+    }
+    None
+}
@@ -0,0 +1,53 @@
+/// 用户配置持久化
+/// 保存 Jellyfin 客户端提交的 UserConfiguration（音频/字幕语言偏好等）
+
+use dashmap::DashMap;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tracing::{info, warn};
+
+use crate::types::jellyfin::UserConfiguration;
+
+/// user_id → UserConfiguration
+static USER_CONFIGS: LazyLock<DashMap<String, UserConfiguration>> = LazyLock::new(|| {
+    let map = DashMap::new();
+    if let Some(data) = load_configs_from_file() {
+        for (k, v) in data {
+            map.insert(k, v);
+        }
+        info!("[USER-CONFIG] 已恢复 {} 个用户配置", map.len());
+    }
+    map
+});
+
+fn config_file_path() -> PathBuf {
+    PathBuf::from(".user_configs.json")
+}
+
+fn load_configs_from_file() -> Option<std::collections::HashMap<String, UserConfiguration>> {
+    let content = std::fs::read_to_string(config_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_configs() {
+    let mut data = std::collections::HashMap::new();
+    for entry in USER_CONFIGS.iter() {
+        data.insert(entry.key().clone(), entry.value().clone());
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&data) {
+        if let Err(e) = std::fs::write(config_file_path(), json) {
+            warn!("[USER-CONFIG] 保存用户配置失败: {}", e);
+        }
+    }
+}
+
+/// 获取已保存的用户配置
+pub fn get_user_config(user_id: &str) -> Option<UserConfiguration> {
+    USER_CONFIGS.get(&user_id.to_lowercase()).map(|v| v.clone())
+}
+
+/// 保存用户配置
+pub fn set_user_config(user_id: &str, config: UserConfiguration) {
+    USER_CONFIGS.insert(user_id.to_lowercase(), config);
+    save_configs();
+}
@@ -33,9 +33,20 @@ pub struct FnosPlayInfo {
     #[serde(default)]
     pub media_guid: String,
     #[serde(default)]
+    pub play_config: FnosPlayConfig,
+    #[serde(default)]
     pub item: FnosItemDetail,
 }
 
+/// 播放配置（用户为剧集设置的跳过片头/片尾，单位秒）
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FnosPlayConfig {
+    #[serde(default)]
+    pub skip_opening: Option<f64>,
+    #[serde(default)]
+    pub skip_ending: Option<f64>,
+}
+
 /// 项目详情
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct FnosItemDetail {
@@ -49,6 +60,9 @@ pub struct FnosItemDetail {
     pub parent_title: String,
     #[serde(default)]
     pub title: String,
+    /// 用户在飞牛中设置的排序标题
+    #[serde(default)]
+    pub sort_title: String,
     #[serde(default)]
     pub posters: String,
     #[serde(default)]
@@ -122,6 +136,12 @@ pub struct FnosPlayListItem {
     pub parent_title: String,
     #[serde(default)]
     pub title: String,
+    /// 原标题（外文片名）
+    #[serde(default)]
+    pub original_title: String,
+    /// 用户在飞牛中设置的排序标题
+    #[serde(default)]
+    pub sort_title: String,
     #[serde(rename = "type", default)]
     pub item_type: String,
     #[serde(default)]
@@ -176,6 +196,14 @@ pub struct FnosPlayListItem {
     pub file_name: String,
 }
 
+impl FnosPlayListItem {
+    /// 首映年份（air_date 的年份部分）
+    pub fn year(&self) -> Option<i32> {
+        self.air_date.split('-').next()?.parse::<i32>().ok().filter(|y| *y > 0)
+    }
+
+}
+
 /// 项目列表响应
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct FnosItemListResponse {
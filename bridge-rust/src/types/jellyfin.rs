@@ -68,6 +68,8 @@ pub struct UserDto {
     pub server_id: String,
     #[serde(rename = "Id")]
     pub id: String,
+    #[serde(rename = "PrimaryImageTag", skip_serializing_if = "Option::is_none")]
+    pub primary_image_tag: Option<String>,
     #[serde(rename = "HasPassword")]
     pub has_password: bool,
     #[serde(rename = "HasConfiguredPassword")]
@@ -88,6 +90,8 @@ pub struct UserDto {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserConfiguration {
+    #[serde(rename = "AudioLanguagePreference", default)]
+    pub audio_language_preference: String,
     #[serde(rename = "PlayDefaultAudioTrack")]
     pub play_default_audio_track: bool,
     #[serde(rename = "SubtitleLanguagePreference")]
@@ -112,6 +116,9 @@ pub struct UserConfiguration {
     pub remember_subtitle_selections: bool,
     #[serde(rename = "EnableNextEpisodeAutoPlay")]
     pub enable_next_episode_auto_play: bool,
+    /// 转码 HDR / 杜比视界片源时总是映射为 SDR（不依赖客户端 DeviceProfile）
+    #[serde(rename = "ForceSdrTranscoding", default)]
+    pub force_sdr_transcoding: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -146,6 +153,8 @@ pub struct UserPolicy {
     pub enable_content_deletion: bool,
     #[serde(rename = "EnableContentDownloading")]
     pub enable_content_downloading: bool,
+    #[serde(rename = "EnableSubtitleManagement")]
+    pub enable_subtitle_management: bool,
     #[serde(rename = "EnableSyncTranscoding")]
     pub enable_sync_transcoding: bool,
     #[serde(rename = "EnableMediaConversion")]
@@ -156,6 +165,8 @@ pub struct UserPolicy {
     pub enable_all_channels: bool,
     #[serde(rename = "EnableAllFolders")]
     pub enable_all_folders: bool,
+    #[serde(rename = "EnabledFolders")]
+    pub enabled_folders: Vec<String>,
     #[serde(rename = "EnablePublicSharing")]
     pub enable_public_sharing: bool,
     #[serde(rename = "InvalidLoginAttemptCount")]
@@ -228,6 +239,55 @@ pub struct PlayStateInfo {
     pub repeat_mode: String,
 }
 
+/// DisplayPreferences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplayPreferencesDto {
+    #[serde(rename = "Id", default)]
+    pub id: String,
+    #[serde(rename = "ViewType", default, skip_serializing_if = "Option::is_none")]
+    pub view_type: Option<String>,
+    #[serde(rename = "SortBy", default = "default_sort_by")]
+    pub sort_by: String,
+    #[serde(rename = "IndexBy", default, skip_serializing_if = "Option::is_none")]
+    pub index_by: Option<String>,
+    #[serde(rename = "RememberIndexing", default)]
+    pub remember_indexing: bool,
+    #[serde(rename = "PrimaryImageHeight", default)]
+    pub primary_image_height: i32,
+    #[serde(rename = "PrimaryImageWidth", default)]
+    pub primary_image_width: i32,
+    #[serde(rename = "CustomPrefs", default)]
+    pub custom_prefs: std::collections::BTreeMap<String, Option<String>>,
+    #[serde(rename = "ScrollDirection", default = "default_scroll_direction")]
+    pub scroll_direction: String,
+    #[serde(rename = "ShowBackdrop", default = "default_true")]
+    pub show_backdrop: bool,
+    #[serde(rename = "RememberSorting", default)]
+    pub remember_sorting: bool,
+    #[serde(rename = "SortOrder", default = "default_sort_order")]
+    pub sort_order: String,
+    #[serde(rename = "ShowSidebar", default)]
+    pub show_sidebar: bool,
+    #[serde(rename = "Client", default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+}
+
+fn default_sort_by() -> String {
+    "SortName".into()
+}
+
+fn default_sort_order() -> String {
+    "Ascending".into()
+}
+
+fn default_scroll_direction() -> String {
+    "Horizontal".into()
+}
+
+fn default_true() -> bool {
+    true
+}
+
 /// Jellyfin Authorization 头解析结果
 #[derive(Debug, Clone, Default)]
 pub struct JellyfinAuthHeader {
@@ -243,6 +303,12 @@ pub struct JellyfinAuthHeader {
 pub struct BaseItemDto {
     #[serde(rename = "Name")]
     pub name: String,
+    #[serde(rename = "OriginalTitle", skip_serializing_if = "Option::is_none")]
+    pub original_title: Option<String>,
+    #[serde(rename = "SortName", skip_serializing_if = "Option::is_none")]
+    pub sort_name: Option<String>,
+    #[serde(rename = "ForcedSortName", skip_serializing_if = "Option::is_none")]
+    pub forced_sort_name: Option<String>,
     #[serde(rename = "ServerId")]
     pub server_id: String,
     #[serde(rename = "Id")]
@@ -267,6 +333,23 @@ pub struct BaseItemDto {
     pub image_tags: Option<serde_json::Value>,
     #[serde(rename = "BackdropImageTags", skip_serializing_if = "Option::is_none")]
     pub backdrop_image_tags: Option<Vec<String>>,
+    #[serde(rename = "SeriesPrimaryImageTag", skip_serializing_if = "Option::is_none")]
+    pub series_primary_image_tag: Option<String>,
+    #[serde(rename = "ParentBackdropItemId", skip_serializing_if = "Option::is_none")]
+    pub parent_backdrop_item_id: Option<String>,
+    #[serde(rename = "ParentBackdropImageTags", skip_serializing_if = "Option::is_none")]
+    pub parent_backdrop_image_tags: Option<Vec<String>>,
+    #[serde(rename = "ParentThumbItemId", skip_serializing_if = "Option::is_none")]
+    pub parent_thumb_item_id: Option<String>,
+    #[serde(rename = "ParentThumbImageTag", skip_serializing_if = "Option::is_none")]
+    pub parent_thumb_image_tag: Option<String>,
+    #[serde(rename = "ParentLogoItemId", skip_serializing_if = "Option::is_none")]
+    pub parent_logo_item_id: Option<String>,
+    #[serde(rename = "ParentLogoImageTag", skip_serializing_if = "Option::is_none")]
+    pub parent_logo_image_tag: Option<String>,
+    /// 图片类型 → 标签 → BlurHash
+    #[serde(rename = "ImageBlurHashes", skip_serializing_if = "Option::is_none")]
+    pub image_blur_hashes: Option<serde_json::Value>,
     #[serde(rename = "LocationType", skip_serializing_if = "Option::is_none")]
     pub location_type: Option<String>,
     #[serde(rename = "UserData", skip_serializing_if = "Option::is_none")]
@@ -275,6 +358,12 @@ pub struct BaseItemDto {
     pub index_number: Option<i32>,
     #[serde(rename = "ParentIndexNumber", skip_serializing_if = "Option::is_none")]
     pub parent_index_number: Option<i32>,
+    #[serde(rename = "AirsBeforeSeasonNumber", skip_serializing_if = "Option::is_none")]
+    pub airs_before_season_number: Option<i32>,
+    #[serde(rename = "AirsAfterSeasonNumber", skip_serializing_if = "Option::is_none")]
+    pub airs_after_season_number: Option<i32>,
+    #[serde(rename = "AirsBeforeEpisodeNumber", skip_serializing_if = "Option::is_none")]
+    pub airs_before_episode_number: Option<i32>,
     #[serde(rename = "SeriesId", skip_serializing_if = "Option::is_none")]
     pub series_id: Option<String>,
     #[serde(rename = "SeriesName", skip_serializing_if = "Option::is_none")]
@@ -291,12 +380,93 @@ pub struct BaseItemDto {
     pub collection_type: Option<String>,
     #[serde(rename = "PremiereDate", skip_serializing_if = "Option::is_none")]
     pub premiere_date: Option<String>,
+    #[serde(rename = "DateCreated", skip_serializing_if = "Option::is_none")]
+    pub date_created: Option<String>,
+    #[serde(rename = "DateLastMediaAdded", skip_serializing_if = "Option::is_none")]
+    pub date_last_media_added: Option<String>,
     #[serde(rename = "ProductionYear", skip_serializing_if = "Option::is_none")]
     pub production_year: Option<i32>,
+    #[serde(rename = "IsHD", skip_serializing_if = "Option::is_none")]
+    pub is_hd: Option<bool>,
+    #[serde(rename = "Width", skip_serializing_if = "Option::is_none")]
+    pub width: Option<i32>,
+    #[serde(rename = "Height", skip_serializing_if = "Option::is_none")]
+    pub height: Option<i32>,
+    #[serde(rename = "VideoType", skip_serializing_if = "Option::is_none")]
+    pub video_type: Option<String>,
+    #[serde(rename = "Video3DFormat", skip_serializing_if = "Option::is_none")]
+    pub video_3d_format: Option<String>,
+    /// 主视频流的动态范围（SDR / HDR）与类型（SDR / HDR10 / HLG / DOVI 等）
+    #[serde(rename = "VideoRange", skip_serializing_if = "Option::is_none")]
+    pub video_range: Option<String>,
+    #[serde(rename = "VideoRangeType", skip_serializing_if = "Option::is_none")]
+    pub video_range_type: Option<String>,
     #[serde(rename = "MediaSources", skip_serializing_if = "Option::is_none")]
     pub media_sources: Option<Vec<serde_json::Value>>,
     #[serde(rename = "MediaStreams", skip_serializing_if = "Option::is_none")]
     pub media_streams: Option<Vec<serde_json::Value>>,
+    #[serde(rename = "PlaylistItemId", skip_serializing_if = "Option::is_none")]
+    pub playlist_item_id: Option<String>,
+    #[serde(rename = "Chapters", skip_serializing_if = "Option::is_none")]
+    pub chapters: Option<Vec<ChapterInfoDto>>,
+    /// mediaSourceId → 宽度 → 缩略图信息
+    #[serde(rename = "Trickplay", skip_serializing_if = "Option::is_none")]
+    pub trickplay: Option<std::collections::BTreeMap<String, std::collections::BTreeMap<String, TrickplayInfoDto>>>,
+}
+
+/// ChapterInfo
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ChapterInfoDto {
+    #[serde(rename = "StartPositionTicks")]
+    pub start_position_ticks: i64,
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// 章节图片已生成时才有，图片地址 /Items/{id}/Images/Chapter/{index}
+    #[serde(rename = "ImageTag", skip_serializing_if = "Option::is_none")]
+    pub image_tag: Option<String>,
+    #[serde(rename = "ImageDateModified")]
+    pub image_date_modified: String,
+}
+
+/// MediaSegmentDto — 片头/片尾等可跳过片段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaSegmentDto {
+    #[serde(rename = "Id")]
+    pub id: String,
+    #[serde(rename = "ItemId")]
+    pub item_id: String,
+    /// Unknown / Commercial / Preview / Recap / Outro / Intro
+    #[serde(rename = "Type")]
+    pub segment_type: String,
+    #[serde(rename = "StartTicks")]
+    pub start_ticks: i64,
+    #[serde(rename = "EndTicks")]
+    pub end_ticks: i64,
+}
+
+/// TrickplayInfoDto — 拖动进度条时的缩略图拼图信息
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TrickplayInfoDto {
+    /// 单张缩略图宽度
+    #[serde(rename = "Width")]
+    pub width: i32,
+    /// 单张缩略图高度
+    #[serde(rename = "Height")]
+    pub height: i32,
+    /// 每张拼图的列数
+    #[serde(rename = "TileWidth")]
+    pub tile_width: i32,
+    /// 每张拼图的行数
+    #[serde(rename = "TileHeight")]
+    pub tile_height: i32,
+    #[serde(rename = "ThumbnailCount")]
+    pub thumbnail_count: i32,
+    /// 缩略图间隔（毫秒）
+    #[serde(rename = "Interval")]
+    pub interval: i32,
+    /// 拼图峰值码率（bit/s）
+    #[serde(rename = "Bandwidth")]
+    pub bandwidth: i32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -313,6 +483,58 @@ pub struct UserItemDataDto {
     pub played_percentage: Option<f64>,
     #[serde(rename = "UnplayedItemCount", skip_serializing_if = "Option::is_none")]
     pub unplayed_item_count: Option<i32>,
+    #[serde(rename = "LastPlayedDate", skip_serializing_if = "Option::is_none")]
+    pub last_played_date: Option<String>,
+}
+
+/// 搜索提示（/Search/Hints）
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SearchHint {
+    #[serde(rename = "ItemId")]
+    pub item_id: String,
+    #[serde(rename = "Id")]
+    pub id: String,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "MatchedTerm", skip_serializing_if = "Option::is_none")]
+    pub matched_term: Option<String>,
+    #[serde(rename = "IndexNumber", skip_serializing_if = "Option::is_none")]
+    pub index_number: Option<i32>,
+    #[serde(rename = "ParentIndexNumber", skip_serializing_if = "Option::is_none")]
+    pub parent_index_number: Option<i32>,
+    #[serde(rename = "ProductionYear", skip_serializing_if = "Option::is_none")]
+    pub production_year: Option<i32>,
+    #[serde(rename = "PrimaryImageTag", skip_serializing_if = "Option::is_none")]
+    pub primary_image_tag: Option<String>,
+    #[serde(rename = "ThumbImageTag", skip_serializing_if = "Option::is_none")]
+    pub thumb_image_tag: Option<String>,
+    #[serde(rename = "ThumbImageItemId", skip_serializing_if = "Option::is_none")]
+    pub thumb_image_item_id: Option<String>,
+    #[serde(rename = "BackdropImageTag", skip_serializing_if = "Option::is_none")]
+    pub backdrop_image_tag: Option<String>,
+    #[serde(rename = "BackdropImageItemId", skip_serializing_if = "Option::is_none")]
+    pub backdrop_image_item_id: Option<String>,
+    #[serde(rename = "Type")]
+    pub item_type: String,
+    #[serde(rename = "IsFolder")]
+    pub is_folder: bool,
+    #[serde(rename = "RunTimeTicks", skip_serializing_if = "Option::is_none")]
+    pub run_time_ticks: Option<i64>,
+    #[serde(rename = "MediaType", skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    #[serde(rename = "Series", skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>,
+    #[serde(rename = "PrimaryImageAspectRatio", skip_serializing_if = "Option::is_none")]
+    pub primary_image_aspect_ratio: Option<f64>,
+}
+
+/// 搜索提示结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHintResult {
+    #[serde(rename = "SearchHints")]
+    pub search_hints: Vec<SearchHint>,
+    #[serde(rename = "TotalRecordCount")]
+    pub total_record_count: i64,
 }
 
 /// Items 查询结果
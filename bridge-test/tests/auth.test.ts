@@ -4,6 +4,7 @@
 
 import { describe, it, before, after } from 'node:test';
 import assert from 'node:assert';
+import axios from 'axios';
 import { get, post, assertSuccess, assertStatus } from '../lib/client.ts';
 import { login, logout, getCurrentUser, getUser, isLoggedIn, skipIfNoCredentials } from '../lib/auth-helper.ts';
 import { config, testState } from '../config.ts';
@@ -126,9 +127,89 @@ describe('Auth & Users API', () => {
         
         const response = await get('/Users/invalid-user-id');
         // 可能返回 404 或空数据
-        assert.ok(response.status === 200 || response.status === 404, 
+        assert.ok(response.status === 200 || response.status === 404,
           `期望 200 或 404，实际得到 ${response.status}`);
       });
+
+      it('PrimaryImageTag 存在时应该能获取用户头像', async () => {
+        if (!isLoggedIn()) {
+          await login();
+        }
+
+        const user = await getUser(testState.userId!);
+        const response = await get(`/Users/${testState.userId}/Images/Primary`, {
+          responseType: 'arraybuffer',
+        });
+
+        if (user.PrimaryImageTag) {
+          assertStatus(response, 200);
+        } else {
+          assertStatus(response, 404);
+        }
+      });
+
+      it('未开启公开用户列表时，匿名请求不应该取到用户头像', async () => {
+        if (!isLoggedIn()) {
+          await login();
+        }
+
+        const publicUsers = await get('/Users/Public');
+        if (publicUsers.data?.length > 0) return; // PUBLIC_USER_LIST 已开启
+
+        const response = await axios.get(`${config.baseURL}/Users/${testState.userId}/Images/Primary`, {
+          responseType: 'arraybuffer',
+          validateStatus: () => true,
+        });
+        assert.strictEqual(response.status, 401);
+      });
+
+      it('IsAdministrator 在 /Users 与 /Users/Me 中应该一致', async () => {
+        if (!isLoggedIn()) {
+          await login();
+        }
+
+        const me = await get('/Users/Me');
+        const users = await get('/Users');
+        assertSuccess(users);
+        const listed = users.data!.find((u: any) => u.Id === me.data!.Id);
+        if (!listed) return;
+        assert.strictEqual(listed.Policy?.IsAdministrator, me.data!.Policy?.IsAdministrator);
+      });
+    });
+  });
+
+  describe('POST /Users/:userId/Configuration（已认证）', () => {
+    skipIfNoCredentials(() => {
+      it('应该保存语言偏好并在用户信息中返回', async () => {
+        if (!isLoggedIn()) {
+          await login();
+        }
+
+        const response = await post(`/Users/${testState.userId}/Configuration`, {
+          AudioLanguagePreference: 'jpn',
+          SubtitleLanguagePreference: 'chi',
+          SubtitleMode: 'Always',
+          PlayDefaultAudioTrack: false,
+        });
+        assertStatus(response, 204);
+
+        const user = await getCurrentUser();
+        assert.strictEqual(user.Configuration.AudioLanguagePreference, 'jpn');
+        assert.strictEqual(user.Configuration.SubtitleLanguagePreference, 'chi');
+        assert.strictEqual(user.Configuration.SubtitleMode, 'Always');
+        assert.strictEqual(user.Configuration.PlayDefaultAudioTrack, false);
+      });
+
+      it('应该拒绝修改其他用户的配置', async () => {
+        if (!isLoggedIn()) {
+          await login();
+        }
+
+        const response = await post('/Users/00000000000000000000000000000000/Configuration', {
+          SubtitleMode: 'None',
+        });
+        assertStatus(response, 403);
+      });
     });
   });
 
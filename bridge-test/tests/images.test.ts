@@ -5,7 +5,8 @@
 
 import { describe, it, before } from 'node:test';
 import assert from 'node:assert';
-import { get, assertSuccess, assertStatus } from '../lib/client.ts';
+import axios from 'axios';
+import { client, get, assertSuccess, assertStatus } from '../lib/client.ts';
 import { login, isLoggedIn, skipIfNoCredentials } from '../lib/auth-helper.ts';
 import { config, testState } from '../config.ts';
 
@@ -201,4 +202,169 @@ describe('Images API', () => {
       });
     });
   });
+
+  describe('图片缩放与 ETag', () => {
+    skipIfNoCredentials(() => {
+      it('应该按 maxWidth 缩放并转换格式', async () => {
+        if (!testItemId) return;
+
+        const response = await client.get(`/Items/${testItemId}/Images/Primary?maxWidth=120&format=png`, {
+          responseType: 'arraybuffer',
+          validateStatus: () => true,
+        });
+        if (response.status === 404) {
+          console.log('  [SKIP] 该项目没有封面图');
+          return;
+        }
+        assert.strictEqual(response.status, 200);
+
+        const data = Buffer.from(response.data);
+        if (response.headers['content-type'] === 'image/png') {
+          // IHDR 中的宽度
+          assert.ok(data.readUInt32BE(16) <= 120, '宽度不应超过 maxWidth');
+        }
+        console.log(`  ✓ ${response.headers['content-type']}, ${data.length} bytes`);
+      });
+
+      it('携带 If-None-Match 时应该返回 304', async () => {
+        if (!testItemId) return;
+
+        const url = `/Items/${testItemId}/Images/Primary?fillWidth=200&quality=80`;
+        const first = await client.get(url, { responseType: 'arraybuffer', validateStatus: () => true });
+        if (first.status === 404) return;
+        assert.strictEqual(first.status, 200);
+
+        const etag = first.headers['etag'];
+        assert.ok(etag, '应该返回 ETag');
+
+        const second = await client.get(url, {
+          headers: { 'If-None-Match': etag },
+          validateStatus: () => true,
+        });
+        assert.strictEqual(second.status, 304);
+
+        // 参数不同则 ETag 不同
+        const other = await client.get(`/Items/${testItemId}/Images/Primary?fillWidth=100&quality=80`, {
+          responseType: 'arraybuffer',
+          validateStatus: () => true,
+        });
+        assert.notStrictEqual(other.headers['etag'], etag);
+      });
+    });
+  });
+
+  describe('图片类型与索引', () => {
+    skipIfNoCredentials(() => {
+      it('BackdropImageTags 与 Backdrop/{index} 一致，没有 Logo 标签时返回 404', async () => {
+        if (!testItemId) return;
+
+        const itemResponse = await get(`/Items/${testItemId}`);
+        if (!itemResponse.success) return;
+        const item = itemResponse.data;
+        const backdrops: string[] = item.BackdropImageTags || [];
+
+        for (let i = 0; i < backdrops.length; i++) {
+          const response = await get(`/Items/${testItemId}/Images/Backdrop/${i}?tag=${backdrops[i]}`, {
+            responseType: 'arraybuffer',
+          });
+          assertStatus(response, 200);
+        }
+        const beyond = await get(`/Items/${testItemId}/Images/Backdrop/${backdrops.length}`);
+        assertStatus(beyond, 404);
+
+        if (!item.ImageTags?.Logo) {
+          const logo = await get(`/Items/${testItemId}/Images/Logo`);
+          assertStatus(logo, 404);
+        }
+        console.log(`  ✓ ${backdrops.length} 张背景图`);
+      });
+    });
+  });
+
+  describe('BlurHash', () => {
+    skipIfNoCredentials(() => {
+      it('请求过的主封面应该出现在 ImageBlurHashes 中', async () => {
+        if (!testItemId) return;
+
+        const itemResponse = await get(`/Items/${testItemId}`);
+        const tag = itemResponse.data?.ImageTags?.Primary;
+        if (!tag) return;
+
+        const image = await get(`/Items/${testItemId}/Images/Primary?tag=${tag}`, {
+          responseType: 'arraybuffer',
+        });
+        if (image.status !== 200) return;
+        // BlurHash 在后台计算
+        await new Promise((resolve) => setTimeout(resolve, 500));
+
+        const again = await get(`/Items/${testItemId}`);
+        assertSuccess(again);
+        const hash = again.data.ImageBlurHashes?.Primary?.[tag];
+        assert.ok(typeof hash === 'string' && hash.length >= 6, 'Primary 应该有 BlurHash');
+        console.log(`  ✓ BlurHash: ${hash}`);
+      });
+    });
+  });
+
+  describe('匿名图片请求', () => {
+    skipIfNoCredentials(() => {
+      it('不带认证头时，签名 tag 应该能取到未缓存的图片', async () => {
+        if (!testItemId) return;
+
+        const itemResponse = await get(`/Items/${testItemId}`);
+        const tag = itemResponse.data?.ImageTags?.Primary;
+        if (!tag) return;
+
+        // 随机尺寸，避开磁盘缓存，确保需要请求飞牛
+        const width = 100 + Math.floor(Math.random() * 200);
+        const response = await axios.get(
+          `${config.baseURL}/Items/${testItemId}/Images/Primary?tag=${encodeURIComponent(tag)}&maxWidth=${width}`,
+          { responseType: 'arraybuffer', validateStatus: () => true },
+        );
+        assert.strictEqual(response.status, 200);
+        assert.ok(String(response.headers['content-type']).startsWith('image/'));
+      });
+
+      it('同一时段内 ImageTag 应该稳定，篡改签名应该被拒绝', async () => {
+        if (!testItemId) return;
+
+        const first = await get(`/Items/${testItemId}`);
+        const tag = first.data?.ImageTags?.Primary;
+        if (!tag) return;
+        const second = await get(`/Users/${testState.userId}/Items/${testItemId}`);
+        assert.strictEqual(second.data?.ImageTags?.Primary, tag);
+        assert.match(tag, /^[0-9a-f]+-[0-9a-f]+-[0-9a-f]{16}$/);
+        const expiry = parseInt(tag.split('-')[1], 16);
+        assert.ok(expiry * 1000 > Date.now(), '签名标签应该带有未来的过期时间');
+
+        const forged = tag.replace(/.$/, (c: string) => (c === '0' ? '1' : '0'));
+        const width = 100 + Math.floor(Math.random() * 200);
+        const response = await axios.get(
+          `${config.baseURL}/Items/${testItemId}/Images/Primary?tag=${forged}&maxWidth=${width}`,
+          { responseType: 'arraybuffer', validateStatus: () => true },
+        );
+        assert.strictEqual(response.status, 401);
+      });
+
+      it('不带签名 tag 的匿名请求不应该读到已缓存的图片', async () => {
+        if (!testItemId) return;
+
+        const url = `/Items/${testItemId}/Images/Primary?fillWidth=200&quality=80`;
+        const cached = await client.get(url, { responseType: 'arraybuffer', validateStatus: () => true });
+        if (cached.status !== 200) return;
+
+        const anonymous = await axios.get(`${config.baseURL}${url}`, {
+          responseType: 'arraybuffer',
+          validateStatus: () => true,
+        });
+        assert.strictEqual(anonymous.status, 401);
+
+        const wildcard = await axios.get(`${config.baseURL}${url}`, {
+          headers: { 'If-None-Match': '*' },
+          validateStatus: () => true,
+        });
+        assert.strictEqual(wildcard.status, 401);
+      });
+    });
+  });
 });
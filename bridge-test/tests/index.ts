@@ -47,3 +47,4 @@ import './branding.test.ts';
 import './favorites.test.ts';
 import './resume.test.ts';
 import './misc.test.ts';
+import './playlists.test.ts';
@@ -117,6 +117,52 @@ describe('Items API', () => {
           }
         }
       });
+
+      it('按 SortName 排序时应该按拼音升序', async () => {
+        if (!movieLibraryId) return;
+
+        const response = await get(`/Items?ParentId=${movieLibraryId}&SortBy=SortName&SortOrder=Ascending&Limit=50`);
+        assertSuccess(response);
+        const names: string[] = response.data!.Items.map((item: any) => item.SortName);
+        for (const name of names) {
+          assert.strictEqual(typeof name, 'string', '应该包含 SortName');
+        }
+        for (let i = 1; i < names.length; i++) {
+          assert.ok(names[i - 1] <= names[i], `"${names[i - 1]}" 应该排在 "${names[i]}" 之前`);
+        }
+      });
+
+      it('应该支持字母跳转（NameStartsWith / NameLessThan）', async () => {
+        if (!movieLibraryId) return;
+
+        const startsWith = await get(`/Items?ParentId=${movieLibraryId}&NameStartsWith=C&Limit=50`);
+        assertSuccess(startsWith);
+        for (const item of startsWith.data!.Items) {
+          assert.ok(item.SortName.startsWith('c'), `"${item.Name}" 的 SortName "${item.SortName}" 应该以 c 开头`);
+        }
+
+        // jellyfin-web 的 "#" 使用 NameLessThan=A
+        const lessThan = await get(`/Items?ParentId=${movieLibraryId}&NameLessThan=A&Limit=50`);
+        assertSuccess(lessThan);
+        for (const item of lessThan.data!.Items) {
+          assert.ok(item.SortName < 'a', `"${item.Name}" 的 SortName "${item.SortName}" 应该小于 a`);
+        }
+        console.log(`  ✓ C 开头 ${startsWith.data!.TotalRecordCount} 条，# ${lessThan.data!.TotalRecordCount} 条`);
+      });
+
+      it('按 DateCreated 排序时应该按添加时间倒序', async () => {
+        if (!movieLibraryId) return;
+
+        const response = await get(`/Items?ParentId=${movieLibraryId}&SortBy=DateCreated&SortOrder=Descending&Limit=50`);
+        assertSuccess(response);
+        const dates: string[] = response.data!.Items.map((item: any) => item.DateCreated);
+        for (const date of dates) {
+          assert.ok(!Number.isNaN(Date.parse(date)), `DateCreated "${date}" 应该是有效日期`);
+        }
+        for (let i = 1; i < dates.length; i++) {
+          assert.ok(dates[i - 1] >= dates[i], `${dates[i - 1]} 应该晚于 ${dates[i]}`);
+        }
+      });
     });
   });
 
@@ -180,6 +226,32 @@ describe('Items API', () => {
         assert.ok(Array.isArray(data.Genres), 'Genres 应该是数组');
         assert.ok(Array.isArray(data.Tags), 'Tags 应该是数组');
         assert.ok(Array.isArray(data.Years), 'Years 应该是数组');
+        assert.ok(Array.isArray(data.OfficialRatings), 'OfficialRatings 应该是数组');
+        assert.ok(Array.isArray(data.Studios), 'Studios 应该是数组');
+        assert.strictEqual(typeof data.IsHD, 'boolean');
+      });
+
+      it('Filters2 的 Genres 应该为空数组（飞牛列表不返回类型）', async () => {
+        const response = await get('/Items/Filters2');
+        assertSuccess(response);
+        const data = response.data!;
+        assert.deepStrictEqual(data.Genres, []);
+        assert.strictEqual(typeof data.IsHD, 'boolean');
+      });
+
+      it('按 Years 过滤时应该只返回对应年份', async () => {
+        if (!movieLibraryId) return;
+
+        const filters = await get(`/Items/Filters?ParentId=${movieLibraryId}`);
+        const year = filters.data?.Years?.[0];
+        if (!year) return;
+
+        const response = await get(`/Items?ParentId=${movieLibraryId}&Years=${year}&Limit=50`);
+        assertSuccess(response);
+        assert.ok(response.data!.Items.length > 0, `${year} 年应该至少有一项`);
+        for (const item of response.data!.Items) {
+          assert.strictEqual(item.ProductionYear, year);
+        }
       });
     });
   });
@@ -212,6 +284,67 @@ describe('Items API', () => {
           assert.strictEqual(item.Type, 'Movie', '应该只返回 Movie 类型');
         }
       });
+
+      it('GroupItems 应该把同一剧集的新增单集合并为一个条目', async () => {
+        const response = await get('/Items/Latest?GroupItems=true&Limit=30');
+        assertSuccess(response);
+
+        const data: any[] = response.data!;
+        assert.ok(data.length <= 30, '返回数量应该不超过 Limit');
+        const seriesIds = data.filter((i) => i.Type === 'Episode' && i.SeriesId).map((i) => i.SeriesId);
+        assert.strictEqual(new Set(seriesIds).size, seriesIds.length, '同一剧集的单集不应该重复出现');
+        for (const item of data.filter((i) => i.Type === 'Series' && i.UserData?.UnplayedItemCount !== undefined)) {
+          assert.ok(item.ChildCount >= 2, '分组条目的 ChildCount 应该是新增集数');
+          assert.ok(item.UserData.UnplayedItemCount <= item.ChildCount);
+        }
+      });
+
+      it('应该支持 IsPlayed=false 与 GroupItems=false', async () => {
+        const response = await get('/Items/Latest?GroupItems=false&IsPlayed=false&Limit=20');
+        assertSuccess(response);
+        for (const item of response.data!) {
+          assert.notStrictEqual(item.UserData?.Played, true, 'IsPlayed=false 时不应该返回已看项目');
+        }
+      });
+    });
+  });
+
+  describe('GET /Search/Hints - 搜索提示', () => {
+    skipIfNoCredentials(() => {
+      it('按完整标题搜索时，该项目应该排在第一位', async () => {
+        if (!movieLibraryId) return;
+
+        const list = await get(`/Items?ParentId=${movieLibraryId}&Limit=1`);
+        const target = list.data?.Items?.[0];
+        if (!target) return;
+
+        const response = await get(`/Search/Hints?SearchTerm=${encodeURIComponent(target.Name)}&Limit=10`);
+        assertSuccess(response);
+        const data = response.data!;
+        assert.ok(Array.isArray(data.SearchHints), 'SearchHints 应该是数组');
+        assert.strictEqual(typeof data.TotalRecordCount, 'number');
+        assert.ok(data.SearchHints.length > 0, '应该至少命中一条');
+
+        const first = data.SearchHints[0];
+        assert.strictEqual(first.Name, target.Name);
+        assert.strictEqual(first.Id, first.ItemId);
+        assert.ok(first.Type, '应该包含 Type');
+        console.log(`  ✓ "${target.Name}" 命中 ${data.TotalRecordCount} 条`);
+      });
+
+      it('IncludeItemTypes 应该限定返回类型', async () => {
+        const response = await get('/Search/Hints?SearchTerm=a&IncludeItemTypes=Series&Limit=20');
+        assertSuccess(response);
+        for (const hint of response.data!.SearchHints) {
+          assert.strictEqual(hint.Type, 'Series');
+        }
+      });
+
+      it('空搜索词应该返回空结果', async () => {
+        const response = await get('/Search/Hints?SearchTerm=');
+        assertSuccess(response);
+        assert.strictEqual(response.data!.TotalRecordCount, 0);
+      });
     });
   });
 
@@ -5,6 +5,7 @@
 
 import { describe, it, before } from 'node:test';
 import assert from 'node:assert';
+import axios from 'axios';
 import { get, post, assertSuccess, assertStatus } from '../lib/client.ts';
 import { login, isLoggedIn, skipIfNoCredentials } from '../lib/auth-helper.ts';
 import { testState, config } from '../config.ts';
@@ -49,14 +50,49 @@ describe('Misc API', () => {
       assert.ok(data.SortOrder, '应该有 SortOrder');
     });
 
-    it('POST /DisplayPreferences/:id 应该接受偏好设置', async () => {
-      const response = await post('/DisplayPreferences/usersettings', {
+    it('未登录时 POST /DisplayPreferences/:id 应该返回 401', async () => {
+      const response = await axios.post(`${config.baseURL}/DisplayPreferences/usersettings`, {
         SortBy: 'SortName',
         SortOrder: 'Ascending',
-      });
-      
+      }, { validateStatus: () => true });
+
+      assert.strictEqual(response.status, 401);
+    });
+
+    it('GET 应该包含首页分区默认值', async () => {
+      const response = await get('/DisplayPreferences/usersettings?client=emby');
+
       assertSuccess(response);
-      assertStatus(response, 204);
+      assert.strictEqual(response.data!.CustomPrefs.homesection0, 'smalllibrarytiles');
+    });
+
+    skipIfNoCredentials(() => {
+      it('已登录时 POST 的偏好应该被持久化', async () => {
+        if (!isLoggedIn()) {
+          await login();
+        }
+
+        const saved = await post('/DisplayPreferences/usersettings?client=emby', {
+          Id: 'usersettings',
+          SortBy: 'DateCreated',
+          SortOrder: 'Descending',
+          ViewType: 'Poster',
+          ScrollDirection: 'Vertical',
+          CustomPrefs: { homesection0: 'latestmedia', homesection6: 'smalllibrarytiles' },
+        });
+        assertStatus(saved, 204);
+
+        const response = await get('/DisplayPreferences/usersettings?client=emby');
+        assertSuccess(response);
+
+        const data = response.data!;
+        assert.strictEqual(data.SortBy, 'DateCreated');
+        assert.strictEqual(data.SortOrder, 'Descending');
+        assert.strictEqual(data.ViewType, 'Poster');
+        assert.strictEqual(data.ScrollDirection, 'Vertical');
+        assert.strictEqual(data.CustomPrefs.homesection0, 'latestmedia');
+        assert.strictEqual(data.CustomPrefs.homesection1, 'resume', '未提交的分区应保留默认值');
+      });
     });
   });
 
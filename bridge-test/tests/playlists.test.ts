@@ -0,0 +1,136 @@
+/**
+ * Playlists API 测试
+ * 桥接层本地播放列表
+ */
+
+import { describe, it, before, after } from 'node:test';
+import assert from 'node:assert';
+import { get, post, del, assertSuccess, assertStatus } from '../lib/client.ts';
+import { login, isLoggedIn, skipIfNoCredentials } from '../lib/auth-helper.ts';
+import { config, testState } from '../config.ts';
+
+describe('Playlists API', () => {
+  let playlistId: string | null = null;
+  let movieIds: string[] = [];
+
+  before(async () => {
+    if (!isLoggedIn() && config.username && config.password) {
+      try {
+        await login();
+      } catch (e) {
+        console.log('  [警告] 登录失败，部分测试将跳过');
+      }
+    }
+
+    if (isLoggedIn()) {
+      const viewsResponse = await get('/UserViews');
+      const movieLib = viewsResponse.data?.Items?.find((item: any) => item.CollectionType === 'movies');
+      if (movieLib) {
+        const itemsResponse = await get(`/Items?ParentId=${movieLib.Id}&Limit=2`);
+        movieIds = (itemsResponse.data?.Items || []).map((item: any) => item.Id);
+      }
+    }
+  });
+
+  after(async () => {
+    if (playlistId) {
+      await del(`/Items/${playlistId}`);
+    }
+  });
+
+  describe('UserViews', () => {
+    skipIfNoCredentials(() => {
+      it('应该包含播放列表媒体库', async () => {
+        const response = await get('/UserViews');
+
+        assertSuccess(response);
+        const view = response.data?.Items?.find((item: any) => item.CollectionType === 'playlists');
+        assert.ok(view, '应该有 CollectionType=playlists 的媒体库');
+      });
+    });
+  });
+
+  describe('POST /Playlists', () => {
+    skipIfNoCredentials(() => {
+      it('应该创建播放列表', async () => {
+        const response = await post('/Playlists', {
+          Name: '电影之夜',
+          Ids: movieIds,
+          UserId: testState.userId,
+          MediaType: 'Video',
+        });
+
+        assertSuccess(response);
+        assert.ok(response.data?.Id, '应该返回播放列表 Id');
+        playlistId = response.data.Id;
+
+        const detail = await get(`/Items/${playlistId}`);
+        assertSuccess(detail);
+        assert.strictEqual(detail.data?.Type, 'Playlist');
+        assert.strictEqual(detail.data?.Name, '电影之夜');
+      });
+
+      it('缺少名称应该返回 400', async () => {
+        const response = await post('/Playlists', { Ids: [] });
+        assertStatus(response, 400);
+      });
+    });
+  });
+
+  describe('GET /Playlists/:id/Items', () => {
+    skipIfNoCredentials(() => {
+      it('应该返回带 PlaylistItemId 的条目', async () => {
+        if (!playlistId) return;
+
+        const response = await get(`/Playlists/${playlistId}/Items`);
+
+        assertSuccess(response);
+        const data = response.data!;
+        assert.strictEqual(data.Items.length, movieIds.length);
+        for (const item of data.Items) {
+          assert.ok(item.PlaylistItemId, '条目应该有 PlaylistItemId');
+        }
+      });
+    });
+  });
+
+  describe('移动与删除条目', () => {
+    skipIfNoCredentials(() => {
+      it('应该移动条目顺序', async () => {
+        if (!playlistId || movieIds.length < 2) return;
+
+        const before = await get(`/Playlists/${playlistId}/Items`);
+        const last = before.data!.Items[before.data!.Items.length - 1];
+
+        const moved = await post(`/Playlists/${playlistId}/Items/${last.PlaylistItemId}/Move/0`);
+        assertStatus(moved, 204);
+
+        const after = await get(`/Playlists/${playlistId}/Items`);
+        assert.strictEqual(after.data!.Items[0].PlaylistItemId, last.PlaylistItemId);
+      });
+
+      it('应该按 EntryIds 删除条目', async () => {
+        if (!playlistId || movieIds.length === 0) return;
+
+        const before = await get(`/Playlists/${playlistId}/Items`);
+        const first = before.data!.Items[0];
+
+        const removed = await del(`/Playlists/${playlistId}/Items?EntryIds=${first.PlaylistItemId}`);
+        assertStatus(removed, 204);
+
+        const after = await get(`/Playlists/${playlistId}/Items`);
+        assert.strictEqual(after.data!.TotalRecordCount, before.data!.TotalRecordCount - 1);
+      });
+
+      it('应该追加条目', async () => {
+        if (!playlistId || movieIds.length === 0) return;
+
+        const added = await post(`/Playlists/${playlistId}/Items?Ids=${movieIds[0]}`);
+        assertStatus(added, 204);
+
+        const after = await get(`/Playlists/${playlistId}/Items`);
+        assert.strictEqual(after.data!.TotalRecordCount, movieIds.length);
+      });
+    });
+  });
+});
@@ -5,7 +5,7 @@
 
 import { describe, it, before } from 'node:test';
 import assert from 'node:assert';
-import { get, assertSuccess, assertStatus } from '../lib/client.ts';
+import { get, post, assertSuccess, assertStatus } from '../lib/client.ts';
 import { login, isLoggedIn, skipIfNoCredentials } from '../lib/auth-helper.ts';
 import { config, testState } from '../config.ts';
 
@@ -64,6 +64,51 @@ describe('Resume API', () => {
         const data = response.data!;
         assert.ok(data.Items.length <= 5, '返回数量应该不超过 Limit');
       });
+
+      it('同一剧集只应该出现一集，且按最近播放时间倒序', async () => {
+        const response = await get('/UserItems/Resume?Limit=50');
+        assertSuccess(response);
+
+        const items: any[] = response.data!.Items;
+        const seriesIds = items.filter((i) => i.Type === 'Episode' && i.SeriesId).map((i) => i.SeriesId);
+        assert.strictEqual(new Set(seriesIds).size, seriesIds.length, '同一剧集不应该重复出现');
+
+        const dates = items.map((i) => i.UserData?.LastPlayedDate).filter(Boolean);
+        for (let i = 1; i < dates.length; i++) {
+          assert.ok(dates[i - 1] >= dates[i], `${dates[i - 1]} 应该晚于 ${dates[i]}`);
+        }
+      });
+
+      it('刚上报播放进度的项目应该排在最前并带有 LastPlayedDate', async () => {
+        const before = await get('/UserItems/Resume?Limit=50');
+        assertSuccess(before);
+        const items: any[] = before.data!.Items;
+        if (items.length < 2) return;
+
+        // 以当前进度重新上报最后一项，不改变飞牛中的播放进度
+        const target = items[items.length - 1];
+        const report = await post('/Sessions/Playing/Progress', {
+          ItemId: target.Id,
+          PositionTicks: target.UserData.PlaybackPositionTicks,
+          IsPaused: true,
+        });
+        assert.ok([200, 204].includes(report.status), `上报进度失败: ${report.status}`);
+
+        const after = await get('/UserItems/Resume?Limit=50');
+        assertSuccess(after);
+        const first = after.data!.Items[0];
+        assert.strictEqual(first.Id, target.Id, '最近上报的项目应该排在最前');
+        assert.ok(first.UserData?.LastPlayedDate, '最近上报的项目应该带有 LastPlayedDate');
+      });
+
+      it('应该支持 ExcludeItemTypes 与 EnableUserData', async () => {
+        const response = await get('/UserItems/Resume?ExcludeItemTypes=Episode&EnableUserData=false&Limit=20');
+        assertSuccess(response);
+        for (const item of response.data!.Items) {
+          assert.notStrictEqual(item.Type, 'Episode');
+          assert.strictEqual(item.UserData, undefined, 'EnableUserData=false 时不应该返回 UserData');
+        }
+      });
     });
   });
 
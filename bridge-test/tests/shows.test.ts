@@ -5,7 +5,7 @@
 
 import { describe, it, before } from 'node:test';
 import assert from 'node:assert';
-import { get, assertSuccess, assertStatus } from '../lib/client.ts';
+import { get, post, del, assertSuccess, assertStatus } from '../lib/client.ts';
 import { login, isLoggedIn, skipIfNoCredentials } from '../lib/auth-helper.ts';
 import { config, testState } from '../config.ts';
 
@@ -150,16 +150,76 @@ describe('Shows API', () => {
         assertSuccess(response);
         
         const data = response.data!;
-        // 所有返回的集应该属于同一个季
-        if (data.Items.length > 0) {
-          const seasonNumber = data.Items[0].ParentIndexNumber;
+        // 所有返回的集应该属于同一个季（在该季播出的特别篇除外）
+        const regular = data.Items.filter((e: any) => e.ParentIndexNumber !== 0);
+        if (regular.length > 0) {
+          const seasonNumber = regular[0].ParentIndexNumber;
           for (const episode of data.Items) {
+            if (episode.ParentIndexNumber === 0 && seasonNumber !== 0) {
+              assert.ok(episode.PremiereDate, '附带的特别篇应该有首播日期（按首播日期判断在该季播出）');
+              continue;
+            }
             assert.strictEqual(episode.ParentIndexNumber, seasonNumber, 
               '所有集的 ParentIndexNumber 应该相同');
           }
         }
       });
 
+      it('应该支持 StartItemId', async () => {
+        if (!testSeriesId) return;
+
+        const all = await get(`/Shows/${testSeriesId}/Episodes`);
+        assertSuccess(all);
+        const items: any[] = all.data!.Items;
+        if (items.length < 2) return;
+
+        const start = items[1];
+        const response = await get(`/Shows/${testSeriesId}/Episodes?StartItemId=${start.Id}&Limit=3`);
+        assertSuccess(response);
+        assert.strictEqual(response.data!.Items[0]?.Id, start.Id, '应该从 StartItemId 指定的集开始');
+        assert.strictEqual(response.data!.TotalRecordCount, items.length - 1);
+      });
+
+      it('正片应该按季号、集号排列，IsMissing=true 只返回缺失集', async () => {
+        if (!testSeriesId) return;
+
+        const response = await get(`/Shows/${testSeriesId}/Episodes?AdjustForSpecials=false`);
+        assertSuccess(response);
+        const regular = response.data!.Items.filter((e: any) => e.ParentIndexNumber > 0);
+        for (let i = 1; i < regular.length; i++) {
+          const [a, b] = [regular[i - 1], regular[i]];
+          assert.ok(a.ParentIndexNumber < b.ParentIndexNumber
+            || (a.ParentIndexNumber === b.ParentIndexNumber && a.IndexNumber <= b.IndexNumber),
+            `S${a.ParentIndexNumber}E${a.IndexNumber} 应该排在 S${b.ParentIndexNumber}E${b.IndexNumber} 之前`);
+        }
+        for (const episode of response.data!.Items) {
+          assert.notStrictEqual(episode.LocationType, 'Virtual', '默认不应该返回缺失集');
+        }
+
+        const missing = await get(`/Shows/${testSeriesId}/Episodes?IsMissing=true`);
+        assertSuccess(missing);
+        for (const episode of missing.data!.Items) {
+          assert.strictEqual(episode.LocationType, 'Virtual', 'IsMissing=true 应该只返回缺失集');
+          assert.strictEqual(typeof episode.IndexNumber, 'number');
+        }
+        console.log(`  ✓ 缺失集: ${missing.data!.TotalRecordCount} 个`);
+      });
+
+      it('IsVirtualUnaired=true 应该返回空列表，false 不影响结果', async () => {
+        if (!testSeriesId) return;
+
+        const unaired = await get(`/Shows/${testSeriesId}/Episodes?IsVirtualUnaired=true`);
+        assertSuccess(unaired);
+        assert.strictEqual(unaired.data!.TotalRecordCount, 0);
+        assert.strictEqual(unaired.data!.Items.length, 0);
+
+        const all = await get(`/Shows/${testSeriesId}/Episodes`);
+        const aired = await get(`/Shows/${testSeriesId}/Episodes?IsVirtualUnaired=false`);
+        assertSuccess(all);
+        assertSuccess(aired);
+        assert.strictEqual(aired.data!.TotalRecordCount, all.data!.TotalRecordCount);
+      });
+
       it('应该支持分页', async () => {
         if (!testSeriesId) return;
 
@@ -195,6 +255,62 @@ describe('Shows API', () => {
     });
   });
 
+  describe('MediaSegments - 片头片尾', () => {
+    skipIfNoCredentials(() => {
+      it('季上保存的片段应该对单集生效', async () => {
+        if (!testSeriesId) {
+          console.log('  [SKIP] 未找到测试剧集');
+          return;
+        }
+
+        const episodes = await get(`/Shows/${testSeriesId}/Episodes?Limit=1`);
+        const episode = episodes.data?.Items?.[0];
+        if (!episode?.SeasonId) {
+          console.log('  [SKIP] 未找到单集');
+          return;
+        }
+
+        const saved = await post(`/MediaSegments/${episode.SeasonId}`, [
+          { Type: 'intro', StartTicks: 300000000, EndTicks: 1200000000 },
+        ]);
+        const me = await get('/Users/Me');
+        if (!me.data?.Policy?.IsAdministrator) {
+          // 只有管理员可以编辑片段
+          assertStatus(saved, 403);
+          assertStatus(await del(`/MediaSegments/${episode.SeasonId}`), 403);
+          return;
+        }
+        assertStatus(saved, 204);
+
+        const response = await get(`/MediaSegments/${episode.Id}?includeSegmentTypes=Intro`);
+        assertStatus(response, 200);
+        const intro = response.data.Items.find((s: any) => s.Type === 'Intro');
+        assert.ok(intro, '应该返回 Intro 片段');
+        assert.strictEqual(intro.StartTicks, 300000000);
+        assert.strictEqual(intro.EndTicks, 1200000000);
+        assert.strictEqual(intro.ItemId, episode.Id);
+
+        const outroOnly = await get(`/MediaSegments/${episode.Id}?includeSegmentTypes=Outro`);
+        assert.ok(outroOnly.data.Items.every((s: any) => s.Type === 'Outro'), '应该按类型过滤');
+
+        assertStatus(await del(`/MediaSegments/${episode.SeasonId}`), 204);
+      });
+
+      it('应该拒绝无效的片段', async () => {
+        if (!testSeriesId) {
+          console.log('  [SKIP] 未找到测试剧集');
+          return;
+        }
+
+        const me = await get('/Users/Me');
+        const response = await post(`/MediaSegments/${testSeriesId}`, [
+          { Type: 'Intro', StartTicks: 100, EndTicks: 50 },
+        ]);
+        assertStatus(response, me.data?.Policy?.IsAdministrator ? 400 : 403);
+      });
+    });
+  });
+
   describe('GET /Shows/NextUp', () => {
     skipIfNoCredentials(() => {
       it('应该返回空列表（暂未实现）', async () => {
@@ -5,7 +5,8 @@
 
 import { describe, it, before } from 'node:test';
 import assert from 'node:assert';
-import { get, post, head, assertSuccess, assertStatus } from '../lib/client.ts';
+import axios from 'axios';
+import { get, post, del, head, assertSuccess, assertStatus } from '../lib/client.ts';
 import { login, isLoggedIn, skipIfNoCredentials } from '../lib/auth-helper.ts';
 import { config, testState } from '../config.ts';
 
@@ -111,6 +112,83 @@ describe('Stream API', () => {
         }
       });
 
+      it('视频流应该包含 VideoRange，项目详情应该包含尺寸与 VideoType', async () => {
+        if (!testItemId) return;
+
+        const response = await post(`/Items/${testItemId}/PlaybackInfo`, {
+          UserId: testState.userId,
+        });
+        assertSuccess(response);
+        const video = response.data!.MediaSources?.[0]?.MediaStreams?.find((s: any) => s.Type === 'Video');
+        if (!video) return;
+        assert.ok(['SDR', 'HDR'].includes(video.VideoRange), `VideoRange 应该是 SDR/HDR，实际为 ${video.VideoRange}`);
+        assert.ok(video.VideoRangeType, '应该包含 VideoRangeType');
+
+        // stream/list 已缓存，详情中应该带上元数据
+        const item = await get(`/Items/${testItemId}`);
+        assertSuccess(item);
+        assert.strictEqual(typeof item.data!.VideoType, 'string');
+        if (video.Height > 0) {
+          // 多版本时取分辨率最高的版本
+          assert.ok(item.data!.Height >= video.Height, `Height ${item.data!.Height} 应该不小于 ${video.Height}`);
+          assert.strictEqual(typeof item.data!.IsHD, 'boolean');
+        }
+      });
+
+      it('仅支持 SDR 的客户端播放 HDR 片源时应该转码为 SDR', async () => {
+        if (!testItemId) return;
+
+        const sdrOnly = ['h264', 'hevc', 'av1', 'vp9'].map((codec) => ({
+          Type: 'Video',
+          Codec: codec,
+          Conditions: [{ Condition: 'EqualsAny', Property: 'VideoRangeType', Value: 'SDR', IsRequired: false }],
+        }));
+        const source = (await post(`/Items/${testItemId}/PlaybackInfo`, { UserId: testState.userId })).data?.MediaSources?.[0];
+        const response = await post(`/Items/${testItemId}/PlaybackInfo`, {
+          UserId: testState.userId,
+          DeviceProfile: { Name: 'SDR Only', CodecProfiles: sdrOnly },
+        });
+        assertSuccess(response);
+        const transcoded = response.data!.MediaSources?.[0];
+        if (!source || !transcoded) return;
+
+        const sourceVideo = source.MediaStreams.find((s: any) => s.Type === 'Video');
+        const video = transcoded.MediaStreams.find((s: any) => s.Type === 'Video');
+        if (sourceVideo?.VideoRange === 'HDR') {
+          assert.strictEqual(transcoded.SupportsDirectStream, false);
+          assert.deepStrictEqual(transcoded.TranscodeReasons, ['VideoRangeTypeNotSupported']);
+          assert.ok(transcoded.TranscodingUrl.includes('TranscodeReasons=VideoRangeTypeNotSupported'));
+          // SDR 转码使用独立的会话，不替换其他客户端的原动态范围会话
+          assert.notStrictEqual(transcoded.TranscodingUrl.split('?')[0], source.TranscodingUrl.split('?')[0]);
+          assert.strictEqual(video.VideoRange, 'SDR');
+          console.log(`  ✓ ${sourceVideo.VideoRangeType} → SDR`);
+        } else {
+          assert.strictEqual(transcoded.TranscodeReasons, undefined, 'SDR 片源不应该因动态范围转码');
+        }
+      });
+
+      it('偏好语言应该优先于默认音轨标记', async () => {
+        if (!testItemId) return;
+
+        const source = (await post(`/Items/${testItemId}/PlaybackInfo`, { UserId: testState.userId })).data?.MediaSources?.[0];
+        const audio = (source?.MediaStreams ?? []).filter((s: any) => s.Type === 'Audio');
+        const target = audio.find((s: any) => !s.IsDefault && s.Language);
+        if (!target) return;
+
+        await post(`/Users/${testState.userId}/Configuration`, {
+          AudioLanguagePreference: target.Language,
+          PlayDefaultAudioTrack: true,
+        });
+        try {
+          const response = await post(`/Items/${testItemId}/PlaybackInfo`, { UserId: testState.userId });
+          assertSuccess(response);
+          const selected = audio.find((s: any) => s.Index === response.data!.MediaSources[0].DefaultAudioStreamIndex);
+          assert.strictEqual(selected?.Language, target.Language, '应该选中偏好语言的音轨');
+        } finally {
+          await post(`/Users/${testState.userId}/Configuration`, { AudioLanguagePreference: '' });
+        }
+      });
+
       it('应该支持 DirectStream 判断', async () => {
         if (!testItemId) return;
 
@@ -171,12 +249,91 @@ describe('Stream API', () => {
         });
         
         // 应该返回 206 Partial Content 或 200
-        assert.ok([200, 206].includes(response.status), 
+        assert.ok([200, 206].includes(response.status),
           `期望 200 或 206，实际得到 ${response.status}`);
       });
     });
   });
 
+  describe('GET /Videos/:itemId/stream.ts?static=false - 渐进式转码流', () => {
+    skipIfNoCredentials(() => {
+      it('应该从 StartTimeTicks 位置返回 MPEG-TS 流', async () => {
+        if (!testItemId || !testMediaSourceId) return;
+
+        const response = await get(
+          `/Videos/${testItemId}/stream.ts?static=false&MediaSourceId=${testMediaSourceId}&StartTimeTicks=600000000`,
+          { responseType: 'stream', timeout: 60000 },
+        );
+        (response.data as any)?.destroy?.();
+
+        // 转码启动失败时返回 502
+        assert.ok([200, 502].includes(response.status),
+          `期望 200 或 502，实际得到 ${response.status}`);
+      });
+
+      it('Container=mp4 应该返回重封装后的 MP4 流', async () => {
+        if (!testItemId || !testMediaSourceId) return;
+
+        const response = await get(
+          `/Videos/${testItemId}/stream?static=false&Container=mp4&MediaSourceId=${testMediaSourceId}`,
+          { responseType: 'stream', timeout: 60000 },
+        );
+        (response.data as any)?.destroy?.();
+
+        if (response.status === 200) {
+          assert.strictEqual(response.headers['content-type'], 'video/mp4');
+        } else {
+          assert.strictEqual(response.status, 502, `期望 200 或 502，实际得到 ${response.status}`);
+        }
+      });
+
+      it('不支持的容器应该返回 400', async () => {
+        if (!testItemId || !testMediaSourceId) return;
+
+        const response = await get(
+          `/Videos/${testItemId}/stream?static=false&Container=mkv&MediaSourceId=${testMediaSourceId}`,
+        );
+        assert.strictEqual(response.status, 400);
+      });
+    });
+  });
+
+  describe('GET /Items/:itemId/Download', () => {
+    skipIfNoCredentials(() => {
+      it('应该返回原始文件', async () => {
+        if (!testItemId) return;
+
+        const item = await get(`/Items/${testItemId}`);
+        assert.strictEqual(item.data?.CanDownload, true, '电影应该可下载');
+
+        const response = await get(`/Items/${testItemId}/Download`, {
+          headers: { 'Range': 'bytes=0-1023' },
+          responseType: 'arraybuffer',
+        });
+
+        assert.ok([200, 206].includes(response.status),
+          `期望 200 或 206，实际得到 ${response.status}`);
+      });
+
+      it('static=false 应该以附件形式返回转码后的 MPEG-TS', async () => {
+        if (!testItemId) return;
+
+        const response = await get(`/Items/${testItemId}/Download?static=false`, {
+          responseType: 'stream',
+          timeout: 60000,
+        });
+        (response.data as any)?.destroy?.();
+
+        if (response.status === 200) {
+          assert.strictEqual(response.headers['content-type'], 'video/mp2t');
+          assert.match(String(response.headers['content-disposition']), /^attachment; filename=".*\.ts"/);
+        } else {
+          assert.strictEqual(response.status, 502, `期望 200 或 502，实际得到 ${response.status}`);
+        }
+      });
+    });
+  });
+
   describe('GET /Videos/:itemId/stream.container', () => {
     skipIfNoCredentials(() => {
       it('应该支持带扩展名的流请求（Range: 头部 16KB）', async () => {
@@ -323,6 +480,46 @@ describe('Stream API', () => {
     });
   });
 
+  describe('Trickplay 缩略图', () => {
+    skipIfNoCredentials(() => {
+      it('Trickplay 信息与 tiles.m3u8 应该一致', async () => {
+        if (!testItemId || !testMediaSourceId) {
+          console.log('  [SKIP] 未找到测试项目或媒体源');
+          return;
+        }
+
+        const item = await get(`/Users/${testState.userId}/Items/${testItemId}`);
+        assertStatus(item, 200);
+        const resolutions = item.data.Trickplay?.[testMediaSourceId];
+        const response = await get(`/Videos/${testItemId}/Trickplay/320/tiles.m3u8?MediaSourceId=${testMediaSourceId}`);
+
+        if (!resolutions?.['320']) {
+          // 尚未生成（未开启 ENABLE_TRICKPLAY、后台任务进行中或没有 ffmpeg）
+          assertStatus(response, 404);
+          console.log('  ⚠ 缩略图尚未生成');
+          return;
+        }
+
+        assertStatus(response, 200);
+        const playlist = String(response.data);
+        assert.ok(playlist.includes('#EXT-X-IMAGES-ONLY'), '应该是图片播放列表');
+        assert.ok(!playlist.includes('api_key='), '拼图 URL 不应该包含访问令牌');
+        const tile = await get(`/Videos/${testItemId}/Trickplay/320/0.jpg?MediaSourceId=${testMediaSourceId}`, {
+          responseType: 'arraybuffer',
+        });
+        assertStatus(tile, 200);
+
+        // 播放列表中的拼图 URL 凭短期签名标签即可匿名读取，不带标签时返回 401
+        const tileUrl = playlist.split('\n').find((line) => line.startsWith('0.jpg?'))!;
+        const base = `${config.baseURL}/Videos/${testItemId}/Trickplay/320/`;
+        const signed = await axios.get(base + tileUrl, { responseType: 'arraybuffer', validateStatus: () => true });
+        assert.strictEqual(signed.status, 200);
+        const unsigned = await axios.get(`${base}0.jpg?MediaSourceId=${testMediaSourceId}`, { validateStatus: () => true });
+        assert.strictEqual(unsigned.status, 401);
+      });
+    });
+  });
+
   describe('字幕流', () => {
     skipIfNoCredentials(() => {
       it('应该支持字幕请求', async () => {
@@ -338,6 +535,130 @@ describe('Stream API', () => {
         assert.ok([200, 404].includes(response.status), 
           `期望 200 或 404，实际得到 ${response.status}`);
       });
+
+      it('文本字幕应该转换为请求的格式', async () => {
+        if (!testItemId || !testMediaSourceId) {
+          console.log('  [SKIP] 未找到测试项目或媒体源');
+          return;
+        }
+
+        const infoResponse = await post(`/Items/${testItemId}/PlaybackInfo`, {});
+        const streams: any[] = infoResponse.data?.MediaSources?.[0]?.MediaStreams ?? [];
+        const subtitle = streams.find((s) => s.Type === 'Subtitle' && s.IsTextSubtitleStream);
+        if (!subtitle) {
+          console.log('  [SKIP] 未找到文本字幕');
+          return;
+        }
+
+        const vtt = await get(`/Videos/${testItemId}/${testMediaSourceId}/Subtitles/${subtitle.Index}/Stream.vtt`);
+        if (vtt.status !== 200) {
+          console.log(`  ⚠ 字幕返回 ${vtt.status}`);
+          return;
+        }
+        assert.ok(String(vtt.data).startsWith('WEBVTT'), 'VTT 输出应该以 WEBVTT 开头');
+
+        const json = await get(`/Videos/${testItemId}/${testMediaSourceId}/Subtitles/${subtitle.Index}/Stream.js`);
+        assertStatus(json, 200);
+        assert.ok(Array.isArray(json.data.TrackEvents), '应该返回 TrackEvents 数组');
+        console.log(`  ✓ 字幕条目: ${json.data.TrackEvents.length}`);
+      });
+
+      it('带起始位置的字幕应该平移时间轴', async () => {
+        if (!testItemId || !testMediaSourceId) {
+          console.log('  [SKIP] 未找到测试项目或媒体源');
+          return;
+        }
+
+        const infoResponse = await post(`/Items/${testItemId}/PlaybackInfo`, {});
+        const streams: any[] = infoResponse.data?.MediaSources?.[0]?.MediaStreams ?? [];
+        const subtitle = streams.find((s) => s.Type === 'Subtitle' && s.IsTextSubtitleStream);
+        if (!subtitle) {
+          console.log('  [SKIP] 未找到文本字幕');
+          return;
+        }
+
+        const base = `/Videos/${testItemId}/${testMediaSourceId}/Subtitles/${subtitle.Index}`;
+        const startTicks = 6000000000; // 10 分钟
+        const full = await get(`${base}/Stream.js`);
+        const shifted = await get(`${base}/${startTicks}/Stream.js`);
+        const copied = await get(`${base}/${startTicks}/Stream.js?CopyTimestamps=true`);
+        if (full.status !== 200) {
+          console.log(`  ⚠ 字幕返回 ${full.status}`);
+          return;
+        }
+        assertStatus(shifted, 200);
+        assertStatus(copied, 200);
+
+        const fullEvents: any[] = full.data.TrackEvents;
+        const shiftedEvents: any[] = shifted.data.TrackEvents;
+        const expected = fullEvents.filter((e) => e.EndPositionTicks > startTicks).length;
+        assert.strictEqual(shiftedEvents.length, expected, '应该只保留起始位置之后的条目');
+        assert.ok(shiftedEvents.every((e) => e.StartPositionTicks >= 0), '平移后的时间不应为负');
+        assert.strictEqual(copied.data.TrackEvents.length, fullEvents.length, 'CopyTimestamps 时不应平移');
+
+        const vtt = await get(`${base}/${startTicks}/Stream.vtt?AddVttTimeMap=true`);
+        assertStatus(vtt, 200);
+        assert.ok(String(vtt.data).includes('X-TIMESTAMP-MAP='), '应该包含 X-TIMESTAMP-MAP');
+      });
+
+      it('应该支持上传和删除外挂字幕', async () => {
+        if (!testItemId || !testMediaSourceId) {
+          console.log('  [SKIP] 未找到测试项目或媒体源');
+          return;
+        }
+
+        const srt = '1\n00:00:01,000 --> 00:00:02,000\n桥接测试字幕\n';
+        const upload = await post(`/Videos/${testItemId}/Subtitles`, {
+          Language: 'chi',
+          Format: 'srt',
+          IsForced: false,
+          Data: Buffer.from(srt, 'utf8').toString('base64'),
+        });
+        const me = await get('/Users/Me');
+        if (!me.data?.Policy?.EnableSubtitleManagement) {
+          // 非管理员没有字幕管理权限
+          assertStatus(upload, 403);
+          assertStatus(await del(`/Videos/${testItemId}/Subtitles/0`), 403);
+          return;
+        }
+        assertStatus(upload, 204);
+
+        const infoResponse = await post(`/Items/${testItemId}/PlaybackInfo`, {});
+        const source = infoResponse.data.MediaSources.find((m: any) => m.Id === testMediaSourceId)
+          ?? infoResponse.data.MediaSources[0];
+        const external = source.MediaStreams
+          .filter((s: any) => s.Type === 'Subtitle' && s.IsExternal && s.Codec === 'srt' && s.Language === 'chi');
+        assert.ok(external.length > 0, '上传的字幕应该出现在 MediaStreams 中');
+        const uploaded = external[external.length - 1];
+
+        const vtt = await get(`/Videos/${testItemId}/${source.Id}/Subtitles/${uploaded.Index}/Stream.vtt`);
+        assertStatus(vtt, 200);
+        assert.ok(String(vtt.data).includes('桥接测试字幕'), '应该返回上传的字幕内容');
+
+        // 指定不存在的版本时返回 400，不删除任何字幕
+        assertStatus(await del(`/Videos/${testItemId}/Subtitles/${uploaded.Index}?MediaSourceId=unknown`), 400);
+
+        const removed = await del(`/Videos/${testItemId}/Subtitles/${uploaded.Index}?MediaSourceId=${source.Id}`);
+        assertStatus(removed, 204);
+        const again = await del(`/Videos/${testItemId}/Subtitles/${uploaded.Index}?MediaSourceId=${source.Id}`);
+        assertStatus(again, 404);
+      });
+
+      it('应该拒绝不支持的字幕格式', async () => {
+        if (!testItemId) {
+          console.log('  [SKIP] 未找到测试项目');
+          return;
+        }
+        const me = await get('/Users/Me');
+        if (!me.data?.Policy?.EnableSubtitleManagement) return;
+
+        const response = await post(`/Videos/${testItemId}/Subtitles`, {
+          Language: 'eng',
+          Format: 'exe',
+          Data: Buffer.from('x').toString('base64'),
+        });
+        assertStatus(response, 400);
+      });
     });
   });
 });